@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use polynomial_evaluation::pem::{
+    types::{Addr, Const, Reg},
+    ExprWrapper, Instruction, Latencies, Machine,
+};
+
+/// Pad the program with empty instructions so the result of an operation
+/// issued by the last instruction is available to the next one
+fn wait(program: &mut Vec<Instruction>, latency: usize) {
+    program.extend((1..latency).map(|_| Instruction::new()));
+}
+
+/// Memory with addresses `0..count` initialized with symbolic variables
+/// `X0`, `X1`, ...
+fn symbolic_memory(count: u32) -> HashMap<Addr, ExprWrapper> {
+    HashMap::from_iter((0..count).map(|i| {
+        (
+            Addr(i),
+            ExprWrapper::from_symbolic_variable(format!("X{i}")),
+        )
+    }))
+}
+
+/// Evaluate a polynomial of the given degree in `X0` using Horner's method,
+/// e.g. `((X0 * 1 + 2) * X0 + 3) ...`
+fn horner_chain(degree: u32, latencies: &Latencies) -> Vec<Instruction> {
+    let mut program = Vec::new();
+    program.push(
+        Instruction::new()
+            .with_ldi(Reg(0), Const(1))
+            .with_ldr(Reg(1), Addr(0)),
+    );
+    wait(&mut program, latencies.ldr);
+
+    for coefficient in 2..=degree + 1 {
+        program.push(
+            Instruction::new()
+                .with_ldi(Reg(2), Const(coefficient))
+                .with_mul(Reg(0), Reg(0), Reg(1)),
+        );
+        wait(&mut program, latencies.mul);
+        program.push(Instruction::new().with_add(Reg(0), Reg(0), Reg(2)));
+        wait(&mut program, latencies.add);
+    }
+
+    program
+}
+
+/// Scale up the long polynomial example into a product of `(Xi + i)` terms
+fn scaled_long_polynomial(terms: u32, latencies: &Latencies) -> Vec<Instruction> {
+    let mut program = Vec::new();
+    program.push(Instruction::new().with_ldi(Reg(0), Const(1)));
+    wait(&mut program, latencies.ldi);
+
+    for i in 0..terms {
+        program.push(
+            Instruction::new()
+                .with_ldi(Reg(2), Const(i))
+                .with_ldr(Reg(1), Addr(i)),
+        );
+        wait(&mut program, latencies.ldr);
+        program.push(Instruction::new().with_add(Reg(1), Reg(1), Reg(2)));
+        wait(&mut program, latencies.add);
+        program.push(Instruction::new().with_mul(Reg(0), Reg(0), Reg(1)));
+        wait(&mut program, latencies.mul);
+    }
+
+    program
+}
+
+/// Copy memory addresses `0..count` to `count..2 * count`, issuing a load and
+/// a store every cycle across rotating registers
+fn dense_memory_batch(count: u32, latencies: &Latencies) -> Vec<Instruction> {
+    let reg = |i: u32| Reg(1 + i % 6);
+    let ldr = latencies.ldr as u32;
+    let mut program = Vec::from([Instruction::new().with_ldi(Reg(0), Const(0))]);
+
+    for i in 0..count + ldr {
+        let mut instruction = Instruction::new();
+        if i < count {
+            instruction = instruction.with_ldr(reg(i), Addr(i));
+        }
+        if let Some(j) = i.checked_sub(ldr) {
+            instruction = instruction.with_str(reg(j), Addr(count + j));
+        }
+        program.push(instruction);
+    }
+
+    program
+}
+
+fn bench_horner_chain(c: &mut Criterion) {
+    let memory = symbolic_memory(1);
+    let program = horner_chain(64, &Latencies::default());
+    c.bench_function("horner_chain_64", |b| {
+        b.iter(|| {
+            let mut machine = Machine::new(memory.clone());
+            machine.compute(black_box(&program)).unwrap().strong_eval()
+        })
+    });
+}
+
+fn bench_scaled_long_polynomial(c: &mut Criterion) {
+    let memory = symbolic_memory(256);
+    let program = scaled_long_polynomial(256, &Latencies::default());
+    c.bench_function("scaled_long_polynomial_256", |b| {
+        b.iter(|| {
+            let mut machine = Machine::new(memory.clone());
+            machine.compute(black_box(&program)).unwrap().strong_eval()
+        })
+    });
+}
+
+fn bench_dense_memory_batch(c: &mut Criterion) {
+    let memory = symbolic_memory(1024);
+    let program = dense_memory_batch(1024, &Latencies::default());
+    c.bench_function("dense_memory_batch_1024", |b| {
+        b.iter(|| {
+            let mut machine = Machine::new(memory.clone());
+            machine.compute(black_box(&program)).unwrap().weak_eval()
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_horner_chain,
+    bench_scaled_long_polynomial,
+    bench_dense_memory_batch
+);
+criterion_main!(benches);
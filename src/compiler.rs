@@ -0,0 +1,522 @@
+//! Compile infix expressions into PEM programs
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::pem::{
+    types::{Addr, Const, Reg},
+    Instruction, Latencies, OpKind, REGISTER_COUNT,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum CompileError {
+    #[error("Unexpected character '{found}' at position {position}")]
+    UnexpectedCharacter { found: char, position: usize },
+    #[error("Unexpected `{token}` at position {position}, expected {expected}")]
+    UnexpectedToken {
+        token: String,
+        position: usize,
+        expected: &'static str,
+    },
+    #[error("Unexpected end of expression, expected {expected}")]
+    UnexpectedEnd { expected: &'static str },
+    #[error("Constant {token} at position {position} does not fit in 32 bits")]
+    ConstantOutOfRange { token: String, position: usize },
+    #[error("Symbolic variable `{0}` has no memory address")]
+    UnknownVariable(String),
+}
+
+/// Token of an infix expression
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Const(u32),
+    Variable(String),
+    Op(char),
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Const(constant) => write!(f, "{}", constant),
+            Self::Variable(name) => write!(f, "{}", name),
+            Self::Op(op) => write!(f, "{}", op),
+            Self::Open => write!(f, "("),
+            Self::Close => write!(f, ")"),
+        }
+    }
+}
+
+/// Split an infix expression into tokens with their character positions
+///
+/// # Arguments
+/// * `expr` - infix expression
+fn tokenize(expr: &str) -> Result<Vec<(Token, usize)>, CompileError> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        let token =
+            match c {
+                c if c.is_whitespace() => continue,
+                '+' | '-' | '*' => Token::Op(c),
+                '(' => Token::Open,
+                ')' => Token::Close,
+                c if c.is_ascii_alphanumeric() || c == '_' => {
+                    let mut token = c.to_string();
+                    while let Some((_, c)) =
+                        chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+                    {
+                        token.push(c);
+                    }
+                    if c.is_ascii_digit() {
+                        Token::Const(token.parse().map_err(|_| {
+                            CompileError::ConstantOutOfRange {
+                                token: token.clone(),
+                                position,
+                            }
+                        })?)
+                    } else {
+                        Token::Variable(token)
+                    }
+                }
+                found => return Err(CompileError::UnexpectedCharacter { found, position }),
+            };
+        tokens.push((token, position));
+    }
+    Ok(tokens)
+}
+
+/// Expression tree of an infix expression
+#[derive(Debug)]
+enum Ast {
+    Const(u32),
+    Variable(String),
+    Binary(OpKind, Box<Ast>, Box<Ast>),
+}
+
+/// Recursive descent parser for infix expressions
+///
+/// ```text
+/// expr   := term (("+" | "-") term)*
+/// term   := factor ("*" factor)*
+/// factor := constant | variable | "(" expr ")"
+/// ```
+struct Parser {
+    /// Tokens with their character positions
+    tokens: Vec<(Token, usize)>,
+    /// Index of the next unparsed token
+    next: usize,
+}
+
+impl Parser {
+    /// Consume the next token if it is one of the operators `ops`
+    fn next_op(&mut self, ops: &[char]) -> Option<char> {
+        match self.tokens.get(self.next) {
+            Some((Token::Op(op), _)) if ops.contains(op) => {
+                self.next += 1;
+                Some(*op)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse a whole expression, rejecting trailing tokens
+    fn parse(mut self) -> Result<Ast, CompileError> {
+        let ast = self.parse_expr()?;
+        match self.tokens.get(self.next) {
+            None => Ok(ast),
+            Some((token, position)) => Err(CompileError::UnexpectedToken {
+                token: token.to_string(),
+                position: *position,
+                expected: "operator",
+            }),
+        }
+    }
+
+    fn parse_expr(&mut self) -> Result<Ast, CompileError> {
+        let mut lhs = self.parse_term()?;
+        while let Some(op) = self.next_op(&['+', '-']) {
+            let kind = match op {
+                '+' => OpKind::Add,
+                _ => OpKind::Sub,
+            };
+            lhs = Ast::Binary(kind, Box::new(lhs), Box::new(self.parse_term()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_term(&mut self) -> Result<Ast, CompileError> {
+        let mut lhs = self.parse_factor()?;
+        while self.next_op(&['*']).is_some() {
+            lhs = Ast::Binary(OpKind::Mul, Box::new(lhs), Box::new(self.parse_factor()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_factor(&mut self) -> Result<Ast, CompileError> {
+        const EXPECTED: &str = "constant, variable or '('";
+        let Some((token, position)) = self.tokens.get(self.next).cloned() else {
+            return Err(CompileError::UnexpectedEnd { expected: EXPECTED });
+        };
+        self.next += 1;
+        match token {
+            Token::Const(constant) => Ok(Ast::Const(constant)),
+            Token::Variable(name) => Ok(Ast::Variable(name)),
+            Token::Open => {
+                let ast = self.parse_expr()?;
+                match self.tokens.get(self.next) {
+                    Some((Token::Close, _)) => {
+                        self.next += 1;
+                        Ok(ast)
+                    }
+                    Some((token, position)) => Err(CompileError::UnexpectedToken {
+                        token: token.to_string(),
+                        position: *position,
+                        expected: "')'",
+                    }),
+                    None => Err(CompileError::UnexpectedEnd { expected: "')'" }),
+                }
+            }
+            token => Err(CompileError::UnexpectedToken {
+                token: token.to_string(),
+                position,
+                expected: EXPECTED,
+            }),
+        }
+    }
+}
+
+/// Location of an intermediate value
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    Register(Reg),
+    Spilled(Addr),
+    /// Consumed by the operation using it
+    Consumed,
+}
+
+/// Code generator emitting each operation in its own instruction, followed by
+/// empty instructions until it completes
+struct CodeGen<'a> {
+    /// Memory address of each symbolic variable
+    var_addrs: &'a HashMap<String, Addr>,
+    latencies: &'a Latencies,
+    program: Vec<Instruction>,
+    /// Location of each intermediate value
+    values: Vec<Slot>,
+    /// Registers not holding a live value
+    free: Vec<Reg>,
+    /// Next memory address to spill to, past every variable address
+    next_spill: u32,
+}
+
+impl CodeGen<'_> {
+    /// Emit an instruction and wait for it to complete
+    fn emit(&mut self, instruction: Instruction) {
+        let latency = instruction
+            .op_kinds()
+            .into_iter()
+            .map(|op| self.latencies.of(op))
+            .max()
+            .unwrap_or(1);
+        self.program.push(instruction);
+        self.program
+            .extend((1..latency).map(|_| Instruction::new()));
+    }
+
+    /// Get a free register, spilling the oldest live value not in `pinned` to
+    /// memory if every register is in use
+    fn alloc(&mut self, pinned: &[Reg]) -> Reg {
+        if let Some(reg) = self.free.pop() {
+            return reg;
+        }
+
+        let (value, reg) = self
+            .values
+            .iter()
+            .enumerate()
+            .find_map(|(value, slot)| match slot {
+                Slot::Register(reg) if !pinned.contains(reg) => Some((value, *reg)),
+                _ => None,
+            })
+            .expect("At most two registers are pinned at once");
+        let addr = Addr(self.next_spill);
+        self.next_spill += 1;
+        self.emit(Instruction::new().with_str(reg, addr));
+        self.values[value] = Slot::Spilled(addr);
+        reg
+    }
+
+    /// Get the register holding a value, loading it back if it was spilled
+    fn load(&mut self, value: usize, pinned: &[Reg]) -> Reg {
+        match self.values[value] {
+            Slot::Register(reg) => reg,
+            Slot::Spilled(addr) => {
+                let reg = self.alloc(pinned);
+                self.emit(Instruction::new().with_ldr(reg, addr));
+                self.values[value] = Slot::Register(reg);
+                reg
+            }
+            Slot::Consumed => unreachable!("Each value is used once"),
+        }
+    }
+
+    /// Emit the instructions computing an expression tree
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - index of the value holding the result
+    /// * `Err(CompileError::UnknownVariable)` - if a variable has no address
+    fn generate(&mut self, ast: &Ast) -> Result<usize, CompileError> {
+        let slot = match ast {
+            Ast::Const(constant) => {
+                let reg = self.alloc(&[]);
+                self.emit(Instruction::new().with_ldi(reg, Const(*constant)));
+                Slot::Register(reg)
+            }
+            Ast::Variable(name) => {
+                let addr = *self
+                    .var_addrs
+                    .get(name)
+                    .ok_or_else(|| CompileError::UnknownVariable(name.clone()))?;
+                let reg = self.alloc(&[]);
+                self.emit(Instruction::new().with_ldr(reg, addr));
+                Slot::Register(reg)
+            }
+            Ast::Binary(op, lhs, rhs) => {
+                let lhs = self.generate(lhs)?;
+                let rhs = self.generate(rhs)?;
+                let rhs_reg = self.load(rhs, &[]);
+                let lhs_reg = self.load(lhs, &[rhs_reg]);
+                let instruction = match op {
+                    // `add dst src1 src2` is evaluated as `dst = src2 + src1`
+                    OpKind::Add => Instruction::new().with_add(lhs_reg, rhs_reg, lhs_reg),
+                    OpKind::Sub => Instruction::new().with_sub(lhs_reg, lhs_reg, rhs_reg),
+                    _ => Instruction::new().with_mul(lhs_reg, lhs_reg, rhs_reg),
+                };
+                self.emit(instruction);
+                self.values[lhs] = Slot::Consumed;
+                self.values[rhs] = Slot::Consumed;
+                self.free.push(rhs_reg);
+                Slot::Register(lhs_reg)
+            }
+        };
+        self.values.push(slot);
+        Ok(self.values.len() - 1)
+    }
+}
+
+/// Compile an infix expression with `+`, `-`, `*` and parentheses into a
+/// program leaving its value in `Reg(0)`, with the default `Latencies`
+///
+/// # Arguments
+/// * `expr` - infix expression of numeric constants and symbolic variables
+/// * `var_addrs` - memory address each symbolic variable is loaded from
+///
+/// # Returns
+/// * `Ok(Vec<Instruction>)` - program
+/// * `Err(CompileError)` - see `compile_with_latencies()`
+pub fn compile(
+    expr: &str,
+    var_addrs: &HashMap<String, Addr>,
+) -> Result<Vec<Instruction>, CompileError> {
+    compile_with_latencies(expr, var_addrs, &Latencies::default())
+}
+
+/// Compile an infix expression with `+`, `-`, `*` and parentheses into a
+/// program leaving its value in `Reg(0)`
+///
+/// Each operation is issued once the operations it depends on have completed,
+/// and intermediate values are spilled to memory past the highest variable
+/// address when every register is in use.
+///
+/// # Arguments
+/// * `expr` - infix expression of numeric constants and symbolic variables
+/// * `var_addrs` - memory address each symbolic variable is loaded from
+/// * `latencies` - operation latencies to schedule the program for
+///
+/// # Returns
+/// * `Ok(Vec<Instruction>)` - program that strongly evaluates to `expr` when
+///   run on a machine with the variables at their addresses
+/// * `Err(CompileError)` - if the expression is malformed or a variable has no
+///   address
+pub fn compile_with_latencies(
+    expr: &str,
+    var_addrs: &HashMap<String, Addr>,
+    latencies: &Latencies,
+) -> Result<Vec<Instruction>, CompileError> {
+    compile_with_register_count(expr, var_addrs, latencies, REGISTER_COUNT)
+}
+
+/// Compile an infix expression with `+`, `-`, `*` and parentheses into a
+/// program leaving its value in `Reg(0)`, using only the registers
+/// `0..register_count`
+///
+/// # Arguments
+/// * `expr` - infix expression of numeric constants and symbolic variables
+/// * `var_addrs` - memory address each symbolic variable is loaded from
+/// * `latencies` - operation latencies to schedule the program for
+/// * `register_count` - number of registers of the machine running the
+///   program
+///
+/// # Returns
+/// * `Ok(Vec<Instruction>)` - program
+/// * `Err(CompileError)` - see `compile_with_latencies()`
+///
+/// # Panics
+/// * If `register_count` is less than `2`, since both operands of a binary
+///   operation must be held in registers
+pub fn compile_with_register_count(
+    expr: &str,
+    var_addrs: &HashMap<String, Addr>,
+    latencies: &Latencies,
+    register_count: usize,
+) -> Result<Vec<Instruction>, CompileError> {
+    assert!(
+        register_count >= 2,
+        "Compiling needs at least 2 registers, got {register_count}"
+    );
+    let ast = Parser {
+        tokens: tokenize(expr)?,
+        next: 0,
+    }
+    .parse()?;
+
+    let mut codegen = CodeGen {
+        var_addrs,
+        latencies,
+        program: Vec::new(),
+        values: Vec::new(),
+        free: (0..register_count as u32).rev().map(Reg).collect(),
+        next_spill: var_addrs.values().map(|addr| addr.0 + 1).max().unwrap_or(0),
+    };
+    let result = codegen.generate(&ast)?;
+    let reg = codegen.load(result, &[]);
+    if reg != Reg(0) {
+        codegen.emit(Instruction::new().with_mov(Reg(0), reg));
+    }
+    Ok(codegen.program)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pem::{ExprWrapper, Machine};
+
+    use super::*;
+
+    /// Compile `expr` with variables `A..=Z` at addresses `0..26` and strongly
+    /// evaluate the program
+    fn compile_and_run(expr: &str) -> String {
+        let var_addrs: HashMap<_, _> = ('A'..='Z')
+            .enumerate()
+            .map(|(i, c)| (c.to_string(), Addr(i as u32)))
+            .collect();
+        let program = compile(expr, &var_addrs).unwrap();
+        let mut machine = Machine::new(
+            var_addrs
+                .iter()
+                .map(|(name, addr)| (*addr, ExprWrapper::from_symbolic_variable(name.clone())))
+                .collect(),
+        );
+        machine.compute(&program).unwrap().strong_eval()
+    }
+
+    #[test]
+    fn test_compile() {
+        assert_eq!(compile_and_run("(A + 1) * (B + 2)"), "(A + 1) * (B + 2)");
+        assert_eq!(compile_and_run("A + B * C - 3"), "A + B * C - 3");
+        assert_eq!(compile_and_run("A - (B - C)"), "A - (B - C)");
+        assert_eq!(compile_and_run("2 * 3 + 4"), "10");
+        assert_eq!(compile_and_run("A"), "A");
+    }
+
+    #[test]
+    fn test_compile_spills() {
+        let expr = "A + (B + (C + (D + (E + (F + (G + (H + (I + J))))))))";
+        assert_eq!(
+            compile_and_run(expr),
+            "A + B + C + D + E + F + G + H + I + J"
+        );
+
+        let var_addrs = HashMap::from([("A".to_string(), Addr(7))]);
+        let program = compile(
+            "A * (A * (A * (A * (A * (A * (A * (A * (A + 1))))))))",
+            &var_addrs,
+        )
+        .unwrap();
+        assert!(program
+            .iter()
+            .filter_map(|instruction| instruction.writes().first().copied())
+            .any(|location| location == crate::pem::Location::Memory(Addr(8))));
+    }
+
+    #[test]
+    fn test_compile_with_register_count() {
+        let var_addrs: HashMap<_, _> = ('A'..='D')
+            .enumerate()
+            .map(|(i, c)| (c.to_string(), Addr(i as u32)))
+            .collect();
+        let expr = "(A + 1) * (B + (C * (D - 2)))";
+        let run = |program: &[Instruction], register_count: usize| {
+            let mut machine = Machine::new(
+                var_addrs
+                    .iter()
+                    .map(|(name, addr)| (*addr, ExprWrapper::from_symbolic_variable(name.clone())))
+                    .collect(),
+            )
+            .with_register_count(register_count);
+            machine.compute(program).unwrap().strong_eval()
+        };
+
+        let program =
+            compile_with_register_count(expr, &var_addrs, &Latencies::default(), 2).unwrap();
+        assert!(program
+            .iter()
+            .flat_map(Instruction::writes)
+            .all(|location| !matches!(location, crate::pem::Location::Register(Reg(2..)))));
+        assert_eq!(run(&program, 2), "(A + 1) * (B + C * (D - 2))");
+
+        let program =
+            compile_with_register_count(expr, &var_addrs, &Latencies::default(), 16).unwrap();
+        assert_eq!(run(&program, 16), "(A + 1) * (B + C * (D - 2))");
+    }
+
+    #[test]
+    #[should_panic(expected = "Compiling needs at least 2 registers, got 1")]
+    fn test_compile_with_one_register() {
+        let _ = compile_with_register_count("1", &HashMap::new(), &Latencies::default(), 1);
+    }
+
+    #[test]
+    fn test_compile_invalid() {
+        let var_addrs = HashMap::from([("A".to_string(), Addr(0))]);
+        assert_eq!(
+            compile("A + B", &var_addrs).unwrap_err(),
+            CompileError::UnknownVariable("B".to_string())
+        );
+        assert_eq!(
+            compile("A / 2", &var_addrs).unwrap_err(),
+            CompileError::UnexpectedCharacter {
+                found: '/',
+                position: 2
+            }
+        );
+        assert_eq!(
+            compile("(A + 1", &var_addrs).unwrap_err(),
+            CompileError::UnexpectedEnd { expected: "')'" }
+        );
+        assert_eq!(
+            compile("A 1", &var_addrs).unwrap_err(),
+            CompileError::UnexpectedToken {
+                token: "1".to_string(),
+                position: 2,
+                expected: "operator"
+            }
+        );
+        assert!(matches!(
+            compile("A + 99999999999", &var_addrs),
+            Err(CompileError::ConstantOutOfRange { position: 4, .. })
+        ));
+    }
+}
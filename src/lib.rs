@@ -0,0 +1,5 @@
+//! Polynomial Evaluation Machine (PEM) library
+
+pub mod compiler;
+pub mod parser;
+pub mod pem;
@@ -1,31 +1,54 @@
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 
-mod parser;
-mod pem;
-
-use pem::Machine;
+use polynomial_evaluation::{
+    parser,
+    pem::{Latencies, Machine},
+};
 
 fn main() {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
         .format_timestamp_micros()
         .init();
 
-    let program_filepath = std::env::args().nth(1).unwrap_or_else(|| {
+    let annotate = std::env::args().any(|arg| arg == "--annotate");
+    let mut args = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"));
+
+    let program_filepath = args.next().unwrap_or_else(|| {
         debug!("No program file specified, defaulting to `./example_program.txt`");
         "./example_program.txt".to_string()
     });
-    let program = parser::read_program(&program_filepath);
+    let program = parser::read_program(&program_filepath).unwrap_or_else(|e| {
+        error!("Error: {}", e);
+        std::process::exit(1)
+    });
+    if annotate {
+        match parser::annotate(&program, &Latencies::default()) {
+            Ok(source) => println!("{}", source),
+            Err(e) => warn!("Unable to annotate: {}", e),
+        }
+    }
 
-    let startup_memory_filepath = std::env::args().nth(2).unwrap_or_else(|| {
+    let startup_memory_filepath = args.next().unwrap_or_else(|| {
         debug!("No startup memory file specified, defaulting to `./startup_memory.txt`");
         "./startup_memory.txt".to_string()
     });
-    let mut machine = Machine::new(parser::read_startup_memory(&startup_memory_filepath));
+    let memory = parser::read_startup_memory(&startup_memory_filepath).unwrap_or_else(|e| {
+        error!("Error: {}", e);
+        std::process::exit(1)
+    });
+    let mut machine = Machine::new(memory);
     machine.allow_data_race(
         std::env::var("ALLOW_DATA_RACE")
             .map(|s| s == "true")
             .unwrap_or(false),
     );
+    machine.detect_write_after_write(
+        std::env::var("DETECT_WRITE_AFTER_WRITE")
+            .map(|s| s == "true")
+            .unwrap_or(false),
+    );
 
     match machine.compute(&program) {
         Ok(value) => {
@@ -34,4 +57,11 @@ fn main() {
         }
         Err(e) => error!("Error: {}", e),
     }
+
+    if !machine.races().is_empty() {
+        warn!(
+            "{} data race(s) allowed during execution",
+            machine.races().len()
+        );
+    }
 }
@@ -1,159 +1,1590 @@
-use std::{collections::HashMap, fs::read_to_string};
+use std::{collections::HashMap, fs::read_to_string, num::ParseIntError, sync::LazyLock};
 
-use log::info;
+use log::{info, warn};
+use thiserror::Error;
 
 use crate::pem::{
+    analysis::{completion_cycles, AnalysisError},
+    is_identifier,
     types::{Addr, Const, Reg},
-    ExprWrapper, Instruction,
+    ExpectedState, ExprWrapper, Instruction, Latencies, OpKind, REGISTER_COUNT,
 };
 
+/// Configuration for reading programs and startup memory
+#[derive(Debug, Clone)]
+pub struct ParserConfig {
+    /// Terminate the last instruction bundle if the program does not end with
+    /// a `;` rather than failing
+    pub auto_terminate: bool,
+    /// Separator between the address and value of each startup memory line,
+    /// e.g. `,` or `\t` for spreadsheet exports
+    pub memory_separator: char,
+    /// Fail rather than warn when a startup memory value is not a valid
+    /// symbolic variable name, i.e. neither an identifier nor a quoted name
+    /// such as `"complex name"`
+    pub strict_variable_names: bool,
+    /// Operation latencies the instructions expanded from `pow` are padded
+    /// for
+    pub latencies: Latencies,
+    /// Number of registers of the machine the program runs on, see
+    /// `Machine::with_register_count()`
+    pub register_count: usize,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self {
+            auto_terminate: false,
+            memory_separator: ' ',
+            strict_variable_names: true,
+            latencies: Latencies::default(),
+            register_count: REGISTER_COUNT,
+        }
+    }
+}
+
+pub use crate::pem::OperandKind;
+
+/// Specification of an opcode accepted by the parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeSpec {
+    /// Mnemonic the operation is written with, e.g. `ldi`
+    pub mnemonic: &'static str,
+    /// Kinds of the operands in the order they are written
+    pub operands: &'static [OperandKind],
+}
+
+impl OpcodeSpec {
+    /// Get the number of operands the opcode takes
+    pub fn arity(&self) -> usize {
+        self.operands.len()
+    }
+}
+
+/// Instruction set accepted by the parser, i.e. every operation kind followed
+/// by the `nop` and `pow` pseudo-instructions
+static OPCODES: LazyLock<Vec<OpcodeSpec>> = LazyLock::new(|| {
+    use OperandKind::{Constant, Register};
+    OpKind::all()
+        .map(|kind| OpcodeSpec {
+            mnemonic: kind.mnemonic(),
+            operands: kind.operands(),
+        })
+        .chain([
+            OpcodeSpec {
+                mnemonic: "nop",
+                operands: &[],
+            },
+            OpcodeSpec {
+                mnemonic: "pow",
+                operands: &[Register, Register, Constant],
+            },
+        ])
+        .collect()
+});
+
+/// Get the instruction set accepted by the parser, e.g. for editor
+/// autocompletion or validating generated programs
+///
+/// # Returns
+/// * `&'static [OpcodeSpec]` - every opcode with its operand kinds
+pub fn opcodes() -> &'static [OpcodeSpec] {
+    &OPCODES
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ParseError {
+    #[error("Unable to read `{path}`: {reason}")]
+    Io { path: String, reason: String },
+    #[error("Invalid operation on line {line}: `{token}`")]
+    InvalidOperation { token: String, line: usize },
+    #[error("Invalid {op} operands on line {line}: `{text}`")]
+    InvalidOperands {
+        op: String,
+        text: String,
+        line: usize,
+    },
+    #[error("Invalid {op} {kind} {token} on line {line}: {reason}")]
+    InvalidOperand {
+        op: String,
+        kind: OperandKind,
+        token: String,
+        line: usize,
+        reason: String,
+    },
+    #[error("Operand {token} of {op} on line {line} is not a valid {kind}")]
+    WrongOperandKind {
+        op: String,
+        kind: OperandKind,
+        token: String,
+        line: usize,
+    },
+    #[error("Undefined named constant {name} on line {line}")]
+    UndefinedConstant { name: String, line: usize },
+    #[error("Invalid `.const` directive on line {line}: {reason}")]
+    InvalidConstDirective { line: usize, reason: String },
+    #[error("Invalid `pow` on line {line}: {reason}")]
+    InvalidPow { line: usize, reason: String },
+    #[error(
+        "Missing semicolon at end of program to terminate instruction starting on line {line}"
+    )]
+    MissingSemicolon { line: usize },
+    #[error("No memory address on line {line}")]
+    MissingAddress { line: usize },
+    #[error("No value on line {line}")]
+    MissingValue { line: usize },
+    #[error("Invalid memory address {token} on line {line}: {reason}")]
+    InvalidAddress {
+        token: String,
+        line: usize,
+        reason: String,
+    },
+    #[error("Memory address {addr} on line {line} is already defined on line {first_line}")]
+    DuplicateAddress {
+        addr: u32,
+        line: usize,
+        first_line: usize,
+    },
+    #[error("Invalid numeric constant {token} on line {line}: {reason}")]
+    InvalidValue {
+        token: String,
+        line: usize,
+        reason: String,
+    },
+    #[error("Invalid symbolic variable `{name}` on line {line}")]
+    InvalidVariable { name: String, line: usize },
+    #[error("Comment on line {line} where a {op} operand is expected")]
+    UnexpectedComment { op: String, line: usize },
+    #[error("`nop` on line {line} is combined with other operations in its bundle")]
+    ConflictingNop { line: usize },
+    #[error("Undefined label `{label}` on line {line}")]
+    UndefinedLabel { label: String, line: usize },
+    #[error("Label `{label}` on line {line} is already defined on line {first_line}")]
+    DuplicateLabel {
+        label: String,
+        line: usize,
+        first_line: usize,
+    },
+}
+
+/// Parse an operand token and validate it against the expected operand kind
+///
+/// # Arguments
+/// * `op` - operation the operand belongs to
+/// * `token` - operand token
+/// * `kind` - expected operand kind
+/// * `register_count` - number of registers register operands must be below
+/// * `line` - line number of the operand
+///
+/// # Returns
+/// * `Ok(u32)` - numeric value of the operand
+/// * `Err(ParseError::InvalidOperand)` - if the token is not a `u32`, written
+///   in any base accepted by `parse_number()` for constants
+/// * `Err(ParseError::WrongOperandKind)` - if the value is not valid for the
+///   operand kind, e.g. a register outside `0..register_count`
+fn parse_operand(
+    op: &str,
+    token: &str,
+    kind: OperandKind,
+    register_count: usize,
+    line: usize,
+) -> Result<u32, ParseError> {
+    let value = match kind {
+        OperandKind::Constant => parse_number(token),
+        _ => token.parse::<u32>(),
+    }
+    .map_err(|e| ParseError::InvalidOperand {
+        op: op.to_string(),
+        kind,
+        token: token.to_string(),
+        line,
+        reason: e.to_string(),
+    })?;
+
+    match kind {
+        OperandKind::Register if value as usize >= register_count => {
+            Err(ParseError::WrongOperandKind {
+                op: op.to_string(),
+                kind,
+                token: token.to_string(),
+                line,
+            })
+        }
+        // Every `u32` is a valid memory address or constant
+        _ => Ok(value),
+    }
+}
+
+/// Parse a numeric constant written in hexadecimal with a `0x`/`0X` prefix,
+/// binary with a `0b` prefix, octal with a `0o` prefix or decimal otherwise
+///
+/// # Arguments
+/// * `token` - numeric constant
+///
+/// # Returns
+/// * `Ok(u32)` - value of the constant
+/// * `Err(ParseIntError)` - if the digits are invalid for the base or the value
+///   does not fit in a `u32`
+fn parse_number(token: &str) -> Result<u32, ParseIntError> {
+    if let Some(digits) = token
+        .strip_prefix("0x")
+        .or_else(|| token.strip_prefix("0X"))
+    {
+        u32::from_str_radix(digits, 16)
+    } else if let Some(digits) = token.strip_prefix("0b") {
+        u32::from_str_radix(digits, 2)
+    } else if let Some(digits) = token.strip_prefix("0o") {
+        u32::from_str_radix(digits, 8)
+    } else {
+        token.parse::<u32>()
+    }
+}
+
+/// Parse a constant operand token, resolving named constants defined with
+/// `.const`
+///
+/// # Arguments
+/// * `op` - operation the operand belongs to
+/// * `token` - operand token, either a `u32` or a named constant
+/// * `constants` - named constants defined so far
+/// * `line` - line number of the operand
+///
+/// # Returns
+/// * `Ok(u32)` - numeric value of the constant
+/// * `Err(ParseError::UndefinedConstant)` - if the token names an undefined
+///   constant
+/// * `Err(ParseError::InvalidOperand)` - if the token is not a `u32`
+fn parse_constant(
+    op: &str,
+    token: &str,
+    constants: &HashMap<String, u32>,
+    line: usize,
+) -> Result<u32, ParseError> {
+    if let Some(value) = constants.get(token) {
+        return Ok(*value);
+    }
+    if is_identifier(token) {
+        return Err(ParseError::UndefinedConstant {
+            name: token.to_string(),
+            line,
+        });
+    }
+    parse_operand(op, token, OperandKind::Constant, REGISTER_COUNT, line)
+}
+
+/// Read startup memory from file with the default `ParserConfig`
+///
+/// # Arguments
+/// * `filepath` - path to file containing startup memory
+///
+/// # Returns
+/// * `Ok(HashMap<Addr, ExprWrapper>)` - startup memory
+/// * `Err(ParseError)` - see `read_startup_memory_with_config()`
+pub fn read_startup_memory(filepath: &str) -> Result<HashMap<Addr, ExprWrapper>, ParseError> {
+    read_startup_memory_with_config(filepath, &ParserConfig::default())
+}
+
 /// Read startup memory from file
 ///
 /// # Arguments
 /// * `filepath` - path to file containing startup memory
+/// * `config` - parser configuration
 ///
 /// # Returns
-/// * `HashMap<Addr, ExprWrapper>` - startup memory
-pub(crate) fn read_startup_memory(filepath: &str) -> HashMap<Addr, ExprWrapper> {
+/// * `Ok(HashMap<Addr, ExprWrapper>)` - startup memory
+/// * `Err(ParseError)` - if the file cannot be read, or see
+///   `parse_startup_memory_with_config()`
+pub fn read_startup_memory_with_config(
+    filepath: &str,
+    config: &ParserConfig,
+) -> Result<HashMap<Addr, ExprWrapper>, ParseError> {
     info!("Reading startup memory from `{filepath}`");
 
+    let source = read_to_string(filepath).map_err(|e| ParseError::Io {
+        path: filepath.to_string(),
+        reason: e.to_string(),
+    })?;
+    parse_startup_memory_with_config(&source, config)
+}
+
+/// Parse startup memory from a string with the default `ParserConfig`
+///
+/// # Arguments
+/// * `source` - startup memory, in the same format as a startup memory file
+///
+/// # Returns
+/// * `Ok(HashMap<Addr, ExprWrapper>)` - startup memory
+/// * `Err(ParseError)` - see `parse_startup_memory_with_config()`
+pub fn parse_startup_memory(source: &str) -> Result<HashMap<Addr, ExprWrapper>, ParseError> {
+    parse_startup_memory_with_config(source, &ParserConfig::default())
+}
+
+/// Parse startup memory from a string
+///
+/// # Arguments
+/// * `source` - startup memory, in the same format as a startup memory file
+/// * `config` - parser configuration
+///
+/// # Returns
+/// * `Ok(HashMap<Addr, ExprWrapper>)` - startup memory
+/// * `Err(ParseError)` - if a line has no memory address or value, a memory
+///   address is invalid or defined twice, a numeric value is out of range, or
+///   a value is not a valid symbolic variable name and `strict_variable_names`
+///   is set
+///
+/// # Note
+/// A purely numeric value, e.g. `5`, is stored as a numeric constant rather
+/// than a symbolic variable. A symbolic variable name is an identifier of letters, digits and `_` not
+/// starting with a digit. Other names, e.g. ones containing spaces, must be
+/// quoted as `"complex name"`; the quotes are not part of the name.
+pub fn parse_startup_memory_with_config(
+    source: &str,
+    config: &ParserConfig,
+) -> Result<HashMap<Addr, ExprWrapper>, ParseError> {
     let mut memory = HashMap::new();
-    for (num, line) in read_to_string(filepath)
-        .unwrap_or_else(|e| panic!("Unable to read startup memory from `{filepath}`: {e}"))
+    // Line on which each memory address was first defined
+    let mut defined_on: HashMap<u32, usize> = HashMap::new();
+    for (num, line) in source
         .lines()
         .enumerate()
         .map(|(num, line)| (num + 1, line))
     {
-        let mut split = line.splitn(2, ' ');
+        let mut split = line.splitn(2, config.memory_separator);
         let addr = split
             .next()
-            .unwrap_or_else(|| panic!("No memory address on line {num}"));
+            .map(str::trim)
+            .filter(|addr| !addr.is_empty())
+            .ok_or(ParseError::MissingAddress { line: num })?;
         let value = split
             .next()
-            .unwrap_or_else(|| panic!("No value on line {num}"));
-        let addr = Addr(
-            addr.parse::<u32>()
-                .unwrap_or_else(|e| panic!("Invalid memory address {addr} on line {num}: {e}")),
-        );
-        memory.insert(addr, ExprWrapper::from_symbolic_variable(value));
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .ok_or(ParseError::MissingValue { line: num })?;
+        let addr = addr
+            .parse::<u32>()
+            .map_err(|e| ParseError::InvalidAddress {
+                token: addr.to_string(),
+                line: num,
+                reason: e.to_string(),
+            })?;
+        if let Some(&first_line) = defined_on.get(&addr) {
+            return Err(ParseError::DuplicateAddress {
+                addr,
+                line: num,
+                first_line,
+            });
+        }
+        defined_on.insert(addr, num);
+        if value.chars().all(|c| c.is_ascii_digit()) {
+            let constant = value.parse::<u32>().map_err(|e| ParseError::InvalidValue {
+                token: value.to_string(),
+                line: num,
+                reason: e.to_string(),
+            })?;
+            memory.insert(Addr(addr), ExprWrapper::from(constant));
+            continue;
+        }
+        if let Some(name) = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .filter(|name| !name.is_empty() && !name.contains('"'))
+        {
+            memory.insert(Addr(addr), ExprWrapper::from_symbolic_variable(name));
+            continue;
+        }
+        if !is_identifier(value) {
+            if config.strict_variable_names {
+                return Err(ParseError::InvalidVariable {
+                    name: value.to_string(),
+                    line: num,
+                });
+            }
+            warn!("Suspicious symbolic variable `{value}` on line {num}");
+        }
+        memory.insert(Addr(addr), ExprWrapper::from_symbolic_variable(value));
     }
-    memory
+    Ok(memory)
 }
 
-/// Read program from file
+/// Read the expected final state of a program from file, with one
+/// `reg <reg> <value>` or `addr <addr> <value>` line per checked location
 ///
 /// # Arguments
-/// * `filepath` - path to file containing program
+/// * `filepath` - path to file containing the expected state
 ///
 /// # Returns
-/// * `Vec<Instruction>` - program
+/// * `ExpectedState` - expected strongly evaluated values
 ///
 /// # Panics
-/// * If there is an invalid operation
-/// * If there is an invalid operand
-/// * If there is a missing semicolon at the end of the program
-pub(crate) fn read_program(filepath: &str) -> Vec<Instruction> {
+/// * If a line is not a `reg` or `addr` line
+/// * If a register or memory address is invalid
+/// * If a line has no value
+pub fn read_expected_state(filepath: &str) -> ExpectedState {
+    info!("Reading expected state from `{filepath}`");
+
+    let mut spec = ExpectedState::new();
+    for (num, line) in read_to_string(filepath)
+        .unwrap_or_else(|e| panic!("Unable to read expected state from `{filepath}`: {e}"))
+        .lines()
+        .enumerate()
+        .map(|(num, line)| (num + 1, line))
+    {
+        let mut split = line.splitn(3, ' ');
+        let (Some(kind), Some(location), Some(value)) = (split.next(), split.next(), split.next())
+        else {
+            match line.split(' ').next() {
+                Some("#" | "") => continue,
+                _ => panic!("Invalid expected state on line {num}: `{line}`"),
+            }
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            panic!("No value on line {num}")
+        }
+
+        spec = match kind {
+            "reg" => spec.with_register(
+                Reg(
+                    parse_operand(kind, location, OperandKind::Register, REGISTER_COUNT, num)
+                        .unwrap_or_else(|e| panic!("{e}")),
+                ),
+                value,
+            ),
+            "addr" => spec.with_memory(
+                Addr(
+                    parse_operand(kind, location, OperandKind::Address, REGISTER_COUNT, num)
+                        .unwrap_or_else(|e| panic!("{e}")),
+                ),
+                value,
+            ),
+            "#" => continue,
+            _ => panic!("Invalid expected state on line {num}: `{line}`"),
+        };
+    }
+    spec
+}
+
+/// Read program from file with the default `ParserConfig`
+///
+/// # Arguments
+/// * `filepath` - path to file containing program
+///
+/// # Returns
+/// * `Ok(Vec<Instruction>)` - program
+/// * `Err(ParseError)` - if the file cannot be read, an operation or operand
+///   is invalid, a named constant is undefined, redefined or invalid, or the
+///   program does not end with a `;`
+pub fn read_program(filepath: &str) -> Result<Vec<Instruction>, ParseError> {
+    read_program_with_config(filepath, &ParserConfig::default())
+}
+
+/// Read program from file
+///
+/// # Arguments
+/// * `filepath` - path to file containing program
+/// * `config` - parser configuration
+///
+/// # Returns
+/// * `Ok(Vec<Instruction>)` - program
+/// * `Err(ParseError)` - if the file cannot be read, or see
+///   `parse_program_with_config()`
+pub fn read_program_with_config(
+    filepath: &str,
+    config: &ParserConfig,
+) -> Result<Vec<Instruction>, ParseError> {
     info!("Reading program from `{filepath}`");
 
+    let source = read_to_string(filepath).map_err(|e| ParseError::Io {
+        path: filepath.to_string(),
+        reason: e.to_string(),
+    })?;
+    parse_program_with_config(&source, config)
+}
+
+/// Parse program from a string with the default `ParserConfig`
+///
+/// # Arguments
+/// * `source` - program, in the same format as a program file
+///
+/// # Returns
+/// * `Ok(Vec<Instruction>)` - program
+/// * `Err(ParseError)` - see `parse_program_with_config()`
+pub fn parse_program(source: &str) -> Result<Vec<Instruction>, ParseError> {
+    parse_program_with_config(source, &ParserConfig::default())
+}
+
+/// Parse program from a string
+///
+/// # Arguments
+/// * `source` - program, in the same format as a program file
+/// * `config` - parser configuration
+///
+/// # Returns
+/// * `Ok(Vec<Instruction>)` - program
+/// * `Err(ParseError)` - if an operation or operand is invalid, a named
+///   constant is undefined, redefined or invalid, a label is undefined or
+///   redefined, or the program does not end with a `;` and `auto_terminate` is
+///   not set
+pub fn parse_program_with_config(
+    source: &str,
+    config: &ParserConfig,
+) -> Result<Vec<Instruction>, ParseError> {
     let mut program = Vec::new();
     let mut curr_inst: Option<Instruction> = None;
+    let mut curr_pow: Option<Vec<Instruction>> = None;
+    // Line of the first operation of the current instruction bundle
+    let mut curr_start: Option<usize> = None;
+    // Whether the current instruction bundle is an explicit `nop`
+    let mut curr_nop = false;
+    // Named constants defined by `.const` directives
+    let mut constants: HashMap<String, u32> = HashMap::new();
+    // Instruction index and line of each label defined by a `<label>:` line
+    let mut labels: HashMap<String, (usize, usize)> = HashMap::new();
+    // Instruction index, `jz` condition register, label and line of each jump
+    // to a label
+    let mut jumps: Vec<(usize, Option<Reg>, String, usize)> = Vec::new();
 
-    for (num, line) in read_to_string(filepath)
-        .unwrap_or_else(|e| panic!("Unable to read program from `{filepath}`: {e}"))
+    for (num, line) in source
         .lines()
         .enumerate()
         .map(|(num, line)| (num + 1, line))
     {
         curr_inst = curr_inst.or(Some(Instruction::new()));
 
-        let mut split = line.split(' ');
-        let op = split
-            .next()
-            .unwrap_or_else(|| panic!("No operation on line {num}"));
+        // A token starting with `#` comments out the rest of the line
+        let commented = line.split_whitespace().any(|token| token.starts_with('#'));
+        let mut split = line
+            .split_whitespace()
+            .take_while(|token| !token.starts_with('#'));
+        let op = split.next().unwrap_or_default();
+        let tokens: Vec<_> = split.collect();
+        let operand = |token: &str, kind: OperandKind| match kind {
+            OperandKind::Constant => parse_constant(op, token, &constants, num),
+            // Labels are resolved once every label is defined
+            OperandKind::Label if is_identifier(token) => Ok(0),
+            _ => parse_operand(op, token, kind, config.register_count, num),
+        };
+        let invalid_operands = |arity: usize| {
+            if commented && tokens.len() < arity {
+                ParseError::UnexpectedComment {
+                    op: op.to_string(),
+                    line: num,
+                }
+            } else {
+                ParseError::InvalidOperands {
+                    op: op.to_string(),
+                    text: line.to_string(),
+                    line: num,
+                }
+            }
+        };
+
+        // A `<label>:` line labels the instruction bundle it starts or is in
+        if let Some(label) = op
+            .strip_suffix(':')
+            .filter(|label| is_identifier(label) && tokens.is_empty())
+        {
+            if let Some(&(_, first_line)) = labels.get(label) {
+                return Err(ParseError::DuplicateLabel {
+                    label: label.to_string(),
+                    line: num,
+                    first_line,
+                });
+            }
+            labels.insert(label.to_string(), (program.len(), num));
+            continue;
+        }
+
+        if curr_pow.is_some() && !matches!(op, ";" | "" | ".const") {
+            return Err(ParseError::InvalidPow {
+                line: num,
+                reason: format!("must be the only operation in its bundle, found `{op}`"),
+            });
+        }
+        if !matches!(op, "" | ".const") {
+            curr_start = curr_start.or(Some(num));
+        }
 
         match op {
-            "ldi" => {
-                if let (Some(dst), Some(constant), None) =
-                    (split.next(), split.next(), split.next())
-                {
-                    let dst = dst.parse::<u32>().unwrap_or_else(|e| {
-                        panic!("Invalid {op} register {dst} on line {num}: {e}")
+            ".const" => {
+                let &[name, value] = tokens.as_slice() else {
+                    return Err(invalid_operands(2));
+                };
+                if !is_identifier(name) {
+                    return Err(ParseError::InvalidConstDirective {
+                        line: num,
+                        reason: format!("`{name}` is not a valid constant name"),
                     });
-                    let constant = constant.parse::<u32>().unwrap_or_else(|e| {
-                        panic!("Invalid {op} constant {constant} on line {num}: {e}")
+                }
+                let value = operand(value, OperandKind::Constant)?;
+                if constants.insert(name.to_string(), value).is_some() {
+                    return Err(ParseError::InvalidConstDirective {
+                        line: num,
+                        reason: format!("`{name}` is already defined"),
                     });
-                    curr_inst = curr_inst.map(|inst| inst.with_ldi(Reg(dst), Const(constant)));
-                } else {
-                    panic!("Invalid {op} operands on line {num}: `{line}`")
                 }
             }
-            "ldr" | "str" => {
-                if let (Some(reg), Some(addr), None) = (split.next(), split.next(), split.next()) {
-                    let reg = reg.parse::<u32>().unwrap_or_else(|e| {
-                        panic!("Invalid {op} register {reg} on line {num}: {e}")
-                    });
-                    let addr = addr.parse::<u32>().unwrap_or_else(|e| {
-                        panic!("Invalid {op} memory address {addr} on line {num}: {e}")
-                    });
+            ";" => {
+                match curr_pow.take() {
+                    Some(expansion) => program.extend(expansion),
+                    None => program.push(curr_inst.unwrap()),
+                }
+                curr_inst = None;
+                curr_start = None;
+                curr_nop = false;
+            }
+            "" => continue,
+            _ => {
+                let spec = opcodes()
+                    .iter()
+                    .find(|spec| spec.mnemonic == op)
+                    .ok_or_else(|| ParseError::InvalidOperation {
+                        token: op.to_string(),
+                        line: num,
+                    })?;
+                if tokens.len() != spec.arity() {
+                    return Err(invalid_operands(spec.arity()));
+                }
+                if curr_nop && op != "nop" {
+                    return Err(ParseError::ConflictingNop { line: num });
+                }
+                let operands = tokens
+                    .iter()
+                    .zip(spec.operands)
+                    .map(|(token, kind)| operand(token, *kind))
+                    .collect::<Result<Vec<_>, _>>()?;
 
-                    match op {
-                        "ldr" => {
-                            curr_inst = curr_inst.map(|inst| inst.with_ldr(Reg(reg), Addr(addr)))
+                let inst = curr_inst.take().unwrap();
+                curr_inst = Some(match (op, operands.as_slice()) {
+                    ("ldi", &[dst, constant]) => inst.with_ldi(Reg(dst), Const(constant)),
+                    ("ldr", &[dst, addr]) => inst.with_ldr(Reg(dst), Addr(addr)),
+                    ("str", &[src, addr]) => inst.with_str(Reg(src), Addr(addr)),
+                    ("sti", &[addr, constant]) => inst.with_sti(Addr(addr), Const(constant)),
+                    ("add", &[dst, src1, src2]) => inst.with_add(Reg(dst), Reg(src1), Reg(src2)),
+                    ("sub", &[dst, src1, src2]) => inst.with_sub(Reg(dst), Reg(src1), Reg(src2)),
+                    ("mul", &[dst, src1, src2]) => inst.with_mul(Reg(dst), Reg(src1), Reg(src2)),
+                    ("div", &[dst, src1, src2]) => inst.with_div(Reg(dst), Reg(src1), Reg(src2)),
+                    ("powr", &[dst, src1, src2]) => inst.with_pow(Reg(dst), Reg(src1), Reg(src2)),
+                    ("min", &[dst, src1, src2]) => inst.with_min(Reg(dst), Reg(src1), Reg(src2)),
+                    ("max", &[dst, src1, src2]) => inst.with_max(Reg(dst), Reg(src1), Reg(src2)),
+                    ("and", &[dst, src1, src2]) => inst.with_and(Reg(dst), Reg(src1), Reg(src2)),
+                    ("or", &[dst, src1, src2]) => inst.with_or(Reg(dst), Reg(src1), Reg(src2)),
+                    ("xor", &[dst, src1, src2]) => inst.with_xor(Reg(dst), Reg(src1), Reg(src2)),
+                    ("shl", &[dst, src1, src2]) => inst.with_shl(Reg(dst), Reg(src1), Reg(src2)),
+                    ("shr", &[dst, src1, src2]) => inst.with_shr(Reg(dst), Reg(src1), Reg(src2)),
+                    ("neg", &[dst, src]) => inst.with_neg(Reg(dst), Reg(src)),
+                    ("sqr", &[dst, src]) => inst.with_sqr(Reg(dst), Reg(src)),
+                    ("cmovz", &[dst, src, cond]) => inst.with_cmovz(Reg(dst), Reg(src), Reg(cond)),
+                    ("clr", &[reg]) => inst.with_clr(Reg(reg)),
+                    ("mov", &[dst, src]) => inst.with_mov(Reg(dst), Reg(src)),
+                    ("jmp", &[target]) => {
+                        if is_identifier(tokens[0]) {
+                            jumps.push((program.len(), None, tokens[0].to_string(), num));
                         }
-                        "str" => {
-                            curr_inst = curr_inst.map(|inst| inst.with_str(Reg(reg), Addr(addr)))
+                        inst.with_jmp(target as usize)
+                    }
+                    ("jz", &[reg, target]) => {
+                        if is_identifier(tokens[1]) {
+                            jumps.push((program.len(), Some(Reg(reg)), tokens[1].to_string(), num));
                         }
-                        _ => unreachable!(),
+                        inst.with_jz(Reg(reg), target as usize)
                     }
-                } else {
-                    panic!("Invalid {op} operands on line {num}: `{line}`")
-                }
-            }
-            "add" | "sub" | "mul" => {
-                if let (Some(dst), Some(src1), Some(src2), None) =
-                    (split.next(), split.next(), split.next(), split.next())
-                {
-                    let dst = dst.parse::<u32>().unwrap_or_else(|e| {
-                        panic!("Invalid {op} register {dst} on line {num}: {e}")
-                    });
-                    let src1 = src1.parse::<u32>().unwrap_or_else(|e| {
-                        panic!("Invalid {op} register {src1} on line {num}: {e}")
-                    });
-                    let src2 = src2.parse::<u32>().unwrap_or_else(|e| {
-                        panic!("Invalid {op} register {src2} on line {num}: {e}")
-                    });
-
-                    match op {
-                        "add" => {
-                            curr_inst =
-                                curr_inst.map(|inst| inst.with_add(Reg(dst), Reg(src1), Reg(src2)))
+                    ("halt", &[]) => inst.with_halt(),
+                    ("nop", &[]) => {
+                        if !inst.op_kinds().is_empty() {
+                            return Err(ParseError::ConflictingNop { line: num });
                         }
-                        "sub" => {
-                            curr_inst =
-                                curr_inst.map(|inst| inst.with_sub(Reg(dst), Reg(src1), Reg(src2)))
+                        curr_nop = true;
+                        inst
+                    }
+                    ("pow", &[dst, src, exp]) => {
+                        if !inst.op_kinds().is_empty() {
+                            return Err(ParseError::InvalidPow {
+                                line: num,
+                                reason: "must be the only operation in its bundle".to_string(),
+                            });
                         }
-                        "mul" => {
-                            curr_inst =
-                                curr_inst.map(|inst| inst.with_mul(Reg(dst), Reg(src1), Reg(src2)))
+                        if dst == src && !(exp == 0 || exp >= 2 && exp.is_power_of_two()) {
+                            return Err(ParseError::InvalidPow {
+                                line: num,
+                                reason: format!(
+                                    "destination must differ from source register {src}"
+                                ),
+                            });
                         }
-                        _ => unreachable!(),
+                        curr_pow = Some(expand_pow(Reg(dst), Reg(src), exp, &config.latencies));
+                        inst
                     }
+                    _ => unreachable!(),
+                });
+            }
+        }
+    }
+
+    if let Some(start) = curr_start {
+        if !config.auto_terminate {
+            return Err(ParseError::MissingSemicolon { line: start });
+        }
+        match curr_pow {
+            Some(expansion) => program.extend(expansion),
+            None => program.extend(curr_inst),
+        }
+    }
+
+    for (index, cond, label, line) in jumps {
+        let Some(&(target, _)) = labels.get(&label) else {
+            return Err(ParseError::UndefinedLabel { label, line });
+        };
+        let inst = std::mem::take(&mut program[index]);
+        program[index] = match cond {
+            Some(reg) => inst.with_jz(reg, target),
+            None => inst.with_jmp(target),
+        };
+    }
+
+    Ok(program)
+}
+
+/// Expand the `pow <dst> <src> <exp>` pseudo-instruction into `mul`
+/// instructions computing `src^exp` by exponentiation by squaring
+///
+/// Each `mul` is followed by empty instructions until it completes so the next
+/// `mul` reads its result, and `exp = 0` expands to `ldi <dst> 1`.
+///
+/// # Note
+/// The source register is read after the destination register is first
+/// written, so `dst` must differ from `src` unless `exp` is `0` or a power of
+/// two greater than `1`.
+///
+/// # Arguments
+/// * `dst` - destination register
+/// * `src` - source register
+/// * `exp` - constant exponent
+/// * `latencies` - operation latencies to pad each `mul` for
+///
+/// # Returns
+/// * `Vec<Instruction>` - expanded instructions
+pub fn expand_pow(dst: Reg, src: Reg, exp: u32, latencies: &Latencies) -> Vec<Instruction> {
+    match exp {
+        0 => Vec::from([Instruction::new().with_ldi(dst, Const(1))]),
+        1 => Vec::from([
+            Instruction::new().with_ldi(dst, Const(1)),
+            Instruction::new().with_mul(dst, src, dst),
+        ]),
+        _ => {
+            // The highest bit is accounted for by squaring `src` itself
+            let mut muls = Vec::new();
+            for bit in (0..u32::BITS - 1 - exp.leading_zeros()).rev() {
+                muls.push(if muls.is_empty() {
+                    (src, src)
                 } else {
-                    panic!("Invalid {op} operands on line {num}: `{line}`")
+                    (dst, dst)
+                });
+                if exp >> bit & 1 == 1 {
+                    muls.push((dst, src));
                 }
             }
-            ";" => {
-                program.push(curr_inst.unwrap());
-                curr_inst = None
+
+            let mut program = Vec::new();
+            for (src1, src2) in muls {
+                if !program.is_empty() {
+                    program.extend((1..latencies.mul).map(|_| Instruction::new()));
+                }
+                program.push(Instruction::new().with_mul(dst, src1, src2));
             }
-            "#" | "" => continue,
-            _ => panic!("Invalid operation on line {num}: `{op}`"),
+            program
         }
     }
+}
+
+/// Render a program as canonical source with one operation per line, a `;`
+/// after each instruction and a blank line between instructions
+///
+/// # Arguments
+/// * `program` - program to disassemble
+///
+/// # Returns
+/// * `String` - program source that `read_program` parses back into `program`
+pub fn disassemble(program: &[Instruction]) -> String {
+    program
+        .iter()
+        .map(|instruction| match instruction.to_source() {
+            source if source.is_empty() => ";\n".to_string(),
+            source => format!("{source}\n;\n"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-    if curr_inst.is_some() {
-        panic!("Missing semicolon at end of program")
+/// Render a program as source annotated with the cycle each instruction is
+/// issued at and the cycle its operations have all completed by, e.g.
+/// `; @issued 5 @done 7` after `add 0 0 1`
+///
+/// # Arguments
+/// * `program` - program to annotate
+/// * `latencies` - operation latencies to compute completion cycles with
+///
+/// # Returns
+/// * `Ok(String)` - annotated program source that can be read by
+///   `read_program`, stopping at the first `halt` since no further
+///   instructions are issued
+/// * `Err(AnalysisError::Jump)` if the program jumps, since instructions are
+///   then not issued at the cycle equal to their index
+pub fn annotate(program: &[Instruction], latencies: &Latencies) -> Result<String, AnalysisError> {
+    Ok(program
+        .iter()
+        .zip(completion_cycles(program, latencies)?)
+        .enumerate()
+        .map(|(pc, (instruction, done))| {
+            let terminator = match done {
+                Some(done) => format!("; @issued {pc} @done {done}"),
+                None => format!("; @issued {pc}"),
+            };
+            match instruction.to_source() {
+                source if source.is_empty() => terminator,
+                source => format!("{source}\n{terminator}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pem::Machine;
+
+    use super::*;
+
+    /// Write `source` to a temporary program file and return its path
+    fn write_program(name: &str, source: &str) -> String {
+        let path = std::env::temp_dir().join(format!("pem_parser_{name}.txt"));
+        std::fs::write(&path, source).unwrap();
+        path.to_string_lossy().to_string()
     }
 
-    program
+    #[test]
+    fn test_opcodes() {
+        let arities: Vec<_> = opcodes()
+            .iter()
+            .map(|spec| (spec.mnemonic, spec.arity()))
+            .collect();
+        assert_eq!(
+            arities,
+            [
+                ("ldi", 2),
+                ("ldr", 2),
+                ("str", 2),
+                ("sti", 2),
+                ("add", 3),
+                ("sub", 3),
+                ("mul", 3),
+                ("div", 3),
+                ("powr", 3),
+                ("min", 3),
+                ("max", 3),
+                ("and", 3),
+                ("or", 3),
+                ("xor", 3),
+                ("shl", 3),
+                ("shr", 3),
+                ("neg", 2),
+                ("sqr", 2),
+                ("cmovz", 3),
+                ("mov", 2),
+                ("clr", 1),
+                ("jmp", 1),
+                ("jz", 2),
+                ("halt", 0),
+                ("nop", 0),
+                ("pow", 3),
+            ]
+        );
+        let ldi = opcodes()
+            .iter()
+            .find(|spec| spec.mnemonic == "ldi")
+            .unwrap();
+        assert_eq!(ldi.operands, [OperandKind::Register, OperandKind::Constant]);
+    }
+
+    #[test]
+    fn test_comma_separated_memory() {
+        let filepath = write_program("comma_separated_memory", "0,A\n1, B\n7,C D\n");
+        let memory = read_startup_memory_with_config(
+            &filepath,
+            &ParserConfig {
+                memory_separator: ',',
+                strict_variable_names: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            memory,
+            HashMap::from([
+                (Addr(0), ExprWrapper::from_symbolic_variable("A")),
+                (Addr(1), ExprWrapper::from_symbolic_variable("B")),
+                (Addr(7), ExprWrapper::from_symbolic_variable("C D")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_memory_suspicious_variable() {
+        let filepath = write_program("memory_suspicious_variable", "0 A\n1 B+1\n");
+        let memory = read_startup_memory_with_config(
+            &filepath,
+            &ParserConfig {
+                strict_variable_names: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            memory.get(&Addr(1)),
+            Some(&ExprWrapper::from_symbolic_variable("B+1"))
+        );
+    }
+
+    #[test]
+    fn test_memory_invalid_variable_strict() {
+        let filepath = write_program("memory_invalid_variable_strict", "0 A\n1 B+1\n");
+        assert_eq!(
+            read_startup_memory_with_config(
+                &filepath,
+                &ParserConfig {
+                    strict_variable_names: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap_err(),
+            ParseError::InvalidVariable {
+                name: "B+1".to_string(),
+                line: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_memory_quoted_variable() {
+        let filepath = write_program("memory_quoted_variable", "0 rate_1\n1 \"complex name\"\n");
+        let memory = read_startup_memory(&filepath).unwrap();
+        let name = ExprWrapper::from_symbolic_variable("complex name");
+        assert_eq!(memory.get(&Addr(1)), Some(&name));
+        assert_eq!(name.weak_eval(), "\"complex name\"");
+
+        for (source, value) in [("0 my var\n", "my var"), ("0 \"\"\n", "\"\"")] {
+            let filepath = write_program("memory_quoted_variable", source);
+            assert_eq!(
+                read_startup_memory(&filepath).unwrap_err(),
+                ParseError::InvalidVariable {
+                    name: value.to_string(),
+                    line: 1,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_memory_numeric_value() {
+        let memory = parse_startup_memory("0 5\n1 A\n").unwrap();
+        assert_eq!(memory.get(&Addr(0)), Some(&ExprWrapper::from(5)));
+        let program = parse_program("ldr 0 0\n;\n").unwrap();
+        let mut machine = Machine::new(memory);
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "5");
+
+        assert!(matches!(
+            parse_startup_memory("0 4294967296\n"),
+            Err(ParseError::InvalidValue { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_memory_missing_separator() {
+        let filepath = write_program("memory_missing_separator", "0\tA\n1 B\n");
+        assert_eq!(
+            read_startup_memory_with_config(
+                &filepath,
+                &ParserConfig {
+                    memory_separator: '\t',
+                    ..Default::default()
+                },
+            )
+            .unwrap_err(),
+            ParseError::MissingValue { line: 2 }
+        );
+    }
+
+    #[test]
+    fn test_invalid_memory() {
+        let filepath = write_program("memory_duplicate_address", "0 A\n1 B\n0 C\n");
+        assert_eq!(
+            read_startup_memory(&filepath).unwrap_err(),
+            ParseError::DuplicateAddress {
+                addr: 0,
+                line: 3,
+                first_line: 1,
+            }
+        );
+
+        let filepath = write_program("memory_invalid_address", "0 A\nx B\n");
+        assert!(matches!(
+            read_startup_memory(&filepath),
+            Err(ParseError::InvalidAddress { line: 2, .. })
+        ));
+
+        assert!(matches!(
+            read_startup_memory("/nonexistent/memory.txt"),
+            Err(ParseError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_expected_state() {
+        let filepath = write_program(
+            "expected_state",
+            "# final state\nreg 0 (A + 1) * (B + 2)\n\naddr 4 93\n",
+        );
+        assert_eq!(
+            read_expected_state(&filepath),
+            ExpectedState::new()
+                .with_register(Reg(0), "(A + 1) * (B + 2)")
+                .with_memory(Addr(4), "93")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Operand 8 of reg on line 1 is not a valid register")]
+    fn test_read_expected_state_invalid_register() {
+        let filepath = write_program("expected_state_invalid_register", "reg 8 A\n");
+        read_expected_state(&filepath);
+    }
+
+    #[test]
+    fn test_auto_terminate() {
+        let filepath = write_program("auto_terminate", "ldi 0 1\n;\nldi 1 2\nldr 2 0\n");
+        let program = read_program_with_config(
+            &filepath,
+            &ParserConfig {
+                auto_terminate: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(program.len(), 2);
+        assert_eq!(program[1].to_source(), "ldi 1 2\nldr 2 0");
+    }
+
+    #[test]
+    fn test_missing_semicolon() {
+        let filepath = write_program(
+            "missing_semicolon",
+            "ldi 0 1\n;\n# comment\nldi 1 2\nldr 2 0\n",
+        );
+        assert_eq!(
+            read_program(&filepath).unwrap_err(),
+            ParseError::MissingSemicolon { line: 4 }
+        );
+    }
+
+    #[test]
+    fn test_parse_from_str() {
+        let program = parse_program("ldi 0 2\nldr 1 0\n;\nmul 2 0 1\n;\n").unwrap();
+        assert_eq!(program.len(), 2);
+        assert_eq!(program[0].to_source(), "ldi 0 2\nldr 1 0");
+        assert_eq!(program[1].to_source(), "mul 2 0 1");
+        assert_eq!(
+            parse_program("ldi 0 2\n").unwrap_err(),
+            ParseError::MissingSemicolon { line: 1 }
+        );
+
+        assert_eq!(
+            parse_startup_memory("0 A\n1 B\n").unwrap(),
+            HashMap::from([
+                (Addr(0), ExprWrapper::from_symbolic_variable("A")),
+                (Addr(1), ExprWrapper::from_symbolic_variable("B")),
+            ])
+        );
+        assert_eq!(
+            parse_startup_memory("0 A\n0 B\n").unwrap_err(),
+            ParseError::DuplicateAddress {
+                addr: 0,
+                line: 2,
+                first_line: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_clr() {
+        let filepath = write_program("clr", "ldi 1 1\n;\nclr 1\n;\n");
+        let program = read_program(&filepath).unwrap();
+        assert_eq!(program.len(), 2);
+        assert_eq!(program[1].to_source(), "clr 1");
+    }
+
+    #[test]
+    fn test_mov() {
+        let filepath = write_program("mov", "ldi 1 7\n;\nmov 0 1\n;\n");
+        let program = read_program(&filepath).unwrap();
+        assert_eq!(program.len(), 2);
+        assert_eq!(program[1].to_source(), "mov 0 1");
+    }
+
+    #[test]
+    fn test_mov_missing_operand() {
+        let filepath = write_program("mov_missing_operand", "mov 0\n;\n");
+        let error = read_program(&filepath).unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::InvalidOperands {
+                op: "mov".to_string(),
+                text: "mov 0".to_string(),
+                line: 1,
+            }
+        );
+        assert_eq!(error.to_string(), "Invalid mov operands on line 1: `mov 0`");
+    }
+
+    #[test]
+    fn test_invalid_program() {
+        let filepath = write_program("invalid_operation", "ldi 0 1\n;\ncall 0\n;\n");
+        assert_eq!(
+            read_program(&filepath).unwrap_err(),
+            ParseError::InvalidOperation {
+                token: "call".to_string(),
+                line: 3,
+            }
+        );
+
+        let filepath = write_program("invalid_operand", "ldi 0 x1\n;\n");
+        assert_eq!(
+            read_program(&filepath).unwrap_err(),
+            ParseError::UndefinedConstant {
+                name: "x1".to_string(),
+                line: 1,
+            }
+        );
+
+        let filepath = write_program("invalid_pow", "ldi 0 1\npow 1 2 3\n;\n");
+        assert_eq!(
+            read_program(&filepath).unwrap_err(),
+            ParseError::InvalidPow {
+                line: 2,
+                reason: "must be the only operation in its bundle".to_string(),
+            }
+        );
+
+        assert!(matches!(
+            read_program("/nonexistent/program.txt"),
+            Err(ParseError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_div() {
+        let filepath = write_program("div", "div 0 1 2\nmul 3 4 5\n;\n");
+        let program = read_program(&filepath).unwrap();
+        assert_eq!(program.len(), 1);
+        assert_eq!(program[0].to_source(), "mul 3 4 5\ndiv 0 1 2");
+    }
+
+    #[test]
+    fn test_pow() {
+        let program = parse_program("powr 0 1 2\n;\npow 3 1 2\n;\n").unwrap();
+        assert_eq!(program[0].to_source(), "powr 0 1 2");
+        assert_eq!(program[1].to_source(), "mul 3 1 1");
+
+        // `pow` takes a constant exponent, the runtime operation is `powr`
+        assert_eq!(
+            parse_program("powi 0 1 2\n;\n").unwrap_err(),
+            ParseError::InvalidOperation {
+                token: "powi".to_string(),
+                line: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_logic() {
+        let program = parse_program("and 0 1 2\nor 3 1 2\nxor 4 1 2\n;\n").unwrap();
+        assert_eq!(program[0].to_source(), "and 0 1 2\nor 3 1 2\nxor 4 1 2");
+    }
+
+    #[test]
+    fn test_shift() {
+        let program = parse_program("shl 0 1 2\nshr 3 1 2\n;\n").unwrap();
+        assert_eq!(program[0].to_source(), "shl 0 1 2\nshr 3 1 2");
+    }
+
+    #[test]
+    fn test_min_max() {
+        let program = parse_program("min 0 1 2\nmax 3 1 2\n;\n").unwrap();
+        assert_eq!(program[0].to_source(), "min 0 1 2\nmax 3 1 2");
+    }
+
+    #[test]
+    fn test_neg() {
+        let filepath = write_program("neg", "neg 0 1\n;\n");
+        let program = read_program(&filepath).unwrap();
+        assert_eq!(program.len(), 1);
+        assert_eq!(program[0].to_source(), "neg 0 1");
+    }
+
+    #[test]
+    fn test_sqr() {
+        let program = parse_program("sqr 0 1\n;\n").unwrap();
+        assert_eq!(program, [Instruction::new().with_sqr(Reg(0), Reg(1))]);
+        assert_eq!(program[0].to_source(), "sqr 0 1");
+        assert!(parse_program("sqr 0 1 1\n;\n").is_err());
+        assert!(parse_program("sqr 0 8\n;\n").is_err());
+    }
+
+    #[test]
+    fn test_named_constant() {
+        let filepath = write_program(
+            "named_constant",
+            ".const PI 3\n.const TAU_2 PI\nldi 0 PI\n;\nldi 1 TAU_2\nsti 4 PI\n;\n",
+        );
+        let program = read_program(&filepath).unwrap();
+        assert_eq!(program.len(), 2);
+        assert_eq!(program[0].to_source(), "ldi 0 3");
+        assert_eq!(program[1].to_source(), "ldi 1 3\nsti 4 3");
+    }
+
+    #[test]
+    fn test_undefined_named_constant() {
+        let filepath = write_program("undefined_named_constant", ".const PI 3\nldi 0 E\n;\n");
+        assert_eq!(
+            read_program(&filepath).unwrap_err(),
+            ParseError::UndefinedConstant {
+                name: "E".to_string(),
+                line: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_trailing_comment() {
+        let filepath = write_program("trailing_comment", "ldi 0 1\n;\n\n# comment\n");
+        assert_eq!(read_program(&filepath).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_inline_comment() {
+        let program = parse_program(
+            ".const N 5 # counter limit\nldi 0 N  # initialize counter\nldr 1 0 #load\n; # end\n",
+        )
+        .unwrap();
+        assert_eq!(program.len(), 1);
+        assert_eq!(program[0].to_source(), "ldi 0 5\nldr 1 0");
+
+        let error = parse_program("ldi 0 # 5\n;\n").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::UnexpectedComment {
+                op: "ldi".to_string(),
+                line: 1,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Comment on line 1 where a ldi operand is expected"
+        );
+    }
+
+    /// Compute `program` after loading `value` into `Reg(1)`
+    fn compute_with_reg1(value: ExprWrapper, program: Vec<Instruction>) -> String {
+        let mut machine = Machine::new(HashMap::from([(Addr(0), value)]));
+        let mut full_program = Vec::from([Instruction::new().with_ldr(Reg(1), Addr(0))]);
+        full_program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        full_program.extend(program);
+        machine.compute(&full_program).unwrap().strong_eval()
+    }
+
+    #[test]
+    fn test_expand_pow() {
+        let a = || ExprWrapper::from_symbolic_variable("A");
+        let latencies = Latencies::default();
+        let wait = || (1..latencies.mul).map(|_| Instruction::new());
+        let mul = |src1, src2| Instruction::new().with_mul(Reg(0), Reg(src1), Reg(src2));
+
+        assert_eq!(
+            compute_with_reg1(a(), expand_pow(Reg(0), Reg(1), 0, &latencies)),
+            compute_with_reg1(
+                a(),
+                Vec::from([Instruction::new().with_ldi(Reg(0), Const(1))])
+            )
+        );
+        assert_eq!(
+            compute_with_reg1(a(), expand_pow(Reg(0), Reg(1), 1, &latencies)),
+            compute_with_reg1(
+                a(),
+                Vec::from([Instruction::new().with_ldi(Reg(0), Const(1)), mul(1, 0)])
+            )
+        );
+        assert_eq!(
+            compute_with_reg1(a(), expand_pow(Reg(0), Reg(1), 2, &latencies)),
+            compute_with_reg1(a(), Vec::from([mul(1, 1)]))
+        );
+
+        let mut chain = Vec::from([mul(1, 1)]);
+        for _ in 0..3 {
+            chain.extend(wait());
+            chain.push(mul(0, 1));
+        }
+        assert_eq!(
+            compute_with_reg1(a(), expand_pow(Reg(0), Reg(1), 5, &latencies)),
+            compute_with_reg1(a(), chain)
+        );
+        assert_eq!(
+            compute_with_reg1(a(), expand_pow(Reg(0), Reg(1), 5, &latencies)),
+            "A * A * A * A * A"
+        );
+        assert_eq!(
+            compute_with_reg1(3.into(), expand_pow(Reg(0), Reg(1), 5, &latencies)),
+            "243"
+        );
+        assert_eq!(expand_pow(Reg(0), Reg(1), 5, &latencies).len(), 21);
+
+        let fast = Latencies {
+            mul: 2,
+            ..Latencies::default()
+        };
+        let program = expand_pow(Reg(0), Reg(1), 5, &fast);
+        assert_eq!(program.len(), 5);
+        let mut machine = Machine::new(HashMap::from([(Addr(0), a())])).with_latencies(fast);
+        let mut full_program = Vec::from([Instruction::new().with_ldr(Reg(1), Addr(0))]);
+        full_program.extend((1..fast.ldr).map(|_| Instruction::new()));
+        full_program.extend(program.clone());
+        assert_eq!(
+            machine.compute(&full_program).unwrap().strong_eval(),
+            "A * A * A * A * A"
+        );
+
+        let config = ParserConfig {
+            latencies: fast,
+            ..ParserConfig::default()
+        };
+        assert_eq!(
+            parse_program_with_config("pow 0 1 5\n;\n", &config).unwrap(),
+            program
+        );
+    }
+
+    #[test]
+    fn test_annotate() {
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+        ]);
+        assert_eq!(
+            annotate(&program, &Latencies::default()).unwrap(),
+            "ldi 0 1\nldr 1 0\n; @issued 0 @done 5\n\
+             ; @issued 1\n; @issued 2\n; @issued 3\n; @issued 4\n\
+             add 0 0 1\n; @issued 5 @done 7"
+        );
+
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_jmp(0),
+        ]);
+        assert_eq!(
+            annotate(&program, &Latencies::default()),
+            Err(AnalysisError::Jump { pc: 1 })
+        );
+        assert_eq!(
+            annotate(
+                &[program[0].clone().with_halt(), program[1].clone()],
+                &Latencies::default()
+            ),
+            Ok("ldi 0 1\nhalt\n; @issued 0 @done 1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let program =
+            parse_program("# example\nldr 1 0\nldi 0 1\n;\n;\n;\n;\n;\nadd 0 0 1  # A + 1\n;\n")
+                .unwrap();
+        let source = disassemble(&program);
+        assert_eq!(
+            source,
+            "ldi 0 1\nldr 1 0\n;\n\n;\n\n;\n\n;\n\n;\n\nadd 0 0 1\n;\n"
+        );
+        assert_eq!(parse_program(&source).unwrap(), program);
+        assert_eq!(disassemble(&[]), "");
+
+        let program = parse_program(
+            "ldi 0 1\n;\nloop:\nsti 3 7\njz 0 end\n;\nneg 1 0\nhalt\n;\nend:\nstr 0 4\n;\n",
+        )
+        .unwrap();
+        assert_eq!(parse_program(&disassemble(&program)).unwrap(), program);
+
+        let program = parse_program("str 1 0\nstr 2 3\n;\n").unwrap();
+        assert_eq!(disassemble(&program), "str 1 0\nstr 2 3\n;\n");
+    }
+
+    #[test]
+    fn test_halt() {
+        let program = parse_program("ldi 0 1\nhalt\n;\nldi 0 2\n;\n").unwrap();
+        assert_eq!(program[0].to_source(), "ldi 0 1\nhalt");
+        let mut machine = Machine::new(HashMap::new());
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "1");
+        assert!(parse_program("halt 0\n;\n").is_err());
+    }
+
+    #[test]
+    fn test_nop() {
+        let program = parse_program("ldi 0 1\n;\nnop\n;\nnop  # padding\n;\n").unwrap();
+        assert_eq!(program.len(), 3);
+        assert!(program[1].op_kinds().is_empty());
+        assert!(program[2].op_kinds().is_empty());
+
+        assert_eq!(
+            parse_program("nop\nldi 0 1\n;\n").unwrap_err(),
+            ParseError::ConflictingNop { line: 2 }
+        );
+        assert_eq!(
+            parse_program("ldi 0 1\nnop\n;\n").unwrap_err(),
+            ParseError::ConflictingNop { line: 2 }
+        );
+        assert!(matches!(
+            parse_program("nop 1\n;\n").unwrap_err(),
+            ParseError::InvalidOperands { .. }
+        ));
+    }
+
+    #[test]
+    fn test_labels() {
+        let program = parse_program(
+            "ldi 0 1\n;\nloop:\nadd 0 0 0\njmp loop\n;\njmp end # skip\n;\nldi 0 2\n;\nend:\n",
+        )
+        .unwrap();
+        assert_eq!(program.len(), 4);
+        assert_eq!(program[1].to_source(), "add 0 0 0\njmp 1");
+        assert_eq!(program[2].to_source(), "jmp 4");
+        assert_eq!(parse_program("jmp 0\n;\n").unwrap()[0].to_source(), "jmp 0");
+        assert_eq!(
+            parse_program("top:\njz 1 top\n;\n").unwrap()[0].to_source(),
+            "jz 1 0"
+        );
+
+        assert_eq!(
+            parse_program("jmp start\n;\n").unwrap_err(),
+            ParseError::UndefinedLabel {
+                label: "start".to_string(),
+                line: 1
+            }
+        );
+        assert_eq!(
+            parse_program("start:\nldi 0 1\n;\nstart:\n").unwrap_err(),
+            ParseError::DuplicateLabel {
+                label: "start".to_string(),
+                line: 4,
+                first_line: 1
+            }
+        );
+        assert!(matches!(
+            parse_program("jmp -1\n;\n").unwrap_err(),
+            ParseError::InvalidOperand { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_operand_kinds() {
+        assert_eq!(
+            parse_operand("ldi", "9", OperandKind::Constant, REGISTER_COUNT, 1),
+            Ok(9)
+        );
+        assert_eq!(
+            parse_operand("ldr", "9", OperandKind::Address, REGISTER_COUNT, 1),
+            Ok(9)
+        );
+        assert_eq!(
+            parse_operand("ldr", "9", OperandKind::Register, REGISTER_COUNT, 1),
+            Err(ParseError::WrongOperandKind {
+                op: "ldr".to_string(),
+                kind: OperandKind::Register,
+                token: "9".to_string(),
+                line: 1
+            })
+        );
+        assert_eq!(
+            parse_operand("ldi", "7", OperandKind::Register, REGISTER_COUNT, 2),
+            Ok(7)
+        );
+        assert_eq!(
+            parse_operand("ldr", "9", OperandKind::Register, 16, 1),
+            Ok(9)
+        );
+    }
+
+    #[test]
+    fn test_parse_register_count() {
+        let source = "ldi 15 1\n;\n";
+        assert!(matches!(
+            parse_program(source),
+            Err(ParseError::WrongOperandKind {
+                kind: OperandKind::Register,
+                line: 1,
+                ..
+            })
+        ));
+
+        let config = ParserConfig {
+            register_count: 16,
+            ..Default::default()
+        };
+        let program = parse_program_with_config(source, &config).unwrap();
+        assert_eq!(program, [Instruction::new().with_ldi(Reg(15), Const(1))]);
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("255"), Ok(255));
+        assert_eq!(parse_number("0xFF"), Ok(255));
+        assert_eq!(parse_number("0Xff"), Ok(255));
+        assert_eq!(parse_number("0b1010"), Ok(10));
+        assert_eq!(parse_number("0o17"), Ok(15));
+        assert!(parse_number("0xZZ").is_err());
+        assert!(parse_number("0x").is_err());
+    }
+
+    #[test]
+    fn test_radix_constants() {
+        let program = parse_program("ldi 0 0xFF\n;\nldi 1 0b1010\nsti 3 0o17\n;\n").unwrap();
+        assert_eq!(program[0].to_source(), "ldi 0 255");
+        assert_eq!(program[1].to_source(), "ldi 1 10\nsti 3 15");
+
+        assert!(matches!(
+            parse_program("ldi 0 0xZZ\n;\n"),
+            Err(ParseError::InvalidOperand {
+                kind: OperandKind::Constant,
+                line: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_operand_invalid() {
+        assert!(
+            parse_operand("ldi", "A", OperandKind::Constant, REGISTER_COUNT, 3).is_err_and(
+                |e| matches!(
+                    e,
+                    ParseError::InvalidOperand {
+                        kind: OperandKind::Constant,
+                        line: 3,
+                        ..
+                    }
+                )
+            )
+        );
+    }
 }
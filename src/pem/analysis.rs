@@ -0,0 +1,499 @@
+//! Static analysis of PEM programs that does not require running a `Machine`
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, BinaryHeap, HashMap, HashSet},
+};
+
+use thiserror::Error;
+
+use super::{types::Reg, Instruction, Latencies, Location, OpKind};
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum AnalysisError {
+    #[error("Instruction #{pc} jumps, so the program is not straight-line code")]
+    Jump { pc: usize },
+}
+
+/// Get the instructions of a program that are issued, checking they are
+/// straight-line code, since the analyses assume each instruction is issued
+/// exactly once, at the cycle equal to its index
+///
+/// # Arguments
+/// * `program` - instructions to check
+///
+/// # Returns
+/// * `Ok(&[Instruction])` - instructions up to and including the first `halt`,
+///   after which no further instructions are issued
+/// * `Err(AnalysisError::Jump)` with the first issued instruction that jumps
+///
+/// # Note
+/// The jump of an instruction that also halts is never taken.
+pub(super) fn straight_line(program: &[Instruction]) -> Result<&[Instruction], AnalysisError> {
+    let issued = match program.iter().position(|instruction| instruction.halt) {
+        Some(pc) => &program[..=pc],
+        None => program,
+    };
+    match issued.iter().position(|instruction| {
+        !instruction.halt && (instruction.jmp.is_some() || instruction.jz.is_some())
+    }) {
+        Some(pc) => Err(AnalysisError::Jump { pc }),
+        None => Ok(issued),
+    }
+}
+
+/// Estimate the peak number of in-flight operations a program reaches by
+/// simulating issue and completion cycles without building expressions
+///
+/// # Arguments
+/// * `program` - instructions to simulate
+/// * `latencies` - operation latencies to simulate with
+///
+/// # Returns
+/// * `Ok(usize)` - maximum number of pending operations at any cycle
+/// * `Err(AnalysisError::Jump)` if the program jumps
+pub fn peak_inflight(
+    program: &[Instruction],
+    latencies: &Latencies,
+) -> Result<usize, AnalysisError> {
+    let program = straight_line(program)?;
+    let mut pending = BinaryHeap::new();
+    let mut peak = 0;
+
+    for (pc, instruction) in program.iter().enumerate() {
+        pending.extend(
+            instruction
+                .op_kinds()
+                .into_iter()
+                .map(|op| Reverse(pc + latencies.of(op))),
+        );
+        peak = peak.max(pending.len());
+
+        // Operations completing by the next cycle retire at the end of this one
+        while pending
+            .peek()
+            .is_some_and(|Reverse(complete_by)| *complete_by <= pc + 1)
+        {
+            pending.pop();
+        }
+    }
+
+    Ok(peak)
+}
+
+/// Compute the cycle by which each instruction's operations have all completed
+///
+/// # Arguments
+/// * `program` - instructions to analyse
+/// * `latencies` - operation latencies to analyse with
+///
+/// # Returns
+/// * `Ok(Vec<Option<usize>>)` - completion cycle of the slowest operation of
+///   each issued instruction, or `None` if the instruction has no operations,
+///   stopping at the first `halt`
+/// * `Err(AnalysisError::Jump)` if the program jumps
+pub fn completion_cycles(
+    program: &[Instruction],
+    latencies: &Latencies,
+) -> Result<Vec<Option<usize>>, AnalysisError> {
+    let program = straight_line(program)?;
+    Ok(program
+        .iter()
+        .enumerate()
+        .map(|(pc, instruction)| {
+            instruction
+                .op_kinds()
+                .into_iter()
+                .map(|op| pc + latencies.of(op))
+                .max()
+        })
+        .collect())
+}
+
+/// Check whether a program is purely combinational, i.e. it only reads memory
+/// and computes into registers without writing memory
+///
+/// # Arguments
+/// * `program` - instructions to check
+///
+/// # Returns
+/// * `bool` - whether no instruction writes to memory
+pub fn is_pure(program: &[Instruction]) -> bool {
+    program.iter().all(|instruction| {
+        instruction
+            .writes()
+            .iter()
+            .all(|location| matches!(location, Location::Register(_)))
+    })
+}
+
+/// Find the instructions contributing to the final value of a register by
+/// slicing the program backwards over def-use chains
+///
+/// A read issued at cycle `t` sees the latest write completing by `t`, so
+/// writes still in flight when a value is read do not contribute to it.
+///
+/// # Arguments
+/// * `program` - instructions to slice
+/// * `reg` - register whose final value to slice for
+/// * `latencies` - operation latencies to slice with
+///
+/// # Returns
+/// * `Ok(Vec<usize>)` - indices of the contributing instructions in program
+///   order
+/// * `Err(AnalysisError::Jump)` if the program jumps
+pub fn slice_for_register(
+    program: &[Instruction],
+    reg: Reg,
+    latencies: &Latencies,
+) -> Result<Vec<usize>, AnalysisError> {
+    let program = straight_line(program)?;
+    // Every operation as `(pc, complete_by, kind, reads, write)`
+    let operations: Vec<_> = program
+        .iter()
+        .enumerate()
+        .flat_map(|(pc, instruction)| {
+            instruction
+                .effects()
+                .into_iter()
+                .map(move |(kind, reads, write)| (pc, pc + latencies.of(kind), kind, reads, write))
+        })
+        .collect();
+
+    let mut slice = BTreeSet::new();
+    let mut visited = HashSet::new();
+    // Values needed as `(location, cycle)`, where the value is read at `cycle`
+    let mut needed = Vec::from([(Location::Register(reg), usize::MAX)]);
+    while let Some((location, cycle)) = needed.pop() {
+        let Some(latest) = operations
+            .iter()
+            .filter(|(_, complete_by, _, _, write)| *write == location && *complete_by <= cycle)
+            .map(|(_, complete_by, ..)| *complete_by)
+            .max()
+        else {
+            continue;
+        };
+
+        // Writes completing in the same cycle race, so all of them contribute
+        for (i, (pc, complete_by, kind, reads, write)) in operations.iter().enumerate() {
+            if *write != location || *complete_by != latest || !visited.insert(i) {
+                continue;
+            }
+            slice.insert(*pc);
+            needed.extend(reads.iter().map(|read| (*read, *pc)));
+            // A `cmovz` not taken keeps the previous value
+            if *kind == OpKind::Cmovz {
+                needed.push((location, latest - 1));
+            }
+        }
+    }
+
+    Ok(slice.into_iter().collect())
+}
+
+/// Longest dependency chain of a program
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CriticalPath {
+    /// Number of cycles the chain takes when every operation issues as soon as
+    /// its operands are ready
+    pub latency: usize,
+    /// Indices of the instructions on the chain in program order
+    pub chain: Vec<usize>,
+}
+
+/// Find the longest chain of dependent operations, which bounds how short any
+/// schedule of the program can be
+///
+/// Each operation depends on the latest operation of an earlier instruction
+/// writing a location it reads, regardless of when either is issued.
+///
+/// # Arguments
+/// * `program` - instructions to analyse
+/// * `latencies` - operation latencies to analyse with
+///
+/// # Returns
+/// * `Ok(CriticalPath)` - latency and instructions of the longest chain, the
+///   latest one among chains of equal latency, empty if the program has no
+///   operations
+/// * `Err(AnalysisError::Jump)` if the program jumps
+pub fn critical_path(
+    program: &[Instruction],
+    latencies: &Latencies,
+) -> Result<CriticalPath, AnalysisError> {
+    let program = straight_line(program)?;
+    // Every operation as `(pc, finish, predecessor)`, where `finish` is the
+    // latency of the longest chain ending with the operation
+    let mut operations: Vec<(usize, usize, Option<usize>)> = Vec::new();
+    // Operation of an earlier instruction that last wrote each location
+    let mut last_writers: HashMap<Location, usize> = HashMap::new();
+
+    for (pc, instruction) in program.iter().enumerate() {
+        let mut writes = Vec::new();
+        for (kind, reads, write) in instruction.effects() {
+            let predecessor = reads
+                .iter()
+                .filter_map(|read| last_writers.get(read).copied())
+                .max_by_key(|&i| operations[i].1);
+            let ready = predecessor.map_or(0, |i| operations[i].1);
+            writes.push((write, operations.len()));
+            operations.push((pc, ready + latencies.of(kind), predecessor));
+        }
+
+        // Several `str`s of an instruction may write the same address
+        for (write, i) in writes {
+            let slower = last_writers
+                .get(&write)
+                .is_some_and(|&j| operations[j].0 == pc && operations[j].1 >= operations[i].1);
+            if !slower {
+                last_writers.insert(write, i);
+            }
+        }
+    }
+
+    let Some(mut last) = (0..operations.len()).max_by_key(|&i| operations[i].1) else {
+        return Ok(CriticalPath::default());
+    };
+    let latency = operations[last].1;
+    let mut chain = Vec::from([operations[last].0]);
+    while let Some(predecessor) = operations[last].2 {
+        chain.push(operations[predecessor].0);
+        last = predecessor;
+    }
+    chain.reverse();
+
+    Ok(CriticalPath { latency, chain })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pem::types::{Addr, Const, Reg};
+
+    use super::*;
+
+    #[test]
+    fn test_peak_inflight_back_to_back_mul() {
+        let program = Vec::from_iter((0..16).map(|_| {
+            Instruction::new()
+                .with_ldi(Reg(1), Const(1))
+                .with_mul(Reg(0), Reg(1), Reg(1))
+        }));
+        assert_eq!(peak_inflight(&program, &Latencies::default()).unwrap(), 11);
+        assert_eq!(
+            peak_inflight(
+                &program,
+                &Latencies {
+                    mul: 2,
+                    ..Default::default()
+                }
+            )
+            .unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_peak_inflight_example_program() {
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new()
+                .with_ldi(Reg(2), Const(2))
+                .with_ldr(Reg(3), Addr(1)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+            Instruction::new().with_add(Reg(2), Reg(2), Reg(3)),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(0), Reg(0), Reg(2)),
+        ]);
+        assert_eq!(peak_inflight(&program, &Latencies::default()).unwrap(), 3);
+        assert_eq!(peak_inflight(&[], &Latencies::default()).unwrap(), 0);
+        assert_eq!(
+            completion_cycles(&program, &Latencies::default()).unwrap(),
+            Vec::from([
+                Some(5),
+                Some(6),
+                None,
+                None,
+                None,
+                Some(7),
+                Some(8),
+                None,
+                Some(18)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_slice_for_register() {
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new().with_ldi(Reg(2), Const(2)),
+            Instruction::new().with_ldi(Reg(3), Const(3)),
+            Instruction::new().with_mul(Reg(4), Reg(2), Reg(3)),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+            Instruction::new().with_str(Reg(1), Addr(1)),
+        ]);
+        let latencies = Latencies::default();
+        assert_eq!(
+            slice_for_register(&program, Reg(0), &latencies).unwrap(),
+            Vec::from([0, 5])
+        );
+        assert_eq!(
+            slice_for_register(&program, Reg(4), &latencies).unwrap(),
+            Vec::from([1, 2, 3])
+        );
+        assert!(slice_for_register(&program, Reg(7), &latencies)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_slice_for_register_through_memory() {
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(1), Const(1))
+                .with_ldr(Reg(0), Addr(0)),
+            Instruction::new().with_str(Reg(1), Addr(0)),
+            Instruction::new().with_ldr(Reg(0), Addr(0)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_ldr(Reg(0), Addr(0)),
+        ]);
+        assert_eq!(
+            slice_for_register(&program, Reg(0), &Latencies::default()).unwrap(),
+            Vec::from([0, 1, 6])
+        );
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new()
+                .with_ldi(Reg(2), Const(2))
+                .with_ldr(Reg(3), Addr(1)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+            Instruction::new().with_add(Reg(2), Reg(2), Reg(3)),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(0), Reg(0), Reg(2)),
+            Instruction::new().with_str(Reg(0), Addr(2)),
+        ]);
+        let latencies = Latencies::default();
+        assert_eq!(
+            critical_path(&program, &latencies).unwrap(),
+            CriticalPath {
+                latency: latencies.ldr + latencies.add + latencies.mul + latencies.str,
+                chain: Vec::from([1, 6, 8, 9]),
+            }
+        );
+        assert_eq!(
+            critical_path(&[], &latencies).unwrap(),
+            CriticalPath::default()
+        );
+    }
+
+    #[test]
+    fn test_critical_path_through_memory() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_mul(Reg(1), Reg(0), Reg(0)),
+            Instruction::new().with_str(Reg(1), Addr(0)),
+            Instruction::new().with_ldr(Reg(2), Addr(0)),
+            Instruction::new().with_ldi(Reg(3), Const(2)),
+        ]);
+        let latencies = Latencies::default();
+        assert_eq!(
+            critical_path(&program, &latencies).unwrap(),
+            CriticalPath {
+                latency: latencies.ldi + latencies.mul + latencies.str + latencies.ldr,
+                chain: Vec::from([0, 1, 2, 3]),
+            }
+        );
+        assert_eq!(
+            critical_path(&program, &latencies.with(OpKind::Ldi, 100))
+                .unwrap()
+                .chain,
+            Vec::from([0, 1, 2, 3])
+        );
+        assert_eq!(
+            critical_path(&program[3..], &latencies.with(OpKind::Ldi, 100)).unwrap(),
+            CriticalPath {
+                latency: 100,
+                chain: Vec::from([1]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_analysis_with_jumps() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_jz(Reg(1), 0),
+            Instruction::new().with_jmp(0),
+        ]);
+        let latencies = Latencies::default();
+        let error = AnalysisError::Jump { pc: 1 };
+        assert_eq!(peak_inflight(&program, &latencies), Err(error.clone()));
+        assert_eq!(completion_cycles(&program, &latencies), Err(error.clone()));
+        assert_eq!(
+            slice_for_register(&program, Reg(0), &latencies),
+            Err(error.clone())
+        );
+        assert_eq!(critical_path(&program, &latencies), Err(error));
+        assert_eq!(
+            critical_path(&program[2..], &latencies),
+            Err(AnalysisError::Jump { pc: 0 })
+        );
+    }
+
+    #[test]
+    fn test_analysis_with_halt() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new()
+                .with_mul(Reg(0), Reg(0), Reg(0))
+                .with_jmp(0)
+                .with_halt(),
+            Instruction::new().with_jmp(0),
+            Instruction::new().with_ldi(Reg(0), Const(2)),
+        ]);
+        let latencies = Latencies::default();
+        assert_eq!(
+            completion_cycles(&program, &latencies),
+            Ok(Vec::from([Some(latencies.ldi), Some(1 + latencies.mul)]))
+        );
+        assert_eq!(
+            slice_for_register(&program, Reg(0), &latencies),
+            Ok(Vec::from([0, 1]))
+        );
+        assert_eq!(critical_path(&program, &latencies).unwrap().chain, [0, 1]);
+    }
+
+    #[test]
+    fn test_is_pure() {
+        let mut program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+        ]);
+        assert!(is_pure(&program));
+        assert!(is_pure(&[]));
+
+        program.push(Instruction::new().with_str(Reg(0), Addr(1)));
+        assert!(!is_pure(&program));
+        assert!(!is_pure(&[Instruction::new().with_sti(Addr(1), Const(1))]));
+    }
+}
@@ -0,0 +1,71 @@
+use super::{
+    types::{Addr, Reg},
+    Location,
+};
+
+/// Expected final register and memory values of a program, compared against
+/// the strongly evaluated values of a `Machine`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExpectedState {
+    /// Expected strongly evaluated value of each location in insertion order
+    pub(super) values: Vec<(Location, String)>,
+}
+
+impl ExpectedState {
+    /// Create an empty `ExpectedState`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect a register to hold a value
+    ///
+    /// # Arguments
+    /// * `reg` - register to check
+    /// * `value` - expected strongly evaluated value, e.g. `(A + 1) * B` or `93`
+    pub fn with_register<S: Into<String>>(mut self, reg: Reg, value: S) -> Self {
+        self.values.push((Location::Register(reg), value.into()));
+        self
+    }
+
+    /// Expect a memory address to hold a value
+    ///
+    /// # Arguments
+    /// * `addr` - memory address to check
+    /// * `value` - expected strongly evaluated value, e.g. `(A + 1) * B` or `93`
+    pub fn with_memory<S: Into<String>>(mut self, addr: Addr, value: S) -> Self {
+        self.values.push((Location::Memory(addr), value.into()));
+        self
+    }
+}
+
+/// A location whose final value differs from the `ExpectedState`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// Register or memory address checked
+    pub location: Location,
+    /// Expected strongly evaluated value
+    pub expected: String,
+    /// Actual strongly evaluated value, or `None` if uninitialized
+    pub actual: Option<String>,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let location = match self.location {
+            Location::Register(reg) => reg.to_string(),
+            Location::Memory(addr) => addr.to_string(),
+        };
+        match &self.actual {
+            Some(actual) => write!(
+                f,
+                "{location}: expected `{}`, got `{actual}`",
+                self.expected
+            ),
+            None => write!(
+                f,
+                "{location}: expected `{}`, got uninitialized",
+                self.expected
+            ),
+        }
+    }
+}
@@ -1,8 +1,58 @@
 use std::{
-    ops::{Add, Mul, Sub},
+    collections::{BTreeSet, HashMap},
+    ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Shl, Shr, Sub},
     rc::Rc,
 };
 
+use thiserror::Error;
+
+use super::{
+    polynomial::{Polynomial, VariableOrder},
+    symbol::Symbol,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum WeakEvalError {
+    #[error("Unexpected end of input, expected {expected}")]
+    UnexpectedEnd { expected: &'static str },
+    #[error("Unexpected character '{found}' at position {position}, expected {expected}")]
+    UnexpectedCharacter {
+        found: char,
+        position: usize,
+        expected: &'static str,
+    },
+    #[error("Constant {token} at position {position} does not fit in 32 bits")]
+    ConstantOutOfRange { token: String, position: usize },
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SexprError {
+    #[error("Unexpected end of input, expected {expected}")]
+    UnexpectedEnd { expected: &'static str },
+    #[error("Unexpected token `{token}` at position {position}, expected {expected}")]
+    UnexpectedToken {
+        token: String,
+        position: usize,
+        expected: &'static str,
+    },
+    #[error("Constant {token} at position {position} does not fit in 32 bits")]
+    ConstantOutOfRange { token: String, position: usize },
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum CoeffError {
+    #[error("Expression is not univariate in {expected}, found variable {found}")]
+    UnexpectedVariable { found: String, expected: String },
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum EvalError {
+    #[error("Symbolic variable {0} is not bound to a value")]
+    UnboundVariable(String),
+    #[error("Division by zero")]
+    DivisionByZero,
+}
+
 /// Enum representing the kind of an evaluated expression
 ///
 /// # Variants
@@ -14,18 +64,33 @@ enum EvaluatedExprKind {
     Value(String),
 }
 
-/// Enum representing the precedence of an evaluated expression tree
+/// Enum representing the precedence of an evaluated expression tree, ordered
+/// from the loosest to the tightest binding
 ///
 /// # Variants
+/// * `Or` - bitwise OR
+/// * `Xor` - bitwise XOR
+/// * `And` - bitwise AND
+/// * `Shift` - left or right shift
 /// * `Add` - addition
 /// * `Sub` - subtraction
 /// * `Mul` - multiplication
+/// * `Div` - division
+/// * `Pow` - exponentiation
+/// * `Neg` - negation
 /// * `NumericOrSymbolicVariable` - numeric constant or symbolic variable
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum Precedence {
+    Or,
+    Xor,
+    And,
+    Shift,
     Add,
     Sub,
     Mul,
+    Div,
+    Pow,
+    Neg,
     NumericOrSymbolicVariable,
 }
 
@@ -33,17 +98,38 @@ enum Precedence {
 ///
 /// # Variants
 /// * `Const(u32)` - numeric constant
-/// * `SymbolicVariable(String)` - symbolic variable
+/// * `SymbolicVariable(Symbol)` - symbolic variable with an interned name
 /// * `Add(RcExpr, RcExpr)` - addition
 /// * `Sub(RcExpr, RcExpr)` - subtraction
 /// * `Mul(RcExpr, RcExpr)` - multiplication
-#[derive(Debug, Clone)]
+/// * `Div(RcExpr, RcExpr)` - division
+/// * `Pow(RcExpr, RcExpr)` - exponentiation of a base by an exponent
+/// * `Neg(RcExpr)` - negation
+/// * `And(RcExpr, RcExpr)` - bitwise AND
+/// * `Or(RcExpr, RcExpr)` - bitwise OR
+/// * `Xor(RcExpr, RcExpr)` - bitwise XOR
+/// * `Shl(RcExpr, RcExpr)` - left shift by the number of bits of the rhs
+/// * `Shr(RcExpr, RcExpr)` - right shift by the number of bits of the rhs
+///
+/// # Note
+/// `^` already denotes exponentiation in `weak_eval()`, so bitwise XOR is
+/// weakly evaluated as `(A xor B)` and strongly evaluated as `A ^ B`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Expr {
     Const(u32),
-    SymbolicVariable(String),
+    SymbolicVariable(Symbol),
     Add(RcExpr, RcExpr),
     Sub(RcExpr, RcExpr),
     Mul(RcExpr, RcExpr),
+    Div(RcExpr, RcExpr),
+    Pow(RcExpr, RcExpr),
+    Neg(RcExpr),
+    And(RcExpr, RcExpr),
+    Or(RcExpr, RcExpr),
+    Xor(RcExpr, RcExpr),
+    Shl(RcExpr, RcExpr),
+    Shr(RcExpr, RcExpr),
 }
 
 impl std::fmt::Display for Expr {
@@ -54,18 +140,714 @@ impl std::fmt::Display for Expr {
             Expr::Add(lhs, rhs) => write!(f, "({} + {})", lhs, rhs),
             Expr::Sub(lhs, rhs) => write!(f, "({} - {})", lhs, rhs),
             Expr::Mul(lhs, rhs) => write!(f, "({} * {})", lhs, rhs),
+            Expr::Div(lhs, rhs) => write!(f, "({} / {})", lhs, rhs),
+            Expr::Pow(lhs, rhs) => write!(f, "({} ^ {})", lhs, rhs),
+            Expr::Neg(operand) => write!(f, "(-{})", operand),
+            Expr::And(lhs, rhs) => write!(f, "({} & {})", lhs, rhs),
+            Expr::Or(lhs, rhs) => write!(f, "({} | {})", lhs, rhs),
+            Expr::Xor(lhs, rhs) => write!(f, "({} xor {})", lhs, rhs),
+            Expr::Shl(lhs, rhs) => write!(f, "({} << {})", lhs, rhs),
+            Expr::Shr(lhs, rhs) => write!(f, "({} >> {})", lhs, rhs),
         }
     }
 }
 
+impl Expr {
+    /// Fold an operation with only numeric constant operands into a numeric
+    /// constant, with overflows handled by wrap-around, shift amounts taken
+    /// modulo `32` and divisions by zero left unfolded
+    fn folded(self) -> Self {
+        let folded = match &self {
+            Expr::Add(lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Const(lhs), Expr::Const(rhs)) => Some(lhs.wrapping_add(*rhs)),
+                _ => None,
+            },
+            Expr::Sub(lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Const(lhs), Expr::Const(rhs)) => Some(lhs.wrapping_sub(*rhs)),
+                _ => None,
+            },
+            Expr::Mul(lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Const(lhs), Expr::Const(rhs)) => Some(lhs.wrapping_mul(*rhs)),
+                _ => None,
+            },
+            Expr::Div(lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Const(lhs), Expr::Const(rhs)) if *rhs != 0 => Some(lhs.wrapping_div(*rhs)),
+                _ => None,
+            },
+            Expr::Pow(lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Const(lhs), Expr::Const(rhs)) => Some(lhs.wrapping_pow(*rhs)),
+                _ => None,
+            },
+            Expr::Neg(operand) => match operand.as_ref() {
+                Expr::Const(operand) => Some(operand.wrapping_neg()),
+                _ => None,
+            },
+            Expr::And(lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Const(lhs), Expr::Const(rhs)) => Some(lhs & rhs),
+                _ => None,
+            },
+            Expr::Or(lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Const(lhs), Expr::Const(rhs)) => Some(lhs | rhs),
+                _ => None,
+            },
+            Expr::Xor(lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Const(lhs), Expr::Const(rhs)) => Some(lhs ^ rhs),
+                _ => None,
+            },
+            Expr::Shl(lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Const(lhs), Expr::Const(rhs)) => Some(lhs.wrapping_shl(*rhs)),
+                _ => None,
+            },
+            Expr::Shr(lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Const(lhs), Expr::Const(rhs)) => Some(lhs.wrapping_shr(*rhs)),
+                _ => None,
+            },
+            Expr::Const(_) | Expr::SymbolicVariable(_) => None,
+        };
+        folded.map_or(self, Expr::Const)
+    }
+}
+
 type RcExpr = Rc<Expr>;
 
+/// Expand an expression tree into a sum of monomials
+///
+/// # Note
+/// Polynomials are not closed under division, so a division that does not
+/// expand to a numeric quotient is kept as an opaque variable named by its
+/// `weak_eval()` rendering, e.g. `(A / B)`. The same applies to an
+/// exponentiation whose exponent does not expand to a numeric constant and to
+/// a bitwise operation or shift whose operands do not both expand to numeric
+/// constants.
+///
+/// # Arguments
+/// * `expr` - expression tree to expand
+fn expand(expr: &RcExpr) -> Polynomial {
+    match expr.as_ref() {
+        Expr::Const(constant) => Polynomial::constant(*constant),
+        Expr::SymbolicVariable(value) => Polynomial::variable(value.as_str()),
+        Expr::Add(lhs, rhs) => &expand(lhs) + &expand(rhs),
+        Expr::Sub(lhs, rhs) => &expand(lhs) - &expand(rhs),
+        Expr::Mul(lhs, rhs) => &expand(lhs) * &expand(rhs),
+        Expr::Div(lhs, rhs) => match (expand(lhs).as_const(), expand(rhs).as_const()) {
+            (Some(lhs), Some(rhs)) if rhs != 0 => Polynomial::constant(lhs.wrapping_div(rhs)),
+            _ => Polynomial::variable(&expr.to_string()),
+        },
+        Expr::Pow(lhs, rhs) => match expand(rhs).as_const() {
+            Some(exponent) => {
+                // Exponentiation by squaring
+                let (mut base, mut exponent) = (expand(lhs), exponent);
+                let mut power = Polynomial::constant(1);
+                while exponent > 0 {
+                    if exponent & 1 == 1 {
+                        power = &power * &base;
+                    }
+                    exponent >>= 1;
+                    if exponent > 0 {
+                        base = &base * &base;
+                    }
+                }
+                power
+            }
+            None => Polynomial::variable(&expr.to_string()),
+        },
+        Expr::Neg(operand) => &Polynomial::default() - &expand(operand),
+        Expr::And(lhs, rhs)
+        | Expr::Or(lhs, rhs)
+        | Expr::Xor(lhs, rhs)
+        | Expr::Shl(lhs, rhs)
+        | Expr::Shr(lhs, rhs) => match (expand(lhs).as_const(), expand(rhs).as_const()) {
+            (Some(lhs), Some(rhs)) => Polynomial::constant(match expr.as_ref() {
+                Expr::And(_, _) => lhs & rhs,
+                Expr::Or(_, _) => lhs | rhs,
+                Expr::Xor(_, _) => lhs ^ rhs,
+                Expr::Shl(_, _) => lhs.wrapping_shl(rhs),
+                _ => lhs.wrapping_shr(rhs),
+            }),
+            _ => Polynomial::variable(&expr.to_string()),
+        },
+    }
+}
+
+/// Replace bound symbolic variables with numeric constants and fold the
+/// operations that become purely numeric
+///
+/// # Arguments
+/// * `expr` - expression tree to substitute into
+/// * `bindings` - numeric values of symbolic variables
+fn substitute(expr: &RcExpr, bindings: &HashMap<String, u32>) -> RcExpr {
+    match expr.as_ref() {
+        Expr::Const(_) => Rc::clone(expr),
+        Expr::SymbolicVariable(value) => bindings
+            .get(value.as_str())
+            .map_or_else(|| Rc::clone(expr), |value| Rc::new(Expr::Const(*value))),
+        Expr::Add(lhs, rhs) => {
+            Rc::new(Expr::Add(substitute(lhs, bindings), substitute(rhs, bindings)).folded())
+        }
+        Expr::Sub(lhs, rhs) => {
+            Rc::new(Expr::Sub(substitute(lhs, bindings), substitute(rhs, bindings)).folded())
+        }
+        Expr::Mul(lhs, rhs) => {
+            Rc::new(Expr::Mul(substitute(lhs, bindings), substitute(rhs, bindings)).folded())
+        }
+        Expr::Div(lhs, rhs) => {
+            Rc::new(Expr::Div(substitute(lhs, bindings), substitute(rhs, bindings)).folded())
+        }
+        Expr::Pow(lhs, rhs) => {
+            Rc::new(Expr::Pow(substitute(lhs, bindings), substitute(rhs, bindings)).folded())
+        }
+        Expr::Neg(operand) => Rc::new(Expr::Neg(substitute(operand, bindings)).folded()),
+        Expr::And(lhs, rhs) => {
+            Rc::new(Expr::And(substitute(lhs, bindings), substitute(rhs, bindings)).folded())
+        }
+        Expr::Or(lhs, rhs) => {
+            Rc::new(Expr::Or(substitute(lhs, bindings), substitute(rhs, bindings)).folded())
+        }
+        Expr::Xor(lhs, rhs) => {
+            Rc::new(Expr::Xor(substitute(lhs, bindings), substitute(rhs, bindings)).folded())
+        }
+        Expr::Shl(lhs, rhs) => {
+            Rc::new(Expr::Shl(substitute(lhs, bindings), substitute(rhs, bindings)).folded())
+        }
+        Expr::Shr(lhs, rhs) => {
+            Rc::new(Expr::Shr(substitute(lhs, bindings), substitute(rhs, bindings)).folded())
+        }
+    }
+}
+
+/// Compute the numeric value of an expression tree, with overflows handled by
+/// wrap-around
+///
+/// # Arguments
+/// * `expr` - expression tree to compute
+/// * `bindings` - numeric values of symbolic variables
+fn evaluate_numeric(expr: &RcExpr, bindings: &HashMap<String, u32>) -> Result<u32, EvalError> {
+    Ok(match expr.as_ref() {
+        Expr::Const(constant) => *constant,
+        Expr::SymbolicVariable(value) => *bindings
+            .get(value.as_str())
+            .ok_or_else(|| EvalError::UnboundVariable(value.as_str().to_string()))?,
+        Expr::Add(lhs, rhs) => {
+            evaluate_numeric(lhs, bindings)?.wrapping_add(evaluate_numeric(rhs, bindings)?)
+        }
+        Expr::Sub(lhs, rhs) => {
+            evaluate_numeric(lhs, bindings)?.wrapping_sub(evaluate_numeric(rhs, bindings)?)
+        }
+        Expr::Mul(lhs, rhs) => {
+            evaluate_numeric(lhs, bindings)?.wrapping_mul(evaluate_numeric(rhs, bindings)?)
+        }
+        Expr::Div(lhs, rhs) => evaluate_numeric(lhs, bindings)?
+            .checked_div(evaluate_numeric(rhs, bindings)?)
+            .ok_or(EvalError::DivisionByZero)?,
+        Expr::Pow(lhs, rhs) => {
+            evaluate_numeric(lhs, bindings)?.wrapping_pow(evaluate_numeric(rhs, bindings)?)
+        }
+        Expr::Neg(operand) => evaluate_numeric(operand, bindings)?.wrapping_neg(),
+        Expr::And(lhs, rhs) => evaluate_numeric(lhs, bindings)? & evaluate_numeric(rhs, bindings)?,
+        Expr::Or(lhs, rhs) => evaluate_numeric(lhs, bindings)? | evaluate_numeric(rhs, bindings)?,
+        Expr::Xor(lhs, rhs) => evaluate_numeric(lhs, bindings)? ^ evaluate_numeric(rhs, bindings)?,
+        Expr::Shl(lhs, rhs) => {
+            evaluate_numeric(lhs, bindings)?.wrapping_shl(evaluate_numeric(rhs, bindings)?)
+        }
+        Expr::Shr(lhs, rhs) => {
+            evaluate_numeric(lhs, bindings)?.wrapping_shr(evaluate_numeric(rhs, bindings)?)
+        }
+    })
+}
+
+/// Bound the numeric value of an expression tree under interval arithmetic,
+/// saturating at the `u32` bounds
+///
+/// # Arguments
+/// * `expr` - expression tree to bound
+/// * `ranges` - inclusive `(min, max)` range of symbolic variables, unbound
+///   variables range over every `u32`
+fn interval(expr: &RcExpr, ranges: &HashMap<String, (u32, u32)>) -> (u32, u32) {
+    match expr.as_ref() {
+        Expr::Const(constant) => (*constant, *constant),
+        Expr::SymbolicVariable(value) => {
+            ranges.get(value.as_str()).copied().unwrap_or((0, u32::MAX))
+        }
+        Expr::Add(lhs, rhs) => {
+            let ((lhs_min, lhs_max), (rhs_min, rhs_max)) =
+                (interval(lhs, ranges), interval(rhs, ranges));
+            (
+                lhs_min.saturating_add(rhs_min),
+                lhs_max.saturating_add(rhs_max),
+            )
+        }
+        Expr::Sub(lhs, rhs) => {
+            let ((lhs_min, lhs_max), (rhs_min, rhs_max)) =
+                (interval(lhs, ranges), interval(rhs, ranges));
+            (
+                lhs_min.saturating_sub(rhs_max),
+                lhs_max.saturating_sub(rhs_min),
+            )
+        }
+        Expr::Mul(lhs, rhs) => {
+            let ((lhs_min, lhs_max), (rhs_min, rhs_max)) =
+                (interval(lhs, ranges), interval(rhs, ranges));
+            let products = [
+                lhs_min.saturating_mul(rhs_min),
+                lhs_min.saturating_mul(rhs_max),
+                lhs_max.saturating_mul(rhs_min),
+                lhs_max.saturating_mul(rhs_max),
+            ];
+            (
+                products.into_iter().min().unwrap(),
+                products.into_iter().max().unwrap(),
+            )
+        }
+        Expr::Div(lhs, rhs) => {
+            // A zero divisor fails the computation, so only nonzero divisors
+            // bound the quotient
+            let ((lhs_min, lhs_max), (rhs_min, rhs_max)) =
+                (interval(lhs, ranges), interval(rhs, ranges));
+            (lhs_min / rhs_max.max(1), lhs_max / rhs_min.max(1))
+        }
+        Expr::Pow(lhs, rhs) => {
+            // `0 ^ 0 = 1`, so the bounds are not always at the same corner
+            let ((lhs_min, lhs_max), (rhs_min, rhs_max)) =
+                (interval(lhs, ranges), interval(rhs, ranges));
+            let powers = [
+                lhs_min.saturating_pow(rhs_min),
+                lhs_min.saturating_pow(rhs_max),
+                lhs_max.saturating_pow(rhs_min),
+                lhs_max.saturating_pow(rhs_max),
+            ];
+            (
+                powers.into_iter().min().unwrap(),
+                powers.into_iter().max().unwrap(),
+            )
+        }
+        Expr::Neg(operand) => {
+            let (min, max) = interval(operand, ranges);
+            (0u32.saturating_sub(max), 0u32.saturating_sub(min))
+        }
+        Expr::And(lhs, rhs) => {
+            // Clearing bits never increases either operand
+            let ((_, lhs_max), (_, rhs_max)) = (interval(lhs, ranges), interval(rhs, ranges));
+            (0, lhs_max.min(rhs_max))
+        }
+        Expr::Or(lhs, rhs) | Expr::Xor(lhs, rhs) => {
+            // Setting bits never increases the highest set bit of either
+            // operand, and OR never clears a bit
+            let ((lhs_min, lhs_max), (rhs_min, rhs_max)) =
+                (interval(lhs, ranges), interval(rhs, ranges));
+            let max = u32::MAX
+                .checked_shr(lhs_max.max(rhs_max).leading_zeros())
+                .unwrap_or(0);
+            match expr.as_ref() {
+                Expr::Or(_, _) => (lhs_min.max(rhs_min), max),
+                _ => (0, max),
+            }
+        }
+        Expr::Shl(lhs, rhs) => {
+            // Shift amounts wrap modulo 32 and shifted out bits are lost, so
+            // only shifts that cannot overflow are bounded
+            let ((lhs_min, lhs_max), (rhs_min, rhs_max)) =
+                (interval(lhs, ranges), interval(rhs, ranges));
+            if rhs_max < 32 && lhs_max.leading_zeros() >= rhs_max {
+                (lhs_min << rhs_min, lhs_max << rhs_max)
+            } else {
+                (0, u32::MAX)
+            }
+        }
+        Expr::Shr(lhs, rhs) => {
+            let ((lhs_min, lhs_max), (rhs_min, rhs_max)) =
+                (interval(lhs, ranges), interval(rhs, ranges));
+            if rhs_max < 32 {
+                (lhs_min >> rhs_max, lhs_max >> rhs_min)
+            } else {
+                (0, lhs_max)
+            }
+        }
+    }
+}
+
+/// Count the occurrences of each symbolic variable among the leaves of an
+/// expression tree
+///
+/// # Arguments
+/// * `expr` - expression tree to count in
+/// * `occurrences` - running count of each symbolic variable
+fn count_variables(expr: &RcExpr, occurrences: &mut HashMap<String, usize>) {
+    match expr.as_ref() {
+        Expr::Const(_) => {}
+        Expr::SymbolicVariable(value) => {
+            *occurrences.entry(value.as_str().to_string()).or_default() += 1
+        }
+        Expr::Add(lhs, rhs)
+        | Expr::Sub(lhs, rhs)
+        | Expr::Mul(lhs, rhs)
+        | Expr::Div(lhs, rhs)
+        | Expr::Pow(lhs, rhs)
+        | Expr::And(lhs, rhs)
+        | Expr::Or(lhs, rhs)
+        | Expr::Xor(lhs, rhs)
+        | Expr::Shl(lhs, rhs)
+        | Expr::Shr(lhs, rhs) => {
+            count_variables(lhs, occurrences);
+            count_variables(rhs, occurrences);
+        }
+        Expr::Neg(operand) => count_variables(operand, occurrences),
+    }
+}
+
+/// Render an expression tree in prefix form with every operation
+/// parenthesized, e.g. `(* (+ A 1) (+ B 2))`
+///
+/// # Arguments
+/// * `expr` - expression tree to render
+fn sexpr(expr: &RcExpr) -> String {
+    let (op, operands) = match expr.as_ref() {
+        Expr::Const(constant) => return constant.to_string(),
+        Expr::SymbolicVariable(value) => return value.to_string(),
+        Expr::Neg(operand) => return format!("(- {})", sexpr(operand)),
+        Expr::Add(lhs, rhs) => ("+", (lhs, rhs)),
+        Expr::Sub(lhs, rhs) => ("-", (lhs, rhs)),
+        Expr::Mul(lhs, rhs) => ("*", (lhs, rhs)),
+        Expr::Div(lhs, rhs) => ("/", (lhs, rhs)),
+        Expr::Pow(lhs, rhs) => ("^", (lhs, rhs)),
+        Expr::And(lhs, rhs) => ("&", (lhs, rhs)),
+        Expr::Or(lhs, rhs) => ("|", (lhs, rhs)),
+        Expr::Xor(lhs, rhs) => ("xor", (lhs, rhs)),
+        Expr::Shl(lhs, rhs) => ("<<", (lhs, rhs)),
+        Expr::Shr(lhs, rhs) => (">>", (lhs, rhs)),
+    };
+    format!("({} {} {})", op, sexpr(operands.0), sexpr(operands.1))
+}
+
+/// Recursive descent parser for the fully parenthesized `weak_eval` grammar
+///
+/// ```text
+/// expr := constant | variable | "(" expr " " op " " expr ")" | "(-" expr ")"
+/// op   := "+" | "-" | "*" | "/" | "^" | "&" | "|" | "xor" | "<<" | ">>"
+/// variable := identifier | '"' name '"'
+/// ```
+struct WeakEvalParser<'a> {
+    /// Input being parsed
+    input: &'a str,
+    /// Byte position of the next unparsed character
+    position: usize,
+}
+
+impl<'a> WeakEvalParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, position: 0 }
+    }
+
+    /// Peek at the next unparsed character
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    /// Consume the next character if it is `expected`
+    ///
+    /// # Arguments
+    /// * `expected` - character to consume
+    /// * `description` - description of `expected` used in errors
+    fn expect(&mut self, expected: char, description: &'static str) -> Result<(), WeakEvalError> {
+        match self.peek() {
+            Some(found) if found == expected => {
+                self.position += found.len_utf8();
+                Ok(())
+            }
+            found => Err(self.unexpected(found, description)),
+        }
+    }
+
+    /// Build the error for an unexpected character or end of input
+    fn unexpected(&self, found: Option<char>, expected: &'static str) -> WeakEvalError {
+        match found {
+            Some(found) => WeakEvalError::UnexpectedCharacter {
+                found,
+                position: self.position,
+                expected,
+            },
+            None => WeakEvalError::UnexpectedEnd { expected },
+        }
+    }
+
+    /// Parse a whole input, rejecting trailing characters
+    fn parse(mut self) -> Result<RcExpr, WeakEvalError> {
+        let expr = self.parse_expr()?;
+        match self.peek() {
+            None => Ok(expr),
+            found => Err(self.unexpected(found, "end of input")),
+        }
+    }
+
+    /// Parse a leaf or a parenthesized operation
+    fn parse_expr(&mut self) -> Result<RcExpr, WeakEvalError> {
+        if self.peek() != Some('(') {
+            return self.parse_leaf();
+        }
+
+        self.expect('(', "'('")?;
+        if self.peek() == Some('-') {
+            self.position += 1;
+            let operand = self.parse_expr()?;
+            self.expect(')', "')'")?;
+            return Ok(Rc::new(Expr::Neg(operand)));
+        }
+        let lhs = self.parse_expr()?;
+        self.expect(' ', "' '")?;
+        let op = self.peek();
+        let rest = &self.input[self.position..];
+        if rest.starts_with("xor") {
+            self.position += "xor".len();
+        } else if rest.starts_with("<<") || rest.starts_with(">>") {
+            self.position += 2;
+        } else if matches!(op, Some('+' | '-' | '*' | '/' | '^' | '&' | '|')) {
+            self.position += 1;
+        } else {
+            return Err(self.unexpected(op, "operator"));
+        }
+        self.expect(' ', "' '")?;
+        let rhs = self.parse_expr()?;
+        self.expect(')', "')'")?;
+
+        Ok(Rc::new(match op {
+            Some('+') => Expr::Add(lhs, rhs),
+            Some('-') => Expr::Sub(lhs, rhs),
+            Some('*') => Expr::Mul(lhs, rhs),
+            Some('/') => Expr::Div(lhs, rhs),
+            Some('^') => Expr::Pow(lhs, rhs),
+            Some('&') => Expr::And(lhs, rhs),
+            Some('|') => Expr::Or(lhs, rhs),
+            Some('<') => Expr::Shl(lhs, rhs),
+            Some('>') => Expr::Shr(lhs, rhs),
+            _ => Expr::Xor(lhs, rhs),
+        }))
+    }
+
+    /// Parse a numeric constant or a symbolic variable
+    fn parse_leaf(&mut self) -> Result<RcExpr, WeakEvalError> {
+        let start = self.position;
+        let rest = &self.input[start..];
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let Some(len) = quoted.find('"') else {
+                self.position = self.input.len();
+                return Err(self.unexpected(None, "'\"'"));
+            };
+            if len == 0 {
+                self.position += 1;
+                return Err(self.unexpected(self.peek(), "variable name"));
+            }
+            self.position += len + 2;
+            return Ok(Rc::new(Expr::SymbolicVariable(Symbol::intern(
+                &quoted[..len],
+            ))));
+        }
+        let len = rest
+            .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.unexpected(self.peek(), "constant or variable"));
+        }
+
+        let token = &rest[..len];
+        self.position += len;
+        if !token.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(Rc::new(Expr::SymbolicVariable(Symbol::intern(token))));
+        }
+        token
+            .parse()
+            .map(|constant| Rc::new(Expr::Const(constant)))
+            .map_err(|_| WeakEvalError::ConstantOutOfRange {
+                token: token.to_string(),
+                position: start,
+            })
+    }
+}
+
+/// Recursive descent parser for the S-expressions rendered by `to_sexpr()`
+///
+/// ```text
+/// expr := constant | variable | "(" op expr expr ")" | "(" "-" expr ")"
+/// op   := "+" | "-" | "*" | "/" | "^" | "&" | "|" | "xor" | "<<" | ">>"
+/// variable := identifier | '"' name '"'
+/// ```
+struct SexprParser<'a> {
+    /// Tokens of the input with their byte positions
+    tokens: Vec<(usize, &'a str)>,
+    /// Index of the next unparsed token
+    next: usize,
+}
+
+impl<'a> SexprParser<'a> {
+    /// Split an input into parentheses and whitespace separated atoms, keeping
+    /// quoted variable names whole
+    fn new(input: &'a str) -> Self {
+        let mut tokens = Vec::new();
+        let mut start = None;
+        let mut quoted = false;
+        for (position, c) in input.char_indices() {
+            if quoted {
+                if c == '"' {
+                    let start = start.take().unwrap_or(position);
+                    tokens.push((start, &input[start..=position]));
+                    quoted = false;
+                }
+            } else if c == '"' && start.is_none() {
+                start = Some(position);
+                quoted = true;
+            } else if c.is_whitespace() || c == '(' || c == ')' {
+                if let Some(start) = start.take() {
+                    tokens.push((start, &input[start..position]));
+                }
+                if !c.is_whitespace() {
+                    tokens.push((position, &input[position..position + 1]));
+                }
+            } else if start.is_none() {
+                start = Some(position);
+            }
+        }
+        if let Some(start) = start {
+            tokens.push((start, &input[start..]));
+        }
+        Self { tokens, next: 0 }
+    }
+
+    /// Consume the next token
+    ///
+    /// # Arguments
+    /// * `expected` - description of the expected token used in errors
+    fn take(&mut self, expected: &'static str) -> Result<(usize, &'a str), SexprError> {
+        let token = self
+            .tokens
+            .get(self.next)
+            .copied()
+            .ok_or(SexprError::UnexpectedEnd { expected })?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    /// Build the error for an unexpected token
+    fn unexpected((position, token): (usize, &str), expected: &'static str) -> SexprError {
+        SexprError::UnexpectedToken {
+            token: token.to_string(),
+            position,
+            expected,
+        }
+    }
+
+    /// Parse a whole input, rejecting trailing tokens
+    fn parse(mut self) -> Result<RcExpr, SexprError> {
+        let expr = self.parse_expr()?;
+        match self.tokens.get(self.next) {
+            None => Ok(expr),
+            Some(&token) => Err(Self::unexpected(token, "end of input")),
+        }
+    }
+
+    /// Parse a leaf or a parenthesized operation
+    fn parse_expr(&mut self) -> Result<RcExpr, SexprError> {
+        let token = self.take("expression")?;
+        match token.1 {
+            "(" => {}
+            ")" | "+" | "-" | "*" | "/" | "^" | "&" | "|" | "xor" | "<<" | ">>" => {
+                return Err(Self::unexpected(token, "expression"));
+            }
+            atom if atom.chars().all(|c| c.is_ascii_digit()) => {
+                return atom
+                    .parse()
+                    .map(|constant| Rc::new(Expr::Const(constant)))
+                    .map_err(|_| SexprError::ConstantOutOfRange {
+                        token: atom.to_string(),
+                        position: token.0,
+                    });
+            }
+            atom if atom.starts_with('"') => {
+                return atom
+                    .strip_prefix('"')
+                    .and_then(|atom| atom.strip_suffix('"'))
+                    .filter(|name| !name.is_empty())
+                    .map(|name| Rc::new(Expr::SymbolicVariable(Symbol::intern(name))))
+                    .ok_or_else(|| Self::unexpected(token, "variable name"));
+            }
+            atom => return Ok(Rc::new(Expr::SymbolicVariable(Symbol::intern(atom)))),
+        }
+
+        let op = self.take("operator")?;
+        let operation: fn(RcExpr, RcExpr) -> Expr = match op.1 {
+            "+" => Expr::Add,
+            "-" => Expr::Sub,
+            "*" => Expr::Mul,
+            "/" => Expr::Div,
+            "^" => Expr::Pow,
+            "&" => Expr::And,
+            "|" => Expr::Or,
+            "xor" => Expr::Xor,
+            "<<" => Expr::Shl,
+            ">>" => Expr::Shr,
+            _ => return Err(Self::unexpected(op, "operator")),
+        };
+        let lhs = self.parse_expr()?;
+        // `-` with a single operand is negation
+        if op.1 == "-"
+            && self
+                .tokens
+                .get(self.next)
+                .is_some_and(|(_, token)| *token == ")")
+        {
+            self.next += 1;
+            return Ok(Rc::new(Expr::Neg(lhs)));
+        }
+        let rhs = self.parse_expr()?;
+        let close = self.take("')'")?;
+        if close.1 != ")" {
+            return Err(Self::unexpected(close, "')'"));
+        }
+        Ok(Rc::new(operation(lhs, rhs)))
+    }
+}
+
+/// Enum representing the style used to render a strongly evaluated expression
+///
+/// # Variants
+/// * `Spaced` - operators surrounded by spaces, e.g. `(A + 1) * (B + 2)`
+/// * `Compact` - operators without surrounding spaces, e.g. `(A+1)*(B+2)`
+/// * `Latex` - LaTeX math, e.g. `\left(A + 1\right) \cdot \left(B + 2\right)`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderStyle {
+    #[default]
+    Spaced,
+    Compact,
+    Latex,
+}
+
+impl RenderStyle {
+    /// Render an operator symbol in this style
+    fn operator(self, op: &str) -> String {
+        match (self, op) {
+            (Self::Latex, "*") => " \\cdot ".to_string(),
+            (Self::Latex, "&") => " \\mathbin{\\&} ".to_string(),
+            (Self::Latex, "|") => " \\mathbin{|} ".to_string(),
+            (Self::Latex, "^") => " \\oplus ".to_string(),
+            (Self::Latex, "<<") => " \\ll ".to_string(),
+            (Self::Latex, ">>") => " \\gg ".to_string(),
+            (Self::Spaced | Self::Latex, _) => format!(" {} ", op),
+            (Self::Compact, _) => op.to_string(),
+        }
+    }
+
+    /// Wrap a subexpression in parentheses in this style
+    fn parenthesized(self, expr: &EvaluatedExpr) -> String {
+        match self {
+            Self::Latex => format!("\\left({}\\right)", expr),
+            Self::Spaced | Self::Compact => format!("({})", expr),
+        }
+    }
+}
+
 /// Struct representing an evaluated expression
 ///
 /// # Fields
 /// * `kind` - kind of the evaluated expression
 /// * `precedence` - precedence of the evaluated expression
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct EvaluatedExpr {
     kind: EvaluatedExprKind,
     precedence: Precedence,
@@ -80,89 +862,322 @@ impl std::fmt::Display for EvaluatedExpr {
     }
 }
 
-impl Add for EvaluatedExpr {
-    type Output = EvaluatedExpr;
+impl EvaluatedExpr {
+    /// Evaluate an expression tree rendering operators in the given style,
+    /// evaluating each subtree shared through `Rc` only once
+    ///
+    /// # Arguments
+    /// * `expr` - expression tree to evaluate
+    /// * `style` - style to render operators with
+    fn evaluate(expr: &RcExpr, style: RenderStyle) -> Self {
+        Self::evaluate_cached(expr, style, Some(&mut HashMap::new()))
+    }
 
-    fn add(self, rhs: Self) -> Self::Output {
+    /// Evaluate an expression tree rendering operators in the given style
+    ///
+    /// # Arguments
+    /// * `expr` - expression tree to evaluate
+    /// * `style` - style to render operators with
+    /// * `cache` - evaluations of shared subtrees keyed by node address, or
+    ///   `None` to evaluate every occurrence of a subtree
+    fn evaluate_cached(
+        expr: &RcExpr,
+        style: RenderStyle,
+        mut cache: Option<&mut HashMap<*const Expr, Self>>,
+    ) -> Self {
+        // Only nodes with several owners can be reached more than once
+        let shared = Rc::strong_count(expr) > 1;
+        let key = Rc::as_ptr(expr);
+        if let Some(evaluated) = cache.as_ref().and_then(|cache| cache.get(&key)) {
+            return evaluated.clone();
+        }
+
+        let mut evaluate = |expr| Self::evaluate_cached(expr, style, cache.as_deref_mut());
+        let evaluated = match expr.as_ref() {
+            Expr::Const(constant) => Self::from(*constant),
+            Expr::SymbolicVariable(value) => Self::from(value.to_string().as_str()),
+            Expr::Add(lhs, rhs) => evaluate(lhs).styled_add(evaluate(rhs), style),
+            Expr::Sub(lhs, rhs) => evaluate(lhs).styled_sub(evaluate(rhs), style),
+            Expr::Mul(lhs, rhs) => evaluate(lhs).styled_mul(evaluate(rhs), style),
+            Expr::Div(lhs, rhs) => evaluate(lhs).styled_div(evaluate(rhs), style),
+            Expr::Pow(lhs, rhs) => evaluate(lhs).styled_pow(evaluate(rhs), style),
+            Expr::Neg(operand) => evaluate(operand).styled_neg(style),
+            Expr::And(lhs, rhs) => {
+                evaluate(lhs).styled_bitwise(evaluate(rhs), Precedence::And, style)
+            }
+            Expr::Or(lhs, rhs) => {
+                evaluate(lhs).styled_bitwise(evaluate(rhs), Precedence::Or, style)
+            }
+            Expr::Xor(lhs, rhs) => {
+                evaluate(lhs).styled_bitwise(evaluate(rhs), Precedence::Xor, style)
+            }
+            Expr::Shl(lhs, rhs) => evaluate(lhs).styled_shift(evaluate(rhs), "<<", style),
+            Expr::Shr(lhs, rhs) => evaluate(lhs).styled_shift(evaluate(rhs), ">>", style),
+        };
+
+        if let Some(cache) = cache.filter(|_| shared) {
+            cache.insert(key, evaluated.clone());
+        }
+        evaluated
+    }
+
+    fn styled_add(self, rhs: Self, style: RenderStyle) -> Self {
         match (&self.kind, &rhs.kind, &rhs.precedence) {
             (EvaluatedExprKind::Numeric(lhs), EvaluatedExprKind::Numeric(rhs), _) => Self {
                 kind: EvaluatedExprKind::Numeric(lhs.wrapping_add(*rhs)),
                 precedence: Precedence::NumericOrSymbolicVariable,
             },
             _ => Self {
-                kind: EvaluatedExprKind::Value(format!("{} + {}", self, rhs)),
+                kind: EvaluatedExprKind::Value(format!(
+                    "{}{}{}",
+                    self.operand(Precedence::Add, style),
+                    style.operator("+"),
+                    rhs.operand(Precedence::Add, style)
+                )),
                 precedence: Precedence::Add,
             },
         }
     }
-}
-
-impl Sub for EvaluatedExpr {
-    type Output = EvaluatedExpr;
 
-    fn sub(self, rhs: Self) -> Self::Output {
+    fn styled_sub(self, rhs: Self, style: RenderStyle) -> Self {
         match (&self.kind, &rhs.kind, &rhs.precedence) {
             (EvaluatedExprKind::Numeric(lhs), EvaluatedExprKind::Numeric(rhs), _) => Self {
                 kind: EvaluatedExprKind::Numeric(lhs.wrapping_sub(*rhs)),
                 precedence: Precedence::NumericOrSymbolicVariable,
             },
-            (_, _, Precedence::Add) | (_, _, Precedence::Sub) => Self {
+            (_, _, precedence) if *precedence <= Precedence::Sub => Self {
                 // ((Expr) - (C + D)) = Expr - (C + D)
                 // ((Expr) - (C - D)) = Expr - (C - D)
-                kind: EvaluatedExprKind::Value(format!("{} - ({})", self, rhs)),
+                kind: EvaluatedExprKind::Value(format!(
+                    "{}{}{}",
+                    self.operand(Precedence::Add, style),
+                    style.operator("-"),
+                    style.parenthesized(&rhs)
+                )),
                 precedence: Precedence::Sub,
             },
             _ => Self {
-                kind: EvaluatedExprKind::Value(format!("{} - {}", self, rhs)),
+                kind: EvaluatedExprKind::Value(format!(
+                    "{}{}{}",
+                    self.operand(Precedence::Add, style),
+                    style.operator("-"),
+                    rhs
+                )),
                 precedence: Precedence::Sub,
             },
         }
     }
-}
-
-impl Mul for EvaluatedExpr {
-    type Output = EvaluatedExpr;
 
-    fn mul(self, rhs: Self) -> Self::Output {
+    fn styled_mul(self, rhs: Self, style: RenderStyle) -> Self {
         match (&self.kind, &rhs.kind) {
             (EvaluatedExprKind::Numeric(lhs), EvaluatedExprKind::Numeric(rhs)) => Self {
                 kind: EvaluatedExprKind::Numeric(lhs.wrapping_mul(*rhs)),
                 precedence: Precedence::NumericOrSymbolicVariable,
             },
             _ => {
-                let lhs = match self.precedence {
-                    Precedence::Add | Precedence::Sub => format!("({})", self),
-                    _ => format!("{}", self),
+                // Variables are juxtaposed in LaTeX, e.g. `A B` for `A * B`
+                let operator = match (style, &self.kind, &rhs.kind, &rhs.precedence) {
+                    (
+                        RenderStyle::Latex,
+                        EvaluatedExprKind::Value(_),
+                        EvaluatedExprKind::Value(_),
+                        Precedence::NumericOrSymbolicVariable,
+                    ) => " ".to_string(),
+                    _ => style.operator("*"),
                 };
+                let lhs = self.operand(Precedence::Mul, style);
                 let rhs = match rhs.precedence {
-                    Precedence::Add | Precedence::Sub => format!("({})", rhs),
-                    _ => format!("{}", rhs),
+                    // (Expr) * (C / D) = Expr * (C / D) as integer division
+                    // does not associate with multiplication
+                    Precedence::Div => style.parenthesized(&rhs),
+                    _ => rhs.operand(Precedence::Mul, style),
                 };
                 Self {
-                    kind: EvaluatedExprKind::Value(format!("{} * {}", lhs, rhs)),
+                    kind: EvaluatedExprKind::Value(format!("{}{}{}", lhs, operator, rhs)),
                     precedence: Precedence::Mul,
                 }
             }
         }
     }
-}
 
-impl From<&RcExpr> for EvaluatedExpr {
-    fn from(expr: &RcExpr) -> Self {
-        match expr.as_ref() {
-            Expr::Const(constant) => Self {
-                kind: EvaluatedExprKind::Numeric(*constant),
-                precedence: Precedence::NumericOrSymbolicVariable,
+    fn styled_div(self, rhs: Self, style: RenderStyle) -> Self {
+        match (&self.kind, &rhs.kind) {
+            (EvaluatedExprKind::Numeric(lhs), EvaluatedExprKind::Numeric(rhs)) if *rhs != 0 => {
+                Self {
+                    kind: EvaluatedExprKind::Numeric(lhs.wrapping_div(*rhs)),
+                    precedence: Precedence::NumericOrSymbolicVariable,
+                }
+            }
+            _ => {
+                let lhs = self.operand(Precedence::Mul, style);
+                let rhs = match rhs.precedence {
+                    Precedence::NumericOrSymbolicVariable => format!("{}", rhs),
+                    _ => style.parenthesized(&rhs),
+                };
+                Self {
+                    kind: EvaluatedExprKind::Value(format!(
+                        "{}{}{}",
+                        lhs,
+                        style.operator("/"),
+                        rhs
+                    )),
+                    precedence: Precedence::Div,
+                }
+            }
+        }
+    }
+
+    /// Raise to the power of `rhs`, rendered as `base^exponent` without spaces
+    /// in every style, or `base^{exponent}` in LaTeX
+    fn styled_pow(self, rhs: Self, style: RenderStyle) -> Self {
+        match (&self.kind, &rhs.kind) {
+            (EvaluatedExprKind::Numeric(lhs), EvaluatedExprKind::Numeric(rhs)) => {
+                Self::from(lhs.wrapping_pow(*rhs))
+            }
+            _ => {
+                // (A + B)^2, (-A)^2 and (A^2)^3 need parentheses around the
+                // base, and any operation needs them around the exponent
+                let lhs = match self.precedence {
+                    Precedence::NumericOrSymbolicVariable => format!("{}", self),
+                    _ => style.parenthesized(&self),
+                };
+                let rhs = match (style, &rhs.precedence) {
+                    (RenderStyle::Latex, _) => format!("{{{}}}", rhs),
+                    (_, Precedence::NumericOrSymbolicVariable) => format!("{}", rhs),
+                    _ => style.parenthesized(&rhs),
+                };
+                Self {
+                    kind: EvaluatedExprKind::Value(format!("{}^{}", lhs, rhs)),
+                    precedence: Precedence::Pow,
+                }
+            }
+        }
+    }
+
+    /// Apply the bitwise operation of the given precedence, parenthesizing
+    /// operands that bind more loosely, e.g. `(A | B) & C`, while arithmetic
+    /// operands bind more tightly and need no parentheses, e.g. `A + 1 & B`
+    fn styled_bitwise(self, rhs: Self, op: Precedence, style: RenderStyle) -> Self {
+        let (symbol, fold): (&str, fn(u32, u32) -> u32) = match op {
+            Precedence::And => ("&", |lhs, rhs| lhs & rhs),
+            Precedence::Xor => ("^", |lhs, rhs| lhs ^ rhs),
+            _ => ("|", |lhs, rhs| lhs | rhs),
+        };
+        match (&self.kind, &rhs.kind) {
+            (EvaluatedExprKind::Numeric(lhs), EvaluatedExprKind::Numeric(rhs)) => {
+                Self::from(fold(*lhs, *rhs))
+            }
+            _ => Self {
+                kind: EvaluatedExprKind::Value(format!(
+                    "{}{}{}",
+                    self.operand(op, style),
+                    style.operator(symbol),
+                    rhs.operand(op, style)
+                )),
+                precedence: op,
             },
-            Expr::SymbolicVariable(value) => Self {
-                kind: EvaluatedExprKind::Value(value.to_string()),
-                precedence: Precedence::NumericOrSymbolicVariable,
+        }
+    }
+
+    /// Shift by `rhs` with the given operator, `<<` or `>>`, parenthesizing
+    /// bitwise operands and shifted amounts, e.g. `(A << B) << C` is rendered
+    /// as `A << B << C` while `A << (B << C)` keeps its parentheses
+    fn styled_shift(self, rhs: Self, op: &str, style: RenderStyle) -> Self {
+        match (&self.kind, &rhs.kind) {
+            (EvaluatedExprKind::Numeric(lhs), EvaluatedExprKind::Numeric(rhs)) => {
+                Self::from(match op {
+                    "<<" => lhs.wrapping_shl(*rhs),
+                    _ => lhs.wrapping_shr(*rhs),
+                })
+            }
+            _ => Self {
+                kind: EvaluatedExprKind::Value(format!(
+                    "{}{}{}",
+                    self.operand(Precedence::Shift, style),
+                    style.operator(op),
+                    rhs.operand(Precedence::Add, style)
+                )),
+                precedence: Precedence::Shift,
             },
-            Expr::Add(lhs, rhs) => Self::from(lhs) + rhs.into(),
-            Expr::Sub(lhs, rhs) => Self::from(lhs) - rhs.into(),
-            Expr::Mul(lhs, rhs) => Self::from(lhs) * rhs.into(),
         }
     }
+
+    /// Render as an operand, parenthesized if it binds more loosely than the
+    /// given precedence
+    ///
+    /// # Arguments
+    /// * `precedence` - loosest precedence the operand may have without
+    ///   parentheses
+    /// * `style` - style to render parentheses with
+    fn operand(&self, precedence: Precedence, style: RenderStyle) -> String {
+        if self.precedence < precedence {
+            style.parenthesized(self)
+        } else {
+            format!("{}", self)
+        }
+    }
+
+    fn styled_neg(self, style: RenderStyle) -> Self {
+        match self.kind {
+            EvaluatedExprKind::Numeric(operand) => Self::from(operand.wrapping_neg()),
+            _ => {
+                let operand = match self.precedence {
+                    Precedence::NumericOrSymbolicVariable => format!("{}", self),
+                    _ => style.parenthesized(&self),
+                };
+                Self {
+                    kind: EvaluatedExprKind::Value(format!("-{}", operand)),
+                    precedence: Precedence::Neg,
+                }
+            }
+        }
+    }
+}
+
+impl Add for EvaluatedExpr {
+    type Output = EvaluatedExpr;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.styled_add(rhs, RenderStyle::default())
+    }
+}
+
+impl Sub for EvaluatedExpr {
+    type Output = EvaluatedExpr;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.styled_sub(rhs, RenderStyle::default())
+    }
+}
+
+impl Mul for EvaluatedExpr {
+    type Output = EvaluatedExpr;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.styled_mul(rhs, RenderStyle::default())
+    }
+}
+
+impl Div for EvaluatedExpr {
+    type Output = EvaluatedExpr;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.styled_div(rhs, RenderStyle::default())
+    }
+}
+
+impl Neg for EvaluatedExpr {
+    type Output = EvaluatedExpr;
+
+    fn neg(self) -> Self::Output {
+        self.styled_neg(RenderStyle::default())
+    }
+}
+
+impl From<&RcExpr> for EvaluatedExpr {
+    fn from(expr: &RcExpr) -> Self {
+        Self::evaluate(expr, RenderStyle::default())
+    }
 }
 
 impl From<u32> for EvaluatedExpr {
@@ -184,8 +1199,14 @@ impl From<&str> for EvaluatedExpr {
 }
 
 /// Public wrapper for `Expr`
-#[derive(Debug, Clone)]
-pub(crate) struct ExprWrapper(RcExpr);
+///
+/// # Note
+/// Equality is structural: constants are equal by value, symbolic variables
+/// by name and operations when their operands are equal in order, so `A + B`
+/// and `B + A` are not equal even though they are mathematically equivalent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExprWrapper(RcExpr);
 
 impl std::fmt::Display for ExprWrapper {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -213,24 +1234,159 @@ impl Sub for &ExprWrapper {
     fn sub(self, rhs: &ExprWrapper) -> Self::Output {
         ExprWrapper::new(Expr::Sub(Rc::clone(&self.0), Rc::clone(&rhs.0)))
     }
-}
+}
+
+impl Mul for &ExprWrapper {
+    type Output = ExprWrapper;
+
+    fn mul(self, rhs: &ExprWrapper) -> Self::Output {
+        ExprWrapper::new(Expr::Mul(Rc::clone(&self.0), Rc::clone(&rhs.0)))
+    }
+}
+
+impl Div for &ExprWrapper {
+    type Output = ExprWrapper;
+
+    fn div(self, rhs: &ExprWrapper) -> Self::Output {
+        ExprWrapper::new(Expr::Div(Rc::clone(&self.0), Rc::clone(&rhs.0)))
+    }
+}
+
+impl Neg for &ExprWrapper {
+    type Output = ExprWrapper;
+
+    fn neg(self) -> Self::Output {
+        ExprWrapper::new(Expr::Neg(Rc::clone(&self.0)))
+    }
+}
+
+impl Shl for &ExprWrapper {
+    type Output = ExprWrapper;
+
+    fn shl(self, rhs: &ExprWrapper) -> Self::Output {
+        ExprWrapper::new(Expr::Shl(Rc::clone(&self.0), Rc::clone(&rhs.0)))
+    }
+}
+
+impl Shr for &ExprWrapper {
+    type Output = ExprWrapper;
+
+    fn shr(self, rhs: &ExprWrapper) -> Self::Output {
+        ExprWrapper::new(Expr::Shr(Rc::clone(&self.0), Rc::clone(&rhs.0)))
+    }
+}
+
+impl BitAnd for &ExprWrapper {
+    type Output = ExprWrapper;
+
+    fn bitand(self, rhs: &ExprWrapper) -> Self::Output {
+        ExprWrapper::new(Expr::And(Rc::clone(&self.0), Rc::clone(&rhs.0)))
+    }
+}
+
+impl BitOr for &ExprWrapper {
+    type Output = ExprWrapper;
+
+    fn bitor(self, rhs: &ExprWrapper) -> Self::Output {
+        ExprWrapper::new(Expr::Or(Rc::clone(&self.0), Rc::clone(&rhs.0)))
+    }
+}
+
+impl BitXor for &ExprWrapper {
+    type Output = ExprWrapper;
+
+    fn bitxor(self, rhs: &ExprWrapper) -> Self::Output {
+        ExprWrapper::new(Expr::Xor(Rc::clone(&self.0), Rc::clone(&rhs.0)))
+    }
+}
+
+impl ExprWrapper {
+    fn new(expr: Expr) -> Self {
+        Self(Rc::new(expr))
+    }
+
+    /// Fold the root operation into a numeric constant if both of its operands
+    /// are numeric constants, with overflows handled by wrap-around
+    ///
+    /// # Returns
+    /// * `ExprWrapper` - folded expression, or a clone of `self` if the root
+    ///   cannot be folded
+    pub(crate) fn folded(&self) -> Self {
+        match self.0.as_ref() {
+            Expr::Const(_) | Expr::SymbolicVariable(_) => self.clone(),
+            expr => match expr.clone().folded() {
+                folded @ Expr::Const(_) => Self::new(folded),
+                _ => self.clone(),
+            },
+        }
+    }
+
+    /// Check whether the root operation overflows 32 bits when folded, with
+    /// both of its operands strongly evaluating to numeric constants
+    ///
+    /// # Note
+    /// Only `add`, `sub`, `mul`, `pow` and `neg` can overflow. Divisions,
+    /// bitwise operations and shifts never do.
+    ///
+    /// # Returns
+    /// * `bool` - whether folding the root operation would wrap around
+    pub(crate) fn overflows(&self) -> bool {
+        let value = |expr: &RcExpr| Self(Rc::clone(expr)).as_const();
+        let (checked, lhs, rhs): (fn(u32, u32) -> Option<u32>, _, _) = match self.0.as_ref() {
+            Expr::Add(lhs, rhs) => (u32::checked_add, lhs, rhs),
+            Expr::Sub(lhs, rhs) => (u32::checked_sub, lhs, rhs),
+            Expr::Mul(lhs, rhs) => (u32::checked_mul, lhs, rhs),
+            Expr::Pow(lhs, rhs) => (u32::checked_pow, lhs, rhs),
+            Expr::Neg(operand) => return value(operand).is_some_and(|v| v.checked_neg().is_none()),
+            _ => return false,
+        };
+        match (value(lhs), value(rhs)) {
+            (Some(lhs), Some(rhs)) => checked(lhs, rhs).is_none(),
+            _ => false,
+        }
+    }
 
-impl Mul for &ExprWrapper {
-    type Output = ExprWrapper;
+    /// Raise the expression to the power of another expression
+    ///
+    /// # Arguments
+    /// * `exponent` - exponent expression
+    ///
+    /// # Returns
+    /// * `ExprWrapper` - new expression tree with the exponentiation at its root
+    pub fn pow(&self, exponent: &ExprWrapper) -> Self {
+        Self::new(Expr::Pow(Rc::clone(&self.0), Rc::clone(&exponent.0)))
+    }
 
-    fn mul(self, rhs: &ExprWrapper) -> Self::Output {
-        ExprWrapper::new(Expr::Mul(Rc::clone(&self.0), Rc::clone(&rhs.0)))
+    /// Create a new `ExprWrapper` from a symbolic variable, interning its name
+    pub fn from_symbolic_variable<S: Into<String>>(value: S) -> Self {
+        Self::new(Expr::SymbolicVariable(Symbol::intern(&value.into())))
     }
-}
 
-impl ExprWrapper {
-    fn new(expr: Expr) -> Self {
-        Self(Rc::new(expr))
+    /// Reconstruct an expression tree from its `weak_eval()` rendering
+    ///
+    /// # Arguments
+    /// * `s` - fully parenthesized expression, with every operation written
+    ///   as `(lhs op rhs)` and separated by single spaces
+    ///
+    /// # Returns
+    /// * `Ok(ExprWrapper)` - if `s` follows the `weak_eval()` grammar
+    /// * `Err(WeakEvalError)` - otherwise
+    pub fn from_weak_eval(s: &str) -> Result<Self, WeakEvalError> {
+        WeakEvalParser::new(s).parse().map(Self)
     }
 
-    /// Create a new `ExprWrapper` from a symbolic variable
-    pub fn from_symbolic_variable<S: Into<String>>(value: S) -> Self {
-        Self::new(Expr::SymbolicVariable(value.into()))
+    /// Reconstruct an expression tree from an S-expression, see `to_sexpr()`
+    ///
+    /// # Arguments
+    /// * `s` - S-expression with integer tokens as numeric constants and any
+    ///   other non-operator token as a symbolic variable, e.g.
+    ///   `(* (+ A 1) 2)`
+    ///
+    /// # Returns
+    /// * `Ok(ExprWrapper)` - if `s` is a single well-formed S-expression
+    /// * `Err(SexprError)` - otherwise
+    pub fn from_sexpr(s: &str) -> Result<Self, SexprError> {
+        SexprParser::new(s).parse().map(Self)
     }
 
     /// Evaluate the expression tree by simply applying parentheses
@@ -242,6 +1398,22 @@ impl ExprWrapper {
         self.0.to_string()
     }
 
+    /// Render the expression tree as an S-expression, with every operation in
+    /// prefix form and parenthesized, and constants and symbolic variables as
+    /// bare tokens
+    ///
+    /// # Note
+    /// Operators are written as in `weak_eval()`, so `(^ A 2)` is
+    /// exponentiation and `(xor A B)` is bitwise XOR, and negation is the
+    /// unary `(- A)`. Each operation maps to exactly one form, so
+    /// `from_sexpr()` rebuilds a structurally equal tree.
+    ///
+    /// # Returns
+    /// * `String` - S-expression, e.g. `(* (+ A 1) (+ B 2))`
+    pub fn to_sexpr(&self) -> String {
+        sexpr(&self.0)
+    }
+
     /// Evaluate the expression tree by applying parentheses
     /// only when necessary and resolve operations with only
     /// numeric operands
@@ -262,6 +1434,219 @@ impl ExprWrapper {
     pub fn strong_eval(&self) -> String {
         EvaluatedExpr::from(&self.0).to_string()
     }
+
+    /// Bind some symbolic variables to numeric values, leaving the rest
+    /// symbolic
+    ///
+    /// # Arguments
+    /// * `bindings` - numeric values of the symbolic variables to bind
+    ///
+    /// # Returns
+    /// * `ExprWrapper` - new expression tree with bound variables replaced by
+    ///   numeric constants and purely numeric operations folded
+    pub fn partial_evaluate(&self, bindings: &HashMap<String, u32>) -> Self {
+        Self(substitute(&self.0, bindings))
+    }
+
+    /// Compute the numeric value of the expression with every symbolic variable
+    /// bound, with overflows handled by wrap-around as in `strong_eval()`
+    ///
+    /// # Arguments
+    /// * `bindings` - numeric values of the symbolic variables
+    ///
+    /// # Returns
+    /// * `Ok(u32)` - numeric value of the expression
+    /// * `Err(EvalError::UnboundVariable)` - if a symbolic variable is not
+    ///   bound
+    /// * `Err(EvalError::DivisionByZero)` - if a divisor evaluates to zero
+    pub fn evaluate(&self, bindings: &HashMap<String, u32>) -> Result<u32, EvalError> {
+        evaluate_numeric(&self.0, bindings)
+    }
+
+    /// Expand the expression into a canonical sum of monomials, with overflows
+    /// handled by wrap-around
+    ///
+    /// # Arguments
+    /// * `order` - ordering of the symbolic variables within each monomial and
+    ///   of the monomials, with the constant term last
+    ///
+    /// # Returns
+    /// * `String` - expanded polynomial, e.g. `A * A + 2 * B + 1`
+    pub fn expanded(&self, order: &VariableOrder) -> String {
+        expand(&self.0).render(order)
+    }
+
+    /// Simplify the expression by expanding it into a sum of monomials,
+    /// combining like terms and dropping terms that cancel out, with overflows
+    /// handled by wrap-around
+    ///
+    /// # Note
+    /// Unlike `weak_eval()` and `strong_eval()`, which preserve the evaluation
+    /// order of the program, the terms are reordered canonically with
+    /// variables sorted by name, so `B + A` and `A + B` simplify identically.
+    ///
+    /// E.g.
+    /// Strong eval: `2 * A + B + 3 * A - B`
+    /// Simplify eval: `5 * A`
+    ///
+    /// # Returns
+    /// * `String` - simplified expression, see `expanded()`
+    pub fn simplify_eval(&self) -> String {
+        self.expanded(&VariableOrder::Lexicographic)
+    }
+
+    /// Check whether two expressions are mathematically equivalent by comparing
+    /// their expansions into sums of monomials, with overflows handled by
+    /// wrap-around
+    ///
+    /// # Note
+    /// Divisions and exponentiations that cannot be expanded are compared
+    /// structurally, so `A / B` and `(A * 2) / (B * 2)` are not equivalent.
+    ///
+    /// # Arguments
+    /// * `other` - expression to compare against
+    ///
+    /// # Returns
+    /// * `bool` - whether both expressions expand to the same polynomial
+    pub fn equivalent_to(&self, other: &ExprWrapper) -> bool {
+        expand(&self.0) == expand(&other.0)
+    }
+
+    /// Get the coefficient of each symbolic variable and the constant term of
+    /// an affine expression, with overflows handled by wrap-around
+    ///
+    /// # Returns
+    /// * `Some((coefficients, constant))` - if the expanded expression has a
+    ///   degree of at most `1`, omitting variables whose coefficients cancel
+    ///   out to zero
+    /// * `None` - if the expression is nonlinear
+    pub fn linear_coefficients(&self) -> Option<(HashMap<String, u32>, u32)> {
+        let polynomial = expand(&self.0);
+        if polynomial.degree() > 1 {
+            return None;
+        }
+
+        let mut coefficients = HashMap::new();
+        let mut constant = 0;
+        for (monomial, coefficient) in polynomial.terms() {
+            match monomial.as_slice() {
+                [] => constant = coefficient,
+                [variable] => {
+                    coefficients.insert(variable.clone(), coefficient);
+                }
+                _ => unreachable!(),
+            }
+        }
+        Some((coefficients, constant))
+    }
+
+    /// Get the dense coefficient vector of a univariate expression, with
+    /// overflows handled by wrap-around
+    ///
+    /// # Arguments
+    /// * `var` - the only symbolic variable allowed in the expression
+    ///
+    /// # Returns
+    /// * `Ok(Vec<u32>)` - coefficients `[c0, c1, ..., cn]` where `n` is the
+    ///   degree of the expanded expression, `[0]` for the zero polynomial
+    /// * `Err(CoeffError)` - if the expression contains another symbolic
+    ///   variable
+    pub fn univariate_coefficients(&self, var: &str) -> Result<Vec<u32>, CoeffError> {
+        let polynomial = expand(&self.0);
+        let mut coefficients = vec![0; polynomial.degree() + 1];
+        for (monomial, coefficient) in polynomial.terms() {
+            if let Some(found) = monomial.iter().find(|v| *v != var) {
+                return Err(CoeffError::UnexpectedVariable {
+                    found: found.clone(),
+                    expected: var.to_string(),
+                });
+            }
+            coefficients[monomial.len()] = coefficient;
+        }
+        Ok(coefficients)
+    }
+
+    /// Conservatively bound the numeric result given the range of each
+    /// symbolic variable, with overflows saturated at the `u32` bounds rather
+    /// than wrapped around
+    ///
+    /// # Arguments
+    /// * `ranges` - inclusive `(min, max)` range of symbolic variables, unbound
+    ///   variables range over every `u32`
+    ///
+    /// # Returns
+    /// * `(u32, u32)` - inclusive `(min, max)` range of the result
+    pub fn evaluate_interval(&self, ranges: &HashMap<String, (u32, u32)>) -> (u32, u32) {
+        interval(&self.0, ranges)
+    }
+
+    /// Count how many times each symbolic variable appears as a leaf of the
+    /// expression tree, so a variable used twice is counted twice
+    ///
+    /// # Returns
+    /// * `HashMap<String, usize>` - number of occurrences of each symbolic
+    ///   variable
+    pub fn variable_occurrences(&self) -> HashMap<String, usize> {
+        let mut occurrences = HashMap::new();
+        count_variables(&self.0, &mut occurrences);
+        occurrences
+    }
+
+    /// Get the total degree of the expression once expanded into a sum of
+    /// monomials, e.g. `2` for `(A + 1) * (A + 2)`
+    ///
+    /// # Note
+    /// Divisions, exponentiations and bitwise operations that cannot be
+    /// expanded count as a single opaque variable, see `simplify_eval()`.
+    ///
+    /// # Returns
+    /// * `u32` - highest total degree among the monomials, `0` for constants
+    pub fn degree(&self) -> u32 {
+        expand(&self.0).degree() as u32
+    }
+
+    /// Get the names of the symbolic variables appearing in the expression
+    /// tree, including those whose terms cancel out once expanded
+    ///
+    /// # Returns
+    /// * `BTreeSet<String>` - symbolic variable names in lexicographic order
+    pub fn variables(&self) -> BTreeSet<String> {
+        self.variable_occurrences().into_keys().collect()
+    }
+
+    /// Get the numeric value of the expression if it strongly evaluates to a
+    /// numeric constant
+    ///
+    /// # Returns
+    /// * `Some(u32)` - if the expression contains no symbolic variables
+    /// * `None` - otherwise
+    pub fn as_const(&self) -> Option<u32> {
+        match EvaluatedExpr::from(&self.0).kind {
+            EvaluatedExprKind::Numeric(value) => Some(value),
+            EvaluatedExprKind::Value(_) => None,
+        }
+    }
+
+    /// Strongly evaluate the expression tree rendering operators in the given
+    /// style, see `strong_eval()`
+    ///
+    /// # Arguments
+    /// * `style` - style to render operators with
+    ///
+    /// # Returns
+    /// * `String` - strongly evaluated expression
+    pub fn render(&self, style: RenderStyle) -> String {
+        EvaluatedExpr::evaluate(&self.0, style).to_string()
+    }
+
+    /// Strongly evaluate the expression tree as LaTeX math, with the same
+    /// parentheses as `strong_eval()` rendered as `\left( \right)`
+    ///
+    /// # Returns
+    /// * `String` - LaTeX expression, e.g. `2 \cdot A B + C^{3}`
+    pub fn to_latex(&self) -> String {
+        self.render(RenderStyle::Latex)
+    }
 }
 
 #[cfg(test)]
@@ -298,6 +1683,42 @@ mod test {
         assert_eq!(value, 1_705_032_704);
     }
 
+    #[test]
+    fn test_overflows() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let max = ExprWrapper::from(u32::MAX);
+        assert!((&max + &1.into()).overflows());
+        assert!(!(&max + &0.into()).overflows());
+        assert!((&ExprWrapper::from(1) - &2.into()).overflows());
+        assert!((&max * &2.into()).overflows());
+        assert!(ExprWrapper::from(2).pow(&32.into()).overflows());
+        assert!((-&ExprWrapper::from(1)).overflows());
+        assert!(!(-&ExprWrapper::from(0)).overflows());
+        assert!(!(&max + &a).overflows());
+        assert!(!(&max << &1.into()).overflows());
+    }
+
+    #[test]
+    fn test_strong_eval_memoized() {
+        let [a, b, c, d] = ["A", "B", "C", "D"].map(ExprWrapper::from_symbolic_variable);
+        let [one, two, three, four] = [1, 2, 3, 4].map(ExprWrapper::from);
+        // Long polynomial with subtrees shared between its terms
+        let lhs = &(&a + &one) * &(&b + &two);
+        let rhs = &(&c - &d) + &three;
+        let cd = &(&four * &c) * &d;
+        let mut expr = &(&lhs * &rhs) + &cd;
+        for _ in 0..8 {
+            expr = &(&expr * &(&expr - &cd)) + &(&lhs / &expr);
+        }
+
+        for style in [RenderStyle::Spaced, RenderStyle::Latex] {
+            let memoized = EvaluatedExpr::evaluate(&expr.0, style);
+            let unmemoized = EvaluatedExpr::evaluate_cached(&expr.0, style, None);
+            assert_eq!(memoized.to_string(), unmemoized.to_string());
+            assert_eq!(memoized.precedence, unmemoized.precedence);
+        }
+    }
+
     #[test]
     fn test_strong_eval_numeric() {
         let EvaluatedExpr {
@@ -436,4 +1857,728 @@ mod test {
         };
         assert_eq!(value, "A + B - C");
     }
+
+    #[test]
+    fn test_strong_eval_div() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let c = ExprWrapper::from_symbolic_variable("C");
+
+        assert_eq!((&a / &b).weak_eval(), "(A / B)");
+        assert_eq!((&(&a + &b) / &c).strong_eval(), "(A + B) / C");
+        assert_eq!((&a / &(&b * &c)).strong_eval(), "A / (B * C)");
+        assert_eq!((&a / &(&b / &c)).strong_eval(), "A / (B / C)");
+        assert_eq!((&(&a / &b) / &c).strong_eval(), "A / B / C");
+        assert_eq!((&(&a / &b) * &c).strong_eval(), "A / B * C");
+        assert_eq!((&a * &(&b / &c)).strong_eval(), "A * (B / C)");
+        assert_eq!((&a - &(&b / &c)).strong_eval(), "A - B / C");
+
+        assert_eq!((&ExprWrapper::from(7) / &2.into()).strong_eval(), "3");
+        assert_eq!((&ExprWrapper::from(7) / &0.into()).strong_eval(), "7 / 0");
+        assert_eq!(
+            (&ExprWrapper::from(7) / &0.into()).folded().weak_eval(),
+            "(7 / 0)"
+        );
+    }
+
+    #[test]
+    fn test_strong_eval_neg() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+
+        assert_eq!((-&a).weak_eval(), "(-A)");
+        assert_eq!((-&a).strong_eval(), "-A");
+        assert_eq!((-&(&a + &b)).strong_eval(), "-(A + B)");
+        assert_eq!((-&(&a * &b)).strong_eval(), "-(A * B)");
+        assert_eq!((-&-&a).strong_eval(), "-(-A)");
+        assert_eq!((&b - &-&a).strong_eval(), "B - -A");
+        assert_eq!((&b / &-&a).strong_eval(), "B / (-A)");
+        assert_eq!((-&ExprWrapper::from(1)).strong_eval(), u32::MAX.to_string());
+        assert_eq!(
+            (-&ExprWrapper::from(1)).folded(),
+            ExprWrapper::from(u32::MAX)
+        );
+
+        let expr = &-&(&a + &1.into()) + &a;
+        assert_eq!(
+            expr.expanded(&VariableOrder::default()),
+            u32::MAX.to_string()
+        );
+        assert_eq!(
+            ExprWrapper::from_weak_eval(&expr.weak_eval()),
+            Ok(expr.clone())
+        );
+        assert_eq!(
+            expr.variable_occurrences(),
+            HashMap::from([("A".to_string(), 2)])
+        );
+    }
+
+    #[test]
+    fn test_strong_eval_pow() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let two = ExprWrapper::from(2);
+        let three = ExprWrapper::from(3);
+
+        assert_eq!(a.pow(&three).weak_eval(), "(A ^ 3)");
+        assert_eq!(a.pow(&three).strong_eval(), "A^3");
+        assert_eq!(
+            (&a.pow(&three) + &(&two * &a.pow(&two))).strong_eval(),
+            "A^3 + 2 * A^2"
+        );
+        assert_eq!((&a + &b).pow(&two).strong_eval(), "(A + B)^2");
+        assert_eq!((&a * &b).pow(&two).strong_eval(), "(A * B)^2");
+        assert_eq!((-&a).pow(&two).strong_eval(), "(-A)^2");
+        assert_eq!(a.pow(&two).pow(&three).strong_eval(), "(A^2)^3");
+        assert_eq!(a.pow(&(&b + &two)).strong_eval(), "A^(B + 2)");
+        assert_eq!(
+            a.pow(&three).render(RenderStyle::Compact),
+            "A^3".to_string()
+        );
+        assert_eq!(two.pow(&three).strong_eval(), "8");
+        assert_eq!(two.pow(&32.into()).strong_eval(), "0");
+        assert_eq!(two.pow(&three).folded(), ExprWrapper::from(8));
+    }
+
+    #[test]
+    fn test_strong_eval_bitwise() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let c = ExprWrapper::from_symbolic_variable("C");
+
+        assert_eq!((&ExprWrapper::from(12) & &10.into()).strong_eval(), "8");
+        assert_eq!((&ExprWrapper::from(12) | &10.into()).strong_eval(), "14");
+        assert_eq!((&ExprWrapper::from(12) ^ &10.into()).strong_eval(), "6");
+        assert_eq!((&a & &b).strong_eval(), "A & B");
+        assert_eq!((&a | &b).strong_eval(), "A | B");
+        assert_eq!((&a ^ &b).strong_eval(), "A ^ B");
+        assert_eq!((&a ^ &b).weak_eval(), "(A xor B)");
+
+        // & binds tighter than ^, which binds tighter than |
+        assert_eq!((&(&a | &b) & &c).strong_eval(), "(A | B) & C");
+        assert_eq!((&(&a & &b) | &c).strong_eval(), "A & B | C");
+        assert_eq!((&a ^ &(&b | &c)).strong_eval(), "A ^ (B | C)");
+        assert_eq!((&(&a & &b) & &c).strong_eval(), "A & B & C");
+
+        // Arithmetic binds tighter than any bitwise operation
+        assert_eq!((&(&a + &1.into()) & &b).strong_eval(), "A + 1 & B");
+        assert_eq!((&(&a & &b) + &c).strong_eval(), "(A & B) + C");
+        assert_eq!((&c - &(&a | &b)).strong_eval(), "C - (A | B)");
+        assert_eq!((&(&a ^ &b) * &c).strong_eval(), "(A ^ B) * C");
+        assert_eq!((&a & &b).pow(&2.into()).strong_eval(), "(A & B)^2");
+        assert_eq!((&a ^ &b).to_latex(), r"A \oplus B");
+    }
+
+    #[test]
+    fn test_strong_eval_shift() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let c = ExprWrapper::from_symbolic_variable("C");
+
+        assert_eq!((&ExprWrapper::from(3) << &4.into()).strong_eval(), "48");
+        assert_eq!((&ExprWrapper::from(48) >> &4.into()).strong_eval(), "3");
+        // Shift amounts of 32 or more wrap around
+        assert_eq!((&ExprWrapper::from(3) << &33.into()).strong_eval(), "6");
+        assert_eq!((&ExprWrapper::from(48) >> &36.into()).strong_eval(), "3");
+        assert_eq!((&a << &b).weak_eval(), "(A << B)");
+        assert_eq!((&(&a << &b) << &c).strong_eval(), "A << B << C");
+        assert_eq!((&a >> &(&b >> &c)).strong_eval(), "A >> (B >> C)");
+
+        // Arithmetic binds tighter than shifts, which bind tighter than
+        // bitwise operations
+        assert_eq!(
+            (&(&a + &1.into()) << &(&b - &c)).strong_eval(),
+            "A + 1 << B - C"
+        );
+        assert_eq!((&(&a << &1.into()) + &b).strong_eval(), "(A << 1) + B");
+        assert_eq!((&(&a << &1.into()) & &b).strong_eval(), "A << 1 & B");
+        assert_eq!((&(&a & &b) >> &c).strong_eval(), "(A & B) >> C");
+        assert_eq!((&a << &b).to_latex(), r"A \ll B");
+
+        let expr = &a << &2.into();
+        assert_eq!(
+            ExprWrapper::from_weak_eval(&expr.weak_eval()),
+            Ok(expr.clone())
+        );
+        assert_eq!(
+            expr.evaluate(&HashMap::from([("A".to_string(), 5)])),
+            Ok(20)
+        );
+        assert_eq!(
+            expr.evaluate_interval(&HashMap::from([("A".to_string(), (1, 5))])),
+            (4, 20)
+        );
+        assert_eq!((&a << &b).evaluate_interval(&HashMap::new()), (0, u32::MAX));
+    }
+
+    #[test]
+    fn test_bitwise_analysis() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let expr = &(&a & &12.into()) | &(&ExprWrapper::from(3) ^ &1.into());
+        assert_eq!(
+            expr.evaluate(&HashMap::from([("A".to_string(), 10)])),
+            Ok(10)
+        );
+        assert_eq!(
+            expr.partial_evaluate(&HashMap::from([("A".to_string(), 10)])),
+            ExprWrapper::from(10)
+        );
+        // Symbolic bitwise operations are kept opaque by the expansion
+        assert_eq!(expr.simplify_eval(), "((A & 12) | (3 xor 1))");
+        assert_eq!((&ExprWrapper::from(3) ^ &1.into()).simplify_eval(), "2");
+        assert_eq!(
+            expr.evaluate_interval(&HashMap::from([("A".to_string(), (0, 100))])),
+            (0, 15)
+        );
+        assert_eq!(
+            ExprWrapper::from_weak_eval(&expr.weak_eval()),
+            Ok(expr.clone())
+        );
+        assert_eq!(expr.variable_occurrences()["A"], 1);
+    }
+
+    #[test]
+    fn test_pow_analysis() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+
+        let expr = (&a + &1.into()).pow(&3.into());
+        assert_eq!(
+            expr.univariate_coefficients("A"),
+            Ok(Vec::from([1, 3, 3, 1]))
+        );
+        assert_eq!(
+            a.pow(&0.into()).univariate_coefficients("A"),
+            Ok(Vec::from([1]))
+        );
+        assert_eq!(
+            a.pow(&b).expanded(&VariableOrder::default()),
+            "(A ^ B)".to_string()
+        );
+        assert_eq!(
+            expr.partial_evaluate(&HashMap::from([("A".to_string(), 2)])),
+            ExprWrapper::from(27)
+        );
+        assert_eq!(
+            a.pow(&b).evaluate_interval(&HashMap::from([
+                ("A".to_string(), (0, 3)),
+                ("B".to_string(), (0, 2))
+            ])),
+            (0, 9)
+        );
+        assert_eq!(
+            ExprWrapper::from_weak_eval(&expr.weak_eval()),
+            Ok(expr.clone())
+        );
+        assert_eq!(
+            a.pow(&a).variable_occurrences(),
+            HashMap::from([("A".to_string(), 2)])
+        );
+    }
+
+    #[test]
+    fn test_div_analysis() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+
+        let expr = &(&(&a / &b) * &2.into()) + &(&ExprWrapper::from(9) / &4.into());
+        assert_eq!(expr.expanded(&VariableOrder::default()), "2 * (A / B) + 2");
+        assert_eq!(
+            expr.partial_evaluate(&HashMap::from([("A".to_string(), 9), ("B".to_string(), 2)]))
+                .as_const(),
+            Some(10)
+        );
+
+        let ranges = HashMap::from([("A".to_string(), (10, 20)), ("B".to_string(), (0, 5))]);
+        assert_eq!((&a / &b).evaluate_interval(&ranges), (2, 20));
+    }
+
+    #[test]
+    fn test_partial_evaluate_with_fold() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let expr = &(&a + &1.into()) * &(&b + &2.into());
+
+        let bound = expr.partial_evaluate(&HashMap::from([("A".to_string(), 4)]));
+        assert_eq!(bound.weak_eval(), "(5 * (B + 2))");
+        assert_eq!(bound.strong_eval(), "5 * (B + 2)");
+
+        let bound =
+            expr.partial_evaluate(&HashMap::from([("A".to_string(), 4), ("B".to_string(), 1)]));
+        assert_eq!(bound.weak_eval(), "15");
+        assert_eq!(expr.weak_eval(), "((A + 1) * (B + 2))");
+    }
+
+    #[test]
+    fn test_partial_evaluate_without_fold() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let expr = &(&a * &b) - &a;
+
+        let bound = expr.partial_evaluate(&HashMap::from([("B".to_string(), 3)]));
+        assert_eq!(bound.weak_eval(), "((A * 3) - A)");
+
+        let bound = expr.partial_evaluate(&HashMap::from([("C".to_string(), 3)]));
+        assert_eq!(bound.weak_eval(), expr.weak_eval());
+    }
+
+    #[test]
+    fn test_expanded_variable_order() {
+        let x = ExprWrapper::from_symbolic_variable("x");
+        let y = ExprWrapper::from_symbolic_variable("y");
+        let z = ExprWrapper::from_symbolic_variable("z");
+
+        // (x + y) * z + x
+        let expr = &(&(&x + &y) * &z) + &x;
+        assert_eq!(
+            expr.expanded(&VariableOrder::Lexicographic),
+            "x + x * z + y * z"
+        );
+        assert_eq!(
+            expr.expanded(&VariableOrder::Custom(Vec::from([
+                "z".to_string(),
+                "y".to_string(),
+                "x".to_string()
+            ]))),
+            "z * y + z * x + x"
+        );
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let bindings = HashMap::from([("A".to_string(), 3), ("B".to_string(), 5)]);
+
+        // (A + 1) * (B + 2) - A / 2
+        let expr = &(&(&a + &1.into()) * &(&b + &2.into())) - &(&a / &2.into());
+        assert_eq!(expr.evaluate(&bindings), Ok(27));
+        assert_eq!(expr.partial_evaluate(&bindings).as_const(), Some(27));
+        assert_eq!((&a - &b).evaluate(&bindings), Ok(3u32.wrapping_sub(5)));
+        assert_eq!(a.pow(&b).evaluate(&bindings), Ok(243));
+        assert_eq!((-&a).evaluate(&bindings), Ok(3u32.wrapping_neg()));
+
+        let c = ExprWrapper::from_symbolic_variable("C");
+        assert_eq!(
+            (&a + &c).evaluate(&bindings),
+            Err(EvalError::UnboundVariable("C".to_string()))
+        );
+        assert_eq!(
+            (&a / &(&b - &b)).evaluate(&bindings),
+            Err(EvalError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_simplify_eval() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let two = ExprWrapper::from(2);
+        let three = ExprWrapper::from(3);
+
+        assert_eq!((&a + &a).strong_eval(), "A + A");
+        assert_eq!((&a + &a).simplify_eval(), "2 * A");
+        assert_eq!((&(&two * &a) + &(&three * &a)).simplify_eval(), "5 * A");
+        assert_eq!((&b + &a).simplify_eval(), (&a + &b).simplify_eval());
+        assert_eq!((&(&a - &a) + &b).simplify_eval(), "B");
+        assert_eq!((&a - &a).simplify_eval(), "0");
+        assert_eq!((&(&b * &a) + &(&a * &b)).simplify_eval(), "2 * A * B");
+    }
+
+    #[test]
+    fn test_linear_coefficients_affine() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+
+        // (A + 1) * 3 - B + A * B - B * A = 3 * A - B + 3
+        let expr = &(&(&(&(&a + &1.into()) * &3.into()) - &b) + &(&a * &b)) - &(&b * &a);
+        assert_eq!(
+            expr.linear_coefficients(),
+            Some((
+                HashMap::from([("A".to_string(), 3), ("B".to_string(), u32::MAX)]),
+                3
+            ))
+        );
+
+        assert_eq!(
+            ExprWrapper::from(5).linear_coefficients(),
+            Some((HashMap::new(), 5))
+        );
+    }
+
+    #[test]
+    fn test_linear_coefficients_nonlinear() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let expr = &(&a + &1.into()) * &(&b + &2.into());
+        assert_eq!(expr.linear_coefficients(), None);
+    }
+
+    #[test]
+    fn test_univariate_coefficients() {
+        let x = ExprWrapper::from_symbolic_variable("x");
+
+        // (x + 1) * (x + 2) = x^2 + 3x + 2
+        let expr = &(&x + &1.into()) * &(&x + &2.into());
+        assert_eq!(expr.univariate_coefficients("x"), Ok(vec![2, 3, 1]));
+
+        // x * x * x - x = x^3 - x
+        let expr = &(&(&x * &x) * &x) - &x;
+        assert_eq!(
+            expr.univariate_coefficients("x"),
+            Ok(vec![0, u32::MAX, 0, 1])
+        );
+
+        assert_eq!(
+            ExprWrapper::from(7).univariate_coefficients("x"),
+            Ok(vec![7])
+        );
+        assert_eq!((&x - &x).univariate_coefficients("x"), Ok(vec![0]));
+    }
+
+    #[test]
+    fn test_univariate_coefficients_multivariate() {
+        let x = ExprWrapper::from_symbolic_variable("x");
+        let y = ExprWrapper::from_symbolic_variable("y");
+        let expr = &(&x * &x) + &y;
+        assert_eq!(
+            expr.univariate_coefficients("x"),
+            Err(CoeffError::UnexpectedVariable {
+                found: "y".to_string(),
+                expected: "x".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_interval() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let ranges = HashMap::from([("A".to_string(), (2, 3)), ("B".to_string(), (4, 10))]);
+
+        assert_eq!((&a * &b).evaluate_interval(&ranges), (8, 30));
+        assert_eq!((&b - &a).evaluate_interval(&ranges), (1, 8));
+        assert_eq!((&a - &b).evaluate_interval(&ranges), (0, 0));
+        assert_eq!(
+            (&(&a + &1.into()) * &b).evaluate_interval(&ranges),
+            (12, 40)
+        );
+    }
+
+    #[test]
+    fn test_evaluate_interval_saturates() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let ranges = HashMap::from([
+            ("A".to_string(), (1 << 16, 1 << 20)),
+            ("B".to_string(), (1 << 10, 1 << 20)),
+        ]);
+
+        assert_eq!((&a * &b).evaluate_interval(&ranges), (1 << 26, u32::MAX));
+        assert_eq!(
+            (&(&a * &b) + &ExprWrapper::from(u32::MAX)).evaluate_interval(&ranges),
+            (u32::MAX, u32::MAX)
+        );
+        assert_eq!(a.evaluate_interval(&HashMap::new()), (0, u32::MAX));
+    }
+
+    #[test]
+    fn test_structural_equality() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let lhs = &(&a + &b) * &ExprWrapper::from(2);
+        let rhs = &(&ExprWrapper::from_symbolic_variable("A") + &b) * &ExprWrapper::from(2);
+        assert_eq!(lhs, rhs);
+        assert_ne!(&a + &b, &b + &a);
+        assert_ne!(lhs, &(&a + &b) * &ExprWrapper::from(3));
+        assert_ne!(
+            &ExprWrapper::from(1) + &ExprWrapper::from(2),
+            ExprWrapper::from(3)
+        );
+    }
+
+    #[test]
+    fn test_equivalent_to() {
+        let [a, b, c] = ["A", "B", "C"].map(ExprWrapper::from_symbolic_variable);
+        let factored = &(&a + &b) * &c;
+        let distributed = &(&a * &c) + &(&c * &b);
+        assert!(factored.equivalent_to(&distributed));
+        assert!(!factored.equivalent_to(&(&a * &c)));
+        assert!((&a + &b).equivalent_to(&(&b + &a)));
+
+        // Coefficients wrap around
+        let wrapped = &(&a * &ExprWrapper::from(u32::MAX)) + &a;
+        assert!(wrapped.equivalent_to(&ExprWrapper::from(0)));
+        assert!((&a - &a).equivalent_to(&(&b * &ExprWrapper::from(0))));
+    }
+
+    #[test]
+    fn test_as_const() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        assert_eq!(
+            (&ExprWrapper::from(2) * &ExprWrapper::from(3)).as_const(),
+            Some(6)
+        );
+        assert_eq!(
+            (&ExprWrapper::from(0) - &ExprWrapper::from(1)).as_const(),
+            Some(u32::MAX)
+        );
+        assert_eq!(a.as_const(), None);
+        assert_eq!((&a * &ExprWrapper::from(0)).as_const(), None);
+    }
+
+    #[test]
+    fn test_folded() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let sum = &ExprWrapper::from(u32::MAX) + &ExprWrapper::from(2);
+        assert_eq!(sum.folded(), ExprWrapper::from(1));
+        assert_eq!((&a + &1.into()).folded().weak_eval(), "(A + 1)");
+        assert_eq!(a.folded(), a);
+    }
+
+    #[test]
+    fn test_variable_occurrences() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let expr = &(&a + &1.into()) * &(&b + &a);
+        assert_eq!(
+            expr.variable_occurrences(),
+            HashMap::from([("A".to_string(), 2), ("B".to_string(), 1)])
+        );
+
+        assert!(ExprWrapper::from(5).variable_occurrences().is_empty());
+    }
+
+    #[test]
+    fn test_from_weak_eval_round_trip() {
+        for source in [
+            "(((1 + A) * (2 + B)) - 0)",
+            "((((A + 1) * (B + 2)) * ((C - D) + 3)) + ((4 * C) * D))",
+            "((1 + 2) + A)",
+            "((A + B) / (C * 2))",
+            "93",
+            "A",
+        ] {
+            let expr = ExprWrapper::from_weak_eval(source).unwrap();
+            assert_eq!(expr.weak_eval(), source);
+        }
+
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let expr = &(&a + &1.into()) * &(&a - &ExprWrapper::from(2));
+        assert_eq!(ExprWrapper::from_weak_eval(&expr.weak_eval()), Ok(expr));
+    }
+
+    #[test]
+    fn test_from_weak_eval_invalid() {
+        assert_eq!(
+            ExprWrapper::from_weak_eval("A + 1"),
+            Err(WeakEvalError::UnexpectedCharacter {
+                found: ' ',
+                position: 1,
+                expected: "end of input"
+            })
+        );
+        assert_eq!(
+            ExprWrapper::from_weak_eval("(A +  1)"),
+            Err(WeakEvalError::UnexpectedCharacter {
+                found: ' ',
+                position: 5,
+                expected: "constant or variable"
+            })
+        );
+        assert_eq!(
+            ExprWrapper::from_weak_eval("(A % 1)"),
+            Err(WeakEvalError::UnexpectedCharacter {
+                found: '%',
+                position: 3,
+                expected: "operator"
+            })
+        );
+        assert_eq!(
+            ExprWrapper::from_weak_eval("(A + 1"),
+            Err(WeakEvalError::UnexpectedEnd { expected: "')'" })
+        );
+        assert_eq!(
+            ExprWrapper::from_weak_eval("4294967296"),
+            Err(WeakEvalError::ConstantOutOfRange {
+                token: "4294967296".to_string(),
+                position: 0
+            })
+        );
+        assert!(ExprWrapper::from_weak_eval("").is_err());
+    }
+
+    #[test]
+    fn test_quoted_variables() {
+        let name = ExprWrapper::from_symbolic_variable("complex name");
+        let expr = &(&name + &ExprWrapper::from_symbolic_variable("A")) * &2.into();
+        assert_eq!(expr.weak_eval(), "((\"complex name\" + A) * 2)");
+        assert_eq!(expr.to_sexpr(), "(* (+ \"complex name\" A) 2)");
+        assert_eq!(
+            ExprWrapper::from_weak_eval(&expr.weak_eval()),
+            Ok(expr.clone())
+        );
+        assert_eq!(ExprWrapper::from_sexpr(&expr.to_sexpr()), Ok(expr.clone()));
+        assert_eq!(
+            expr.variables(),
+            BTreeSet::from(["A".to_string(), "complex name".to_string()])
+        );
+
+        assert_eq!(
+            ExprWrapper::from_weak_eval("(\"A + 1)"),
+            Err(WeakEvalError::UnexpectedEnd { expected: "'\"'" })
+        );
+        assert_eq!(
+            ExprWrapper::from_weak_eval("(\"\" + 1)"),
+            Err(WeakEvalError::UnexpectedCharacter {
+                found: '"',
+                position: 2,
+                expected: "variable name"
+            })
+        );
+        assert!(ExprWrapper::from_sexpr("(+ \"A 1)").is_err());
+        assert!(ExprWrapper::from_sexpr("\"\"").is_err());
+    }
+
+    #[test]
+    fn test_degree_and_variables() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let expr = &(&a + &1.into()) * &(&a + &2.into());
+        assert_eq!(expr.degree(), 2);
+        assert_eq!(expr.variables(), BTreeSet::from(["A".to_string()]));
+
+        // Terms cancelling out lower the degree but keep the variable
+        let expr = &(&(&a * &b) - &(&b * &a)) + &a;
+        assert_eq!(expr.degree(), 1);
+        assert_eq!(
+            expr.variables(),
+            BTreeSet::from(["A".to_string(), "B".to_string()])
+        );
+        assert_eq!(ExprWrapper::from(3).degree(), 0);
+        assert!(ExprWrapper::from(3).variables().is_empty());
+        assert_eq!(a.pow(&3.into()).degree(), 3);
+    }
+
+    #[test]
+    fn test_to_sexpr() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let expr = &(&a + &1.into()) * &(&b + &2.into());
+        assert_eq!(expr.to_sexpr(), "(* (+ A 1) (+ B 2))");
+        assert_eq!((-&(&a - &b)).to_sexpr(), "(- (- A B))");
+        assert_eq!(a.pow(&(&b / &3.into())).to_sexpr(), "(^ A (/ B 3))");
+        assert_eq!((&(&a ^ &b) << &1.into()).to_sexpr(), "(<< (xor A B) 1)");
+        assert_eq!(ExprWrapper::from(7).to_sexpr(), "7");
+        assert_eq!(a.to_sexpr(), "A");
+    }
+
+    #[test]
+    fn test_from_sexpr() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        assert_eq!(
+            ExprWrapper::from_sexpr("(* (+ A 1) 2)"),
+            Ok(&(&a + &1.into()) * &2.into())
+        );
+        assert_eq!(ExprWrapper::from_sexpr("(- A)"), Ok(-&a));
+        assert_eq!(ExprWrapper::from_sexpr(" 42 "), Ok(ExprWrapper::from(42)));
+
+        for expr in [
+            &(&a + &1.into()) * &(&b + &2.into()),
+            -&(&a - &b),
+            a.pow(&(&b / &3.into())),
+            &(&(&a ^ &b) << &1.into()) | &(&(&a & &b) >> &2.into()),
+        ] {
+            assert_eq!(ExprWrapper::from_sexpr(&expr.to_sexpr()), Ok(expr));
+        }
+    }
+
+    #[test]
+    fn test_from_sexpr_invalid() {
+        assert_eq!(
+            ExprWrapper::from_sexpr("(+ A)"),
+            Err(SexprError::UnexpectedToken {
+                token: ")".to_string(),
+                position: 4,
+                expected: "expression"
+            })
+        );
+        assert_eq!(
+            ExprWrapper::from_sexpr("(* A B C)"),
+            Err(SexprError::UnexpectedToken {
+                token: "C".to_string(),
+                position: 7,
+                expected: "')'"
+            })
+        );
+        assert_eq!(
+            ExprWrapper::from_sexpr("(% A B)"),
+            Err(SexprError::UnexpectedToken {
+                token: "%".to_string(),
+                position: 1,
+                expected: "operator"
+            })
+        );
+        assert_eq!(
+            ExprWrapper::from_sexpr("(+ A 1"),
+            Err(SexprError::UnexpectedEnd { expected: "')'" })
+        );
+        assert_eq!(
+            ExprWrapper::from_sexpr("A B"),
+            Err(SexprError::UnexpectedToken {
+                token: "B".to_string(),
+                position: 2,
+                expected: "end of input"
+            })
+        );
+        assert_eq!(
+            ExprWrapper::from_sexpr("4294967296"),
+            Err(SexprError::ConstantOutOfRange {
+                token: "4294967296".to_string(),
+                position: 0
+            })
+        );
+        assert!(ExprWrapper::from_sexpr("").is_err());
+    }
+
+    #[test]
+    fn test_render_compact() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let expr = &(&a + &1.into()) * &(&b + &2.into());
+        assert_eq!(expr.render(RenderStyle::Compact), "(A+1)*(B+2)");
+        assert_eq!(expr.render(RenderStyle::default()), "(A + 1) * (B + 2)");
+        assert_eq!(expr.render(RenderStyle::Spaced), expr.strong_eval());
+
+        let expr = &(&a - &(&b + &a)) - &(&ExprWrapper::from(1) + &ExprWrapper::from(2));
+        assert_eq!(expr.render(RenderStyle::Compact), "A-(B+A)-3");
+    }
+
+    #[test]
+    fn test_to_latex() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let b = ExprWrapper::from_symbolic_variable("B");
+        let c = ExprWrapper::from_symbolic_variable("C");
+
+        let expr = &(&a + &1.into()) * &(&b + &2.into());
+        assert_eq!(
+            expr.to_latex(),
+            r"\left(A + 1\right) \cdot \left(B + 2\right)"
+        );
+        assert_eq!(
+            (&(&(&ExprWrapper::from(2) * &a) * &b) + &c.pow(&3.into())).to_latex(),
+            r"2 \cdot A B + C^{3}"
+        );
+        assert_eq!(
+            (&a + &b).pow(&2.into()).to_latex(),
+            r"\left(A + B\right)^{2}"
+        );
+        assert_eq!(a.pow(&(&b + &1.into())).to_latex(), "A^{B + 1}");
+        assert_eq!((&a - &(&b - &c)).to_latex(), r"A - \left(B - C\right)");
+        assert_eq!((-&(&a + &b)).to_latex(), r"-\left(A + B\right)");
+        assert_eq!((&a / &(&b * &c)).to_latex(), r"A / \left(B C\right)");
+        assert_eq!(ExprWrapper::from(6).to_latex(), "6");
+    }
 }
@@ -3,8 +3,9 @@ use std::cmp::Ordering;
 use log::trace;
 
 use super::{
+    opcode::LatencyField,
     types::{Addr, Const, Reg},
-    ExprWrapper,
+    ExprWrapper, OpKind,
 };
 
 struct OperationLatency;
@@ -15,6 +16,107 @@ impl OperationLatency {
     const ADD: usize = 2;
     const SUB: usize = 2;
     const MUL: usize = 10;
+    const DIV: usize = 20;
+    const POW: usize = 15;
+    const MIN: usize = 2;
+    const MAX: usize = 2;
+    const LOGIC: usize = 1;
+    const SHIFT: usize = 1;
+    const NEG: usize = 2;
+    const CMOVZ: usize = 1;
+    const CLR: usize = 1;
+    const MOV: usize = 1;
+}
+
+/// Number of cycles each operation takes to complete, defaulting to the PEM
+/// operation latencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Latencies {
+    pub ldi: usize,
+    pub ldr: usize,
+    pub str: usize,
+    pub add: usize,
+    pub sub: usize,
+    pub mul: usize,
+    pub div: usize,
+    pub pow: usize,
+    pub min: usize,
+    pub max: usize,
+    pub and: usize,
+    pub or: usize,
+    pub xor: usize,
+    pub shl: usize,
+    pub shr: usize,
+    pub neg: usize,
+    pub cmovz: usize,
+    pub clr: usize,
+    pub mov: usize,
+}
+
+impl Default for Latencies {
+    fn default() -> Self {
+        Self {
+            ldi: OperationLatency::LDI,
+            ldr: OperationLatency::LDR,
+            str: OperationLatency::STR,
+            add: OperationLatency::ADD,
+            sub: OperationLatency::SUB,
+            mul: OperationLatency::MUL,
+            div: OperationLatency::DIV,
+            pow: OperationLatency::POW,
+            min: OperationLatency::MIN,
+            max: OperationLatency::MAX,
+            and: OperationLatency::LOGIC,
+            or: OperationLatency::LOGIC,
+            xor: OperationLatency::LOGIC,
+            shl: OperationLatency::SHIFT,
+            shr: OperationLatency::SHIFT,
+            neg: OperationLatency::NEG,
+            cmovz: OperationLatency::CMOVZ,
+            clr: OperationLatency::CLR,
+            mov: OperationLatency::MOV,
+        }
+    }
+}
+
+impl Latencies {
+    /// Get the latency of an operation kind
+    ///
+    /// # Note
+    /// Jumps take effect at the next cycle, so `jmp`, `jz` and `halt` always
+    /// have latency `1`.
+    ///
+    /// # Arguments
+    /// * `op` - operation kind
+    pub fn of(&self, op: OpKind) -> usize {
+        match op.spec().latency {
+            LatencyField::Own(field) => {
+                let mut latencies = *self;
+                *field(&mut latencies)
+            }
+            LatencyField::Shared(op) => self.of(op),
+            LatencyField::Fixed => 1,
+        }
+    }
+
+    /// Get the latencies with the latency of an operation kind replaced
+    ///
+    /// # Arguments
+    /// * `op` - operation kind, where `sti` shares the latency of `str` and
+    ///   `sqr` shares the latency of `mul`
+    /// * `latency` - number of cycles the operation takes to complete
+    ///
+    /// # Panics
+    /// * If `op` has no configurable latency, i.e. `jmp`, `jz` or `halt`
+    pub fn with(mut self, op: OpKind, latency: usize) -> Self {
+        match op.spec().latency {
+            LatencyField::Own(field) => *field(&mut self) = latency,
+            LatencyField::Shared(op) => return self.with(op, latency),
+            LatencyField::Fixed => panic!("{op} has no configurable latency"),
+        }
+        self
+    }
 }
 
 /// Output of an operation
@@ -22,11 +124,13 @@ impl OperationLatency {
 /// We consider two operations to be equal if they write to the same register or
 /// memory address and the ordering of operations is determined by the tuple
 /// `(output_type, register/memory_address)`. Two outputs writing to the same
-/// register/memory address are considered equal regardless of the values.
-#[derive(Debug)]
+/// register/memory address are considered equal regardless of the values, and
+/// clearing a register counts as writing to it.
+#[derive(Debug, Clone)]
 pub(super) enum OperationOutput {
     WriteToRegister(Reg, ExprWrapper),
     WriteToMemory(Addr, ExprWrapper),
+    ClearRegister(Reg),
 }
 
 impl std::fmt::Display for OperationOutput {
@@ -34,18 +138,25 @@ impl std::fmt::Display for OperationOutput {
         match self {
             Self::WriteToRegister(dst, value) => write!(f, "WriteToRegister({}, `{}`)", dst, value),
             Self::WriteToMemory(addr, value) => write!(f, "WriteToMemory({}, `{}`)", addr, value),
+            Self::ClearRegister(dst) => write!(f, "ClearRegister({})", dst),
+        }
+    }
+}
+
+impl OperationOutput {
+    /// Get the `(output_type, register/memory_address)` tuple identifying what
+    /// the output writes to, where registers come before memory
+    fn target(&self) -> (bool, u32) {
+        match self {
+            Self::WriteToRegister(dst, _) | Self::ClearRegister(dst) => (false, dst.0),
+            Self::WriteToMemory(addr, _) => (true, addr.0),
         }
     }
 }
 
 impl Ord for OperationOutput {
     fn cmp(&self, other: &Self) -> Ordering {
-        match (self, other) {
-            (Self::WriteToRegister(dst1, _), Self::WriteToRegister(dst2, _)) => dst1.0.cmp(&dst2.0),
-            (Self::WriteToMemory(addr1, _), Self::WriteToMemory(addr2, _)) => addr1.0.cmp(&addr2.0),
-            (Self::WriteToRegister(_, _), Self::WriteToMemory(_, _)) => Ordering::Less,
-            (Self::WriteToMemory(_, _), Self::WriteToRegister(_, _)) => Ordering::Greater,
-        }
+        self.target().cmp(&other.target())
     }
 }
 
@@ -59,11 +170,7 @@ impl Eq for OperationOutput {}
 
 impl PartialEq for OperationOutput {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::WriteToRegister(dst1, _), Self::WriteToRegister(dst2, _)) => dst1.0 == dst2.0,
-            (Self::WriteToMemory(addr1, _), Self::WriteToMemory(addr2, _)) => addr1.0 == addr2.0,
-            _ => false,
-        }
+        self.target() == other.target()
     }
 }
 
@@ -72,7 +179,7 @@ impl PartialEq for OperationOutput {
 ///
 /// We consider two in-flight operations to be equal if they complete at the
 /// same cycle and have the same output.
-#[derive(Debug, Eq)]
+#[derive(Debug, Clone, Eq)]
 pub(super) struct InflightOperation {
     /// Operation output when it completes
     output: OperationOutput,
@@ -80,6 +187,9 @@ pub(super) struct InflightOperation {
     complete_by: usize,
     /// The cycle when the operation started
     started_at: usize,
+    /// Index of the instruction that issued the operation, which differs from
+    /// `started_at` once the machine stalls or jumps
+    instruction: usize,
 }
 
 impl Ord for InflightOperation {
@@ -108,13 +218,15 @@ impl InflightOperation {
     ///
     /// # Arguments
     /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
     /// * `dst` - destination register
     /// * `constant` - constant to load
-    pub fn from_ldi(cycle: usize, dst: Reg, Const(constant): Const) -> Self {
+    pub fn from_ldi(cycle: usize, latency: usize, dst: Reg, Const(constant): Const) -> Self {
         let myself = Self {
             output: OperationOutput::WriteToRegister(dst, constant.into()),
-            complete_by: cycle + OperationLatency::LDI,
+            complete_by: cycle + latency,
             started_at: cycle,
+            instruction: 0,
         };
         trace!(
             "LDI operation started at cycle #{} and expect to complete by cycle #{}",
@@ -128,13 +240,15 @@ impl InflightOperation {
     ///
     /// # Arguments
     /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
     /// * `dst` - destination register
     /// * `addr_value` - value of the memory address to load from
-    pub fn from_ldr(cycle: usize, dst: Reg, addr_value: &ExprWrapper) -> Self {
+    pub fn from_ldr(cycle: usize, latency: usize, dst: Reg, addr_value: &ExprWrapper) -> Self {
         let myself = Self {
             output: OperationOutput::WriteToRegister(dst, addr_value.clone()),
-            complete_by: cycle + OperationLatency::LDR,
+            complete_by: cycle + latency,
             started_at: cycle,
+            instruction: 0,
         };
         trace!(
             "LDR operation started at cycle #{} and expect to complete by cycle #{}",
@@ -148,13 +262,15 @@ impl InflightOperation {
     ///
     /// # Arguments
     /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
     /// * `src_value` - value of the source register
     /// * `addr` - memory address to store into
-    pub fn from_str(cycle: usize, src_value: &ExprWrapper, addr: Addr) -> Self {
+    pub fn from_str(cycle: usize, latency: usize, src_value: &ExprWrapper, addr: Addr) -> Self {
         let myself = Self {
             output: OperationOutput::WriteToMemory(addr, src_value.clone()),
-            complete_by: cycle + OperationLatency::STR,
+            complete_by: cycle + latency,
             started_at: cycle,
+            instruction: 0,
         };
         trace!(
             "STR operation started at cycle #{} and expect to complete by cycle #{}",
@@ -164,6 +280,28 @@ impl InflightOperation {
         myself
     }
 
+    /// Store a 32-bit numeric constant into memory
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `addr` - memory address to store into
+    /// * `constant` - constant to store
+    pub fn from_sti(cycle: usize, latency: usize, addr: Addr, Const(constant): Const) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToMemory(addr, constant.into()),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "STI operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
     /// Add the values in the source registers and put the sum in the
     /// destination register
     ///
@@ -172,11 +310,13 @@ impl InflightOperation {
     ///
     /// # Arguments
     /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
     /// * `dst` - destination register
     /// * `src1_value` - value of the first source register
     /// * `src2_value` - value of the second source register
     pub fn from_add(
         cycle: usize,
+        latency: usize,
         dst: Reg,
         src1_value: &ExprWrapper,
         src2_value: &ExprWrapper,
@@ -184,8 +324,9 @@ impl InflightOperation {
         let myself = Self {
             // NB src2 is lhs and src1 is rhs
             output: OperationOutput::WriteToRegister(dst, src2_value + src1_value),
-            complete_by: cycle + OperationLatency::ADD,
+            complete_by: cycle + latency,
             started_at: cycle,
+            instruction: 0,
         };
         trace!(
             "ADD operation started at cycle #{} and expect to complete by cycle #{}",
@@ -200,19 +341,22 @@ impl InflightOperation {
     ///
     /// # Arguments
     /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
     /// * `dst` - destination register
     /// * `src1_value` - value of the first source register
     /// * `src2_value` - value of the second source register
     pub fn from_sub(
         cycle: usize,
+        latency: usize,
         dst: Reg,
         src1_value: &ExprWrapper,
         src2_value: &ExprWrapper,
     ) -> Self {
         let myself = Self {
             output: OperationOutput::WriteToRegister(dst, src1_value - src2_value),
-            complete_by: cycle + OperationLatency::SUB,
+            complete_by: cycle + latency,
             started_at: cycle,
+            instruction: 0,
         };
         trace!(
             "SUB operation started at cycle #{} and expect to complete by cycle #{}",
@@ -227,19 +371,22 @@ impl InflightOperation {
     ///
     /// # Arguments
     /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
     /// * `dst` - destination register
     /// * `src1_value` - value of the first source register
     /// * `src2_value` - value of the second source register
     pub fn from_mul(
         cycle: usize,
+        latency: usize,
         dst: Reg,
         src1_value: &ExprWrapper,
         src2_value: &ExprWrapper,
     ) -> Self {
         let myself = Self {
             output: OperationOutput::WriteToRegister(dst, src1_value * src2_value),
-            complete_by: cycle + OperationLatency::MUL,
+            complete_by: cycle + latency,
             started_at: cycle,
+            instruction: 0,
         };
         trace!(
             "MUL operation started at cycle #{} and expect to complete by cycle #{}",
@@ -249,6 +396,441 @@ impl InflightOperation {
         myself
     }
 
+    /// Divide the value of source register 1 by source register 2 and put the
+    /// quotient in the destination register
+    ///
+    /// # Note
+    /// The caller is responsible for rejecting a numeric zero divisor.
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src1_value` - value of the first source register
+    /// * `src2_value` - value of the second source register
+    pub fn from_div(
+        cycle: usize,
+        latency: usize,
+        dst: Reg,
+        src1_value: &ExprWrapper,
+        src2_value: &ExprWrapper,
+    ) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(dst, src1_value / src2_value),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "DIV operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Raise the value of source register 1 to the power of source register 2
+    /// and put the result in the destination register
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src1_value` - value of the base register
+    /// * `src2_value` - value of the exponent register
+    pub fn from_pow(
+        cycle: usize,
+        latency: usize,
+        dst: Reg,
+        src1_value: &ExprWrapper,
+        src2_value: &ExprWrapper,
+    ) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(dst, src1_value.pow(src2_value)),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "POW operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Put the smaller of the numeric values of the source registers in the
+    /// destination register
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src1_value` - numeric value of source register 1
+    /// * `src2_value` - numeric value of source register 2
+    pub fn from_min(
+        cycle: usize,
+        latency: usize,
+        dst: Reg,
+        src1_value: u32,
+        src2_value: u32,
+    ) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(
+                dst,
+                ExprWrapper::from(src1_value.min(src2_value)),
+            ),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "MIN operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Put the larger of the numeric values of the source registers in the
+    /// destination register
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src1_value` - numeric value of source register 1
+    /// * `src2_value` - numeric value of source register 2
+    pub fn from_max(
+        cycle: usize,
+        latency: usize,
+        dst: Reg,
+        src1_value: u32,
+        src2_value: u32,
+    ) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(
+                dst,
+                ExprWrapper::from(src1_value.max(src2_value)),
+            ),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "MAX operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Put the bitwise AND of the values in the source registers in the
+    /// destination register
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src1_value` - value of the first source register
+    /// * `src2_value` - value of the second source register
+    pub fn from_and(
+        cycle: usize,
+        latency: usize,
+        dst: Reg,
+        src1_value: &ExprWrapper,
+        src2_value: &ExprWrapper,
+    ) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(dst, src1_value & src2_value),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "AND operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Put the bitwise OR of the values in the source registers in the
+    /// destination register
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src1_value` - value of the first source register
+    /// * `src2_value` - value of the second source register
+    pub fn from_or(
+        cycle: usize,
+        latency: usize,
+        dst: Reg,
+        src1_value: &ExprWrapper,
+        src2_value: &ExprWrapper,
+    ) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(dst, src1_value | src2_value),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "OR operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Put the bitwise XOR of the values in the source registers in the
+    /// destination register
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src1_value` - value of the first source register
+    /// * `src2_value` - value of the second source register
+    pub fn from_xor(
+        cycle: usize,
+        latency: usize,
+        dst: Reg,
+        src1_value: &ExprWrapper,
+        src2_value: &ExprWrapper,
+    ) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(dst, src1_value ^ src2_value),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "XOR operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Shift the value of source register 1 left by the value of source
+    /// register 2 and put the result in the destination register
+    ///
+    /// # Note
+    /// Shift amounts of `32` or more wrap around modulo `32`, as in
+    /// `u32::wrapping_shl`
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src1_value` - value of the first source register
+    /// * `src2_value` - value of the second source register
+    pub fn from_shl(
+        cycle: usize,
+        latency: usize,
+        dst: Reg,
+        src1_value: &ExprWrapper,
+        src2_value: &ExprWrapper,
+    ) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(dst, src1_value << src2_value),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "SHL operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Shift the value of source register 1 right by the value of source
+    /// register 2 and put the result in the destination register
+    ///
+    /// # Note
+    /// Shift amounts of `32` or more wrap around modulo `32`, as in
+    /// `u32::wrapping_shr`
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src1_value` - value of the first source register
+    /// * `src2_value` - value of the second source register
+    pub fn from_shr(
+        cycle: usize,
+        latency: usize,
+        dst: Reg,
+        src1_value: &ExprWrapper,
+        src2_value: &ExprWrapper,
+    ) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(dst, src1_value >> src2_value),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "SHR operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Negate the value of the source register and put it in the destination
+    /// register
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src_value` - value of the source register
+    pub fn from_neg(cycle: usize, latency: usize, dst: Reg, src_value: &ExprWrapper) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(dst, -src_value),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "NEG operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Multiply the value of the source register by itself and put the result
+    /// in the destination register
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src_value` - value of the source register
+    pub fn from_sqr(cycle: usize, latency: usize, dst: Reg, src_value: &ExprWrapper) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(dst, src_value * src_value),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "SQR operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Move the value of the source register into the destination register if
+    /// the condition is zero
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src_value` - value of the source register
+    /// * `cond` - numeric value of the condition register
+    ///
+    /// # Returns
+    /// * `Some(operation)` if the condition is zero
+    /// * `None` if the condition is non-zero and the operation produces no
+    ///   output
+    pub fn from_cmovz(
+        cycle: usize,
+        latency: usize,
+        dst: Reg,
+        src_value: &ExprWrapper,
+        cond: u32,
+    ) -> Option<Self> {
+        if cond != 0 {
+            trace!(
+                "CMOVZ operation at cycle #{} not taken with condition {}",
+                cycle,
+                cond
+            );
+            return None;
+        }
+
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(dst, src_value.clone()),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "CMOVZ operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        Some(myself)
+    }
+
+    /// Clear a register back to uninitialized
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - register to clear
+    pub fn from_clr(cycle: usize, latency: usize, dst: Reg) -> Self {
+        let myself = Self {
+            output: OperationOutput::ClearRegister(dst),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "CLR operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Copy the value of the source register into the destination register
+    ///
+    /// # Arguments
+    /// * `cycle` - cycle when the operation starts
+    /// * `latency` - number of cycles the operation takes to complete
+    /// * `dst` - destination register
+    /// * `src_value` - value of the source register
+    pub fn from_mov(cycle: usize, latency: usize, dst: Reg, src_value: &ExprWrapper) -> Self {
+        let myself = Self {
+            output: OperationOutput::WriteToRegister(dst, src_value.clone()),
+            complete_by: cycle + latency,
+            started_at: cycle,
+            instruction: 0,
+        };
+        trace!(
+            "MOV operation started at cycle #{} and expect to complete by cycle #{}",
+            cycle,
+            myself.complete_by
+        );
+        myself
+    }
+
+    /// Fold the output value into a numeric constant if its root operation
+    /// only has numeric constant operands
+    pub fn folded(mut self) -> Self {
+        self.output = match self.output {
+            OperationOutput::WriteToRegister(dst, value) => {
+                OperationOutput::WriteToRegister(dst, value.folded())
+            }
+            OperationOutput::WriteToMemory(addr, value) => {
+                OperationOutput::WriteToMemory(addr, value.folded())
+            }
+            output @ OperationOutput::ClearRegister(_) => output,
+        };
+        self
+    }
+
     pub fn get_output(&self) -> &OperationOutput {
         &self.output
     }
@@ -257,7 +839,20 @@ impl InflightOperation {
         self.complete_by
     }
 
+    /// Set the index of the instruction that issued the operation
+    ///
+    /// # Arguments
+    /// * `instruction` - index of the instruction in the program
+    pub fn with_instruction(mut self, instruction: usize) -> Self {
+        self.instruction = instruction;
+        self
+    }
+
     pub fn get_instruction(&self) -> usize {
+        self.instruction
+    }
+
+    pub fn get_issued_at(&self) -> usize {
         self.started_at
     }
 }
@@ -286,16 +881,29 @@ mod tests {
         assert_eq!(op3, op4);
 
         assert_eq!(op4, op4);
+
+        let op5 = OperationOutput::ClearRegister(Reg(0));
+        assert_eq!(op5, op1);
+        assert_ne!(op5, op3);
+    }
+
+    #[test]
+    #[should_panic(expected = "jmp has no configurable latency")]
+    fn test_latencies_with_jmp() {
+        let _ = Latencies::default().with(OpKind::Jmp, 2);
     }
 
     #[test]
     fn test_inflight_operation_ordering() {
-        let ldi = InflightOperation::from_ldi(0, Reg(0), Const(1));
-        let ldr = InflightOperation::from_ldr(0, Reg(0), &1.into());
-        let str = InflightOperation::from_str(0, &1.into(), Addr(0));
-        let add = InflightOperation::from_add(0, Reg(0), &1.into(), &2.into());
-        let sub = InflightOperation::from_sub(0, Reg(0), &1.into(), &2.into());
-        let mul = InflightOperation::from_mul(0, Reg(0), &1.into(), &2.into());
+        let ldi = InflightOperation::from_ldi(0, OperationLatency::LDI, Reg(0), Const(1));
+        let ldr = InflightOperation::from_ldr(0, OperationLatency::LDR, Reg(0), &1.into());
+        let str = InflightOperation::from_str(0, OperationLatency::STR, &1.into(), Addr(0));
+        let add =
+            InflightOperation::from_add(0, OperationLatency::ADD, Reg(0), &1.into(), &2.into());
+        let sub =
+            InflightOperation::from_sub(0, OperationLatency::SUB, Reg(0), &1.into(), &2.into());
+        let mul =
+            InflightOperation::from_mul(0, OperationLatency::MUL, Reg(0), &1.into(), &2.into());
 
         assert_eq!(ldi, ldi);
         assert_eq!(ldr, ldr);
@@ -327,43 +935,56 @@ mod tests {
 
     #[test]
     fn test_inflight_operation_ldi() {
-        let ldi = InflightOperation::from_ldi(0, Reg(0), Const(1));
+        let ldi = InflightOperation::from_ldi(0, OperationLatency::LDI, Reg(0), Const(1));
         let OperationOutput::WriteToRegister(reg, value) = ldi.get_output() else {
             panic!("Expected WriteToRegister, got {:?}", ldi.get_output());
         };
         assert_eq!(*reg, Reg(0));
         assert_eq!(value.weak_eval(), String::from("1"));
         assert_eq!(ldi.get_complete_by(), OperationLatency::LDI);
-        assert_eq!(ldi.get_instruction(), 0);
+        assert_eq!(ldi.get_issued_at(), 0);
     }
 
     #[test]
     fn test_inflight_operation_ldr() {
-        let ldr = InflightOperation::from_ldr(0, Reg(0), &1.into());
+        let ldr = InflightOperation::from_ldr(0, OperationLatency::LDR, Reg(0), &1.into());
         let OperationOutput::WriteToRegister(reg, value) = ldr.get_output() else {
             panic!("Expected WriteToRegister, got {:?}", ldr.get_output());
         };
         assert_eq!(*reg, Reg(0));
         assert_eq!(value.weak_eval(), String::from("1"));
         assert_eq!(ldr.get_complete_by(), OperationLatency::LDR);
-        assert_eq!(ldr.get_instruction(), 0);
+        assert_eq!(ldr.get_issued_at(), 0);
     }
 
     #[test]
     fn test_inflight_operation_str() {
-        let str_ = InflightOperation::from_str(0, &1.into(), Addr(0));
+        let str_ = InflightOperation::from_str(0, OperationLatency::STR, &1.into(), Addr(0));
         let OperationOutput::WriteToMemory(addr, value) = str_.get_output() else {
             panic!("Expected WriteToMemory, got {:?}", str_.get_output());
         };
         assert_eq!(*addr, Addr(0));
         assert_eq!(value.weak_eval(), String::from("1"));
         assert_eq!(str_.get_complete_by(), OperationLatency::STR);
-        assert_eq!(str_.get_instruction(), 0);
+        assert_eq!(str_.get_issued_at(), 0);
+    }
+
+    #[test]
+    fn test_inflight_operation_sti() {
+        let sti = InflightOperation::from_sti(0, OperationLatency::STR, Addr(0), Const(1));
+        let OperationOutput::WriteToMemory(addr, value) = sti.get_output() else {
+            panic!("Expected WriteToMemory, got {:?}", sti.get_output());
+        };
+        assert_eq!(*addr, Addr(0));
+        assert_eq!(value.weak_eval(), String::from("1"));
+        assert_eq!(sti.get_complete_by(), OperationLatency::STR);
+        assert_eq!(sti.get_issued_at(), 0);
     }
 
     #[test]
     fn test_inflight_operation_add() {
-        let add = InflightOperation::from_add(0, Reg(0), &1.into(), &2.into());
+        let add =
+            InflightOperation::from_add(0, OperationLatency::ADD, Reg(0), &1.into(), &2.into());
         let OperationOutput::WriteToRegister(reg, value) = add.get_output() else {
             panic!("Expected WriteToRegister, got {:?}", add.get_output());
         };
@@ -371,12 +992,13 @@ mod tests {
         assert_eq!(value.weak_eval(), String::from("(2 + 1)"));
         assert_eq!(value.strong_eval(), String::from("3"));
         assert_eq!(add.get_complete_by(), OperationLatency::ADD);
-        assert_eq!(add.get_instruction(), 0);
+        assert_eq!(add.get_issued_at(), 0);
     }
 
     #[test]
     fn test_inflight_operation_sub() {
-        let sub = InflightOperation::from_sub(0, Reg(0), &1.into(), &2.into());
+        let sub =
+            InflightOperation::from_sub(0, OperationLatency::SUB, Reg(0), &1.into(), &2.into());
         let OperationOutput::WriteToRegister(reg, value) = sub.get_output() else {
             panic!("Expected WriteToRegister, got {:?}", sub.get_output());
         };
@@ -384,12 +1006,13 @@ mod tests {
         assert_eq!(value.weak_eval(), String::from("(1 - 2)"));
         assert_eq!(value.strong_eval(), String::from("4294967295"));
         assert_eq!(sub.get_complete_by(), OperationLatency::SUB);
-        assert_eq!(sub.get_instruction(), 0);
+        assert_eq!(sub.get_issued_at(), 0);
     }
 
     #[test]
     fn test_inflight_operation_mul() {
-        let mul = InflightOperation::from_mul(0, Reg(0), &1.into(), &2.into());
+        let mul =
+            InflightOperation::from_mul(0, OperationLatency::MUL, Reg(0), &1.into(), &2.into());
         let OperationOutput::WriteToRegister(reg, value) = mul.get_output() else {
             panic!("Expected WriteToRegister, got {:?}", mul.get_output());
         };
@@ -397,6 +1020,168 @@ mod tests {
         assert_eq!(value.weak_eval(), String::from("(1 * 2)"));
         assert_eq!(value.strong_eval(), String::from("2"));
         assert_eq!(mul.get_complete_by(), OperationLatency::MUL);
-        assert_eq!(mul.get_instruction(), 0);
+        assert_eq!(mul.get_issued_at(), 0);
+    }
+
+    #[test]
+    fn test_inflight_operation_div() {
+        let div =
+            InflightOperation::from_div(0, OperationLatency::DIV, Reg(0), &7.into(), &2.into());
+        let OperationOutput::WriteToRegister(reg, value) = div.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", div.get_output());
+        };
+        assert_eq!(*reg, Reg(0));
+        assert_eq!(value.weak_eval(), String::from("(7 / 2)"));
+        assert_eq!(value.strong_eval(), String::from("3"));
+        assert_eq!(div.get_complete_by(), OperationLatency::DIV);
+        assert_eq!(div.get_issued_at(), 0);
+    }
+
+    #[test]
+    fn test_inflight_operation_min_max() {
+        let min = InflightOperation::from_min(0, OperationLatency::MIN, Reg(0), 3, 4);
+        let OperationOutput::WriteToRegister(reg, value) = min.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", min.get_output());
+        };
+        assert_eq!(*reg, Reg(0));
+        assert_eq!(value, &ExprWrapper::from(3));
+        assert_eq!(min.get_complete_by(), OperationLatency::MIN);
+
+        let max = InflightOperation::from_max(1, OperationLatency::MAX, Reg(1), 3, 4);
+        let OperationOutput::WriteToRegister(reg, value) = max.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", max.get_output());
+        };
+        assert_eq!(*reg, Reg(1));
+        assert_eq!(value, &ExprWrapper::from(4));
+        assert_eq!(max.get_complete_by(), 1 + OperationLatency::MAX);
+        assert_eq!(max.get_issued_at(), 1);
+    }
+
+    #[test]
+    fn test_inflight_operation_logic() {
+        let and =
+            InflightOperation::from_and(0, OperationLatency::LOGIC, Reg(0), &12.into(), &10.into());
+        let OperationOutput::WriteToRegister(reg, value) = and.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", and.get_output());
+        };
+        assert_eq!(*reg, Reg(0));
+        assert_eq!(value.weak_eval(), String::from("(12 & 10)"));
+        assert_eq!(value.strong_eval(), String::from("8"));
+        assert_eq!(and.get_complete_by(), OperationLatency::LOGIC);
+
+        let or =
+            InflightOperation::from_or(0, OperationLatency::LOGIC, Reg(0), &12.into(), &10.into());
+        let OperationOutput::WriteToRegister(_, value) = or.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", or.get_output());
+        };
+        assert_eq!(value.strong_eval(), String::from("14"));
+
+        let xor =
+            InflightOperation::from_xor(0, OperationLatency::LOGIC, Reg(0), &12.into(), &10.into());
+        let OperationOutput::WriteToRegister(_, value) = xor.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", xor.get_output());
+        };
+        assert_eq!(value.strong_eval(), String::from("6"));
+    }
+
+    #[test]
+    fn test_inflight_operation_shift() {
+        let shl =
+            InflightOperation::from_shl(0, OperationLatency::SHIFT, Reg(0), &3.into(), &4.into());
+        let OperationOutput::WriteToRegister(reg, value) = shl.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", shl.get_output());
+        };
+        assert_eq!(*reg, Reg(0));
+        assert_eq!(value.weak_eval(), String::from("(3 << 4)"));
+        assert_eq!(value.strong_eval(), String::from("48"));
+        assert_eq!(shl.get_complete_by(), OperationLatency::SHIFT);
+
+        let shr =
+            InflightOperation::from_shr(0, OperationLatency::SHIFT, Reg(0), &48.into(), &36.into());
+        let OperationOutput::WriteToRegister(_, value) = shr.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", shr.get_output());
+        };
+        assert_eq!(value.strong_eval(), String::from("3"));
+    }
+
+    #[test]
+    fn test_inflight_operation_pow() {
+        let pow =
+            InflightOperation::from_pow(0, OperationLatency::POW, Reg(0), &3.into(), &4.into());
+        let OperationOutput::WriteToRegister(reg, value) = pow.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", pow.get_output());
+        };
+        assert_eq!(*reg, Reg(0));
+        assert_eq!(value.weak_eval(), String::from("(3 ^ 4)"));
+        assert_eq!(value.strong_eval(), String::from("81"));
+        assert_eq!(pow.get_complete_by(), OperationLatency::POW);
+        assert_eq!(pow.get_issued_at(), 0);
+    }
+
+    #[test]
+    fn test_inflight_operation_sqr() {
+        let a = ExprWrapper::from_symbolic_variable("A");
+        let sqr = InflightOperation::from_sqr(0, OperationLatency::MUL, Reg(0), &a);
+        let OperationOutput::WriteToRegister(reg, value) = sqr.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", sqr.get_output());
+        };
+        assert_eq!(*reg, Reg(0));
+        assert_eq!(*value, &a * &a);
+        assert_eq!(sqr.get_complete_by(), OperationLatency::MUL);
+        assert_eq!(Latencies::default().of(OpKind::Sqr), OperationLatency::MUL);
+    }
+
+    #[test]
+    fn test_inflight_operation_neg() {
+        let neg = InflightOperation::from_neg(0, OperationLatency::NEG, Reg(0), &1.into());
+        let OperationOutput::WriteToRegister(reg, value) = neg.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", neg.get_output());
+        };
+        assert_eq!(*reg, Reg(0));
+        assert_eq!(value.weak_eval(), String::from("(-1)"));
+        assert_eq!(value.strong_eval(), u32::MAX.to_string());
+        assert_eq!(neg.get_complete_by(), OperationLatency::NEG);
+        assert_eq!(neg.get_issued_at(), 0);
+    }
+
+    #[test]
+    fn test_inflight_operation_cmovz() {
+        assert!(
+            InflightOperation::from_cmovz(0, OperationLatency::CMOVZ, Reg(0), &1.into(), 1)
+                .is_none()
+        );
+
+        let cmovz = InflightOperation::from_cmovz(0, OperationLatency::CMOVZ, Reg(0), &1.into(), 0)
+            .expect("CMOVZ should be taken with a zero condition");
+        let OperationOutput::WriteToRegister(reg, value) = cmovz.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", cmovz.get_output());
+        };
+        assert_eq!(*reg, Reg(0));
+        assert_eq!(value.weak_eval(), String::from("1"));
+        assert_eq!(cmovz.get_complete_by(), OperationLatency::CMOVZ);
+        assert_eq!(cmovz.get_issued_at(), 0);
+    }
+
+    #[test]
+    fn test_inflight_operation_clr() {
+        let clr = InflightOperation::from_clr(0, OperationLatency::CLR, Reg(3));
+        let OperationOutput::ClearRegister(reg) = clr.get_output() else {
+            panic!("Expected ClearRegister, got {:?}", clr.get_output());
+        };
+        assert_eq!(*reg, Reg(3));
+        assert_eq!(clr.get_complete_by(), OperationLatency::CLR);
+        assert_eq!(clr.get_issued_at(), 0);
+    }
+
+    #[test]
+    fn test_inflight_operation_mov() {
+        let mov = InflightOperation::from_mov(0, OperationLatency::MOV, Reg(2), &1.into());
+        let OperationOutput::WriteToRegister(reg, value) = mov.get_output() else {
+            panic!("Expected WriteToRegister, got {:?}", mov.get_output());
+        };
+        assert_eq!(*reg, Reg(2));
+        assert_eq!(value.weak_eval(), String::from("1"));
+        assert_eq!(mov.get_complete_by(), OperationLatency::MOV);
+        assert_eq!(mov.get_issued_at(), 0);
     }
 }
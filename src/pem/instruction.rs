@@ -1,13 +1,85 @@
-use super::types::{Addr, Const, Reg};
+use thiserror::Error;
 
-#[derive(Debug)]
-pub(crate) struct Instruction {
+use super::{
+    opcode::{OperandKind, OperationSpec, OPERATIONS},
+    types::{Addr, Const, Reg},
+};
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum InstructionError {
+    #[error("Register #{} written by both {first} and {second} in the same instruction", .reg.0)]
+    DuplicateRegister {
+        reg: Reg,
+        first: OpKind,
+        second: OpKind,
+    },
+    #[error("Memory #{} written by both {first} and {second} in the same instruction", .addr.0)]
+    DuplicateAddress {
+        addr: Addr,
+        first: OpKind,
+        second: OpKind,
+    },
+    #[error("Both jmp and jz in the same instruction")]
+    ConflictingJumps,
+}
+
+/// Kind of an operation within an `Instruction`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OpKind {
+    Ldi,
+    Ldr,
+    Str,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Min,
+    Max,
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+    Neg,
+    Sqr,
+    Cmovz,
+    Sti,
+    Clr,
+    Mov,
+    Jmp,
+    Jz,
+    Halt,
+}
+
+impl std::fmt::Display for OpKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.mnemonic())
+    }
+}
+
+/// Register or memory address read or written by an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Location {
+    Register(Reg),
+    Memory(Addr),
+}
+
+/// A bundle of operations issued in the same cycle
+///
+/// Each operation kind has its own field, so the order operations are set in
+/// a bundle does not matter, see `canonical()`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Instruction {
     /// ldi <reg> <const> - load a 32-bit numeric constant into a register
     pub(super) ldi: Option<(Reg, Const)>,
     /// ldr <reg> <addr> - load value from memory into a register
     pub(super) ldr: Option<(Reg, Addr)>,
-    /// str <Reg> <Addr> - store a value from register into memory
-    pub(super) str: Option<(Reg, Addr)>,
+    /// str <Reg> <Addr> - store a value from register into memory, possibly
+    /// several times per bundle
+    pub(super) str: Vec<(Reg, Addr)>,
     /// add <dst> <src1> <src2> - add the values in the source registers and put
     /// the sum in the destination register
     pub(super) add: Option<(Reg, Reg, Reg)>,
@@ -17,34 +89,77 @@ pub(crate) struct Instruction {
     /// mul <dst> <src1> <src2> - multiply the values in the source registers
     /// and put the product in the destination register
     pub(super) mul: Option<(Reg, Reg, Reg)>,
+    /// div <dst> <src1> <src2> - divide the value of source register 1 by
+    /// source register 2 and put the quotient in the destination register
+    pub(super) div: Option<(Reg, Reg, Reg)>,
+    /// powr <dst> <src1> <src2> - raise the value of source register 1 to the
+    /// power of source register 2 and put the result in the destination
+    /// register
+    pub(super) pow: Option<(Reg, Reg, Reg)>,
+    /// min <dst> <src1> <src2> - put the smaller of the numeric values in the
+    /// source registers in the destination register
+    pub(super) min: Option<(Reg, Reg, Reg)>,
+    /// max <dst> <src1> <src2> - put the larger of the numeric values in the
+    /// source registers in the destination register
+    pub(super) max: Option<(Reg, Reg, Reg)>,
+    /// and <dst> <src1> <src2> - put the bitwise AND of the values in the
+    /// source registers in the destination register
+    pub(super) and: Option<(Reg, Reg, Reg)>,
+    /// or <dst> <src1> <src2> - put the bitwise OR of the values in the
+    /// source registers in the destination register
+    pub(super) or: Option<(Reg, Reg, Reg)>,
+    /// xor <dst> <src1> <src2> - put the bitwise XOR of the values in the
+    /// source registers in the destination register
+    pub(super) xor: Option<(Reg, Reg, Reg)>,
+    /// shl <dst> <src1> <src2> - shift the value of source register 1 left by
+    /// source register 2 and put the result in the destination register
+    pub(super) shl: Option<(Reg, Reg, Reg)>,
+    /// shr <dst> <src1> <src2> - shift the value of source register 1 right by
+    /// source register 2 and put the result in the destination register
+    pub(super) shr: Option<(Reg, Reg, Reg)>,
+    /// neg <dst> <src> - negate the value in the source register and put it in
+    /// the destination register
+    pub(super) neg: Option<(Reg, Reg)>,
+    /// sqr <dst> <src> - multiply the value in the source register by itself
+    /// and put the result in the destination register
+    pub(super) sqr: Option<(Reg, Reg)>,
+    /// cmovz <dst> <src> <cond> - move the value in the source register into
+    /// the destination register if the condition register is numeric zero
+    pub(super) cmovz: Option<(Reg, Reg, Reg)>,
+    /// sti <addr> <const> - store a 32-bit numeric constant into memory
+    pub(super) sti: Option<(Addr, Const)>,
+    /// clr <reg> - clear a register back to uninitialized
+    pub(super) clr: Option<Reg>,
+    /// mov <dst> <src> - copy the value in the source register into the
+    /// destination register
+    pub(super) mov: Option<(Reg, Reg)>,
+    /// jmp <label> - continue issuing from the instruction at the label, with
+    /// the label resolved to its instruction index
+    pub(super) jmp: Option<usize>,
+    /// jz <reg> <label> - continue issuing from the instruction at the label
+    /// if the register is numeric zero, with the label resolved to its
+    /// instruction index
+    pub(super) jz: Option<(Reg, usize)>,
+    /// halt - stop issuing instructions after this one, taking precedence
+    /// over a jump in the same bundle
+    pub(super) halt: bool,
 }
 
 impl std::fmt::Display for Instruction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{{")?;
 
-        if let Some((reg, constant)) = &self.ldi {
-            write!(f, " ldi {} {};", reg, constant)?;
-        }
-
-        if let Some((reg, addr)) = &self.ldr {
-            write!(f, " ldr {} {};", reg, addr)?;
-        }
-
-        if let Some((reg, addr)) = &self.str {
-            write!(f, " str {} {};", reg, addr)?;
-        }
-
-        if let Some((dst, src1, src2)) = &self.add {
-            write!(f, " add {} {} {};", dst, src1, src2)?;
-        }
-
-        if let Some((dst, src1, src2)) = &self.sub {
-            write!(f, " sub {} {} {};", dst, src1, src2)?;
-        }
-
-        if let Some((dst, src1, src2)) = &self.mul {
-            write!(f, " mul {} {} {};", dst, src1, src2)?;
+        for (spec, operands) in self.operations() {
+            write!(f, " {}", spec.mnemonic)?;
+            for (kind, operand) in spec.operands.iter().zip(operands) {
+                match kind {
+                    OperandKind::Register => write!(f, " {}", Reg(operand))?,
+                    OperandKind::Address => write!(f, " {}", Addr(operand))?,
+                    OperandKind::Constant => write!(f, " {}", Const(operand))?,
+                    OperandKind::Label => write!(f, " {}", operand)?,
+                }
+            }
+            write!(f, ";")?;
         }
 
         write!(f, " }}")?;
@@ -53,16 +168,57 @@ impl std::fmt::Display for Instruction {
     }
 }
 
+/// Canonical form of an `Instruction` that compares equal for bundles with
+/// the same operations, regardless of the order they were set in
+///
+/// Operations are kept in issue order as `(kind, operands)`, with operands
+/// rendered as their numeric IDs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CanonicalInstruction(Vec<(OpKind, Vec<u32>)>);
+
+impl std::fmt::Display for CanonicalInstruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ops: Vec<String> = self
+            .0
+            .iter()
+            .map(|(kind, operands)| {
+                operands
+                    .iter()
+                    .fold(kind.to_string(), |op, operand| format!("{op} {operand}"))
+            })
+            .collect();
+        write!(f, "{{ {} }}", ops.join("; "))
+    }
+}
+
 impl Instruction {
     /// Create an empty `Instruction`
     pub fn new() -> Self {
         Self {
             ldi: None,
             ldr: None,
-            str: None,
+            str: Vec::new(),
             add: None,
             sub: None,
             mul: None,
+            div: None,
+            pow: None,
+            min: None,
+            max: None,
+            and: None,
+            or: None,
+            xor: None,
+            shl: None,
+            shr: None,
+            neg: None,
+            sqr: None,
+            cmovz: None,
+            sti: None,
+            clr: None,
+            mov: None,
+            jmp: None,
+            jz: None,
+            halt: false,
         }
     }
 
@@ -86,13 +242,17 @@ impl Instruction {
         self
     }
 
-    /// Set `str`` instruction to store a value from a register into memory
+    /// Add a `str` instruction to store a value from a register into memory
+    ///
+    /// # Note
+    /// Unlike other operations, a bundle may hold several `str`s, which all
+    /// complete in the same cycle.
     ///
     /// # Arguments
     /// * `src` - source register
     /// * `addr` - memory address to store into
     pub fn with_str(mut self, src: Reg, addr: Addr) -> Self {
-        self.str = Some((src, addr));
+        self.str.push((src, addr));
         self
     }
 
@@ -134,4 +294,583 @@ impl Instruction {
         self.mul = Some((dst, src1, src2));
         self
     }
+
+    /// Set `div` instruction to divide the value of source register 1 by source
+    /// register 2 and put the quotient in the destination register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src1` - source register 1
+    /// * `src2` - source register 2
+    pub fn with_div(mut self, dst: Reg, src1: Reg, src2: Reg) -> Self {
+        self.div = Some((dst, src1, src2));
+        self
+    }
+
+    /// Set `powr` instruction to raise the value of source register 1 to the
+    /// power of source register 2 and put the result in the destination
+    /// register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src1` - base register
+    /// * `src2` - exponent register
+    pub fn with_pow(mut self, dst: Reg, src1: Reg, src2: Reg) -> Self {
+        self.pow = Some((dst, src1, src2));
+        self
+    }
+
+    /// Set `min` instruction to put the smaller of the values in the source
+    /// registers in the destination register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src1` - source register 1
+    /// * `src2` - source register 2
+    ///
+    /// # Note
+    /// Both source registers must hold numeric constants when the instruction
+    /// is issued.
+    pub fn with_min(mut self, dst: Reg, src1: Reg, src2: Reg) -> Self {
+        self.min = Some((dst, src1, src2));
+        self
+    }
+
+    /// Set `max` instruction to put the larger of the values in the source
+    /// registers in the destination register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src1` - source register 1
+    /// * `src2` - source register 2
+    ///
+    /// # Note
+    /// Both source registers must hold numeric constants when the instruction
+    /// is issued.
+    pub fn with_max(mut self, dst: Reg, src1: Reg, src2: Reg) -> Self {
+        self.max = Some((dst, src1, src2));
+        self
+    }
+
+    /// Set `and` instruction to put the bitwise AND of the values in the
+    /// source registers in the destination register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src1` - source register 1
+    /// * `src2` - source register 2
+    pub fn with_and(mut self, dst: Reg, src1: Reg, src2: Reg) -> Self {
+        self.and = Some((dst, src1, src2));
+        self
+    }
+
+    /// Set `or` instruction to put the bitwise OR of the values in the
+    /// source registers in the destination register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src1` - source register 1
+    /// * `src2` - source register 2
+    pub fn with_or(mut self, dst: Reg, src1: Reg, src2: Reg) -> Self {
+        self.or = Some((dst, src1, src2));
+        self
+    }
+
+    /// Set `xor` instruction to put the bitwise XOR of the values in the
+    /// source registers in the destination register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src1` - source register 1
+    /// * `src2` - source register 2
+    pub fn with_xor(mut self, dst: Reg, src1: Reg, src2: Reg) -> Self {
+        self.xor = Some((dst, src1, src2));
+        self
+    }
+
+    /// Set `shl` instruction to shift the value of source register 1 left by
+    /// the value of source register 2 and put the result in the
+    /// destination register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src1` - source register 1
+    /// * `src2` - source register 2
+    pub fn with_shl(mut self, dst: Reg, src1: Reg, src2: Reg) -> Self {
+        self.shl = Some((dst, src1, src2));
+        self
+    }
+
+    /// Set `shr` instruction to shift the value of source register 1 right
+    /// by the value of source register 2 and put the result in the
+    /// destination register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src1` - source register 1
+    /// * `src2` - source register 2
+    pub fn with_shr(mut self, dst: Reg, src1: Reg, src2: Reg) -> Self {
+        self.shr = Some((dst, src1, src2));
+        self
+    }
+
+    /// Set `neg` instruction to negate the value in the source register and put
+    /// it in the destination register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src` - source register
+    pub fn with_neg(mut self, dst: Reg, src: Reg) -> Self {
+        self.neg = Some((dst, src));
+        self
+    }
+
+    /// Set `sqr` instruction to multiply the value in the source register by
+    /// itself and put the result in the destination register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src` - source register
+    pub fn with_sqr(mut self, dst: Reg, src: Reg) -> Self {
+        self.sqr = Some((dst, src));
+        self
+    }
+
+    /// Set `cmovz` instruction to move the value in the source register into
+    /// the destination register if the condition register is numeric zero
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src` - source register
+    /// * `cond` - condition register
+    pub fn with_cmovz(mut self, dst: Reg, src: Reg, cond: Reg) -> Self {
+        self.cmovz = Some((dst, src, cond));
+        self
+    }
+
+    /// Set `sti` instruction to store a constant into memory
+    ///
+    /// # Arguments
+    /// * `addr` - memory address to store into
+    /// * `constant` - constant to store
+    pub fn with_sti(mut self, addr: Addr, constant: Const) -> Self {
+        self.sti = Some((addr, constant));
+        self
+    }
+
+    /// Set `clr` instruction to clear a register back to uninitialized
+    ///
+    /// # Arguments
+    /// * `reg` - register to clear
+    pub fn with_clr(mut self, reg: Reg) -> Self {
+        self.clr = Some(reg);
+        self
+    }
+
+    /// Set `mov` instruction to copy a register into another register
+    ///
+    /// # Arguments
+    /// * `dst` - destination register
+    /// * `src` - source register
+    pub fn with_mov(mut self, dst: Reg, src: Reg) -> Self {
+        self.mov = Some((dst, src));
+        self
+    }
+
+    /// Set `jmp` instruction to continue issuing from another instruction
+    ///
+    /// # Note
+    /// Operations already in flight are not affected by the jump and still
+    /// complete at their scheduled cycle.
+    ///
+    /// # Arguments
+    /// * `target` - index of the instruction to issue next
+    pub fn with_jmp(mut self, target: usize) -> Self {
+        self.jmp = Some(target);
+        self
+    }
+
+    /// Set `jz` instruction to continue issuing from another instruction if a
+    /// register is numeric zero
+    ///
+    /// # Note
+    /// Operations already in flight are not affected by the jump and still
+    /// complete at their scheduled cycle.
+    ///
+    /// # Arguments
+    /// * `reg` - condition register
+    /// * `target` - index of the instruction to issue next if the condition is
+    ///   zero
+    pub fn with_jz(mut self, reg: Reg, target: usize) -> Self {
+        self.jz = Some((reg, target));
+        self
+    }
+
+    /// Set `halt` instruction to stop issuing instructions after this one
+    ///
+    /// # Note
+    /// Operations already in flight, including those of this instruction,
+    /// still complete before the computation returns. A `jmp` or `jz` in the
+    /// same instruction is ignored.
+    pub fn with_halt(mut self) -> Self {
+        self.halt = true;
+        self
+    }
+
+    /// Render the instruction as program source with one operation per line,
+    /// without the terminating `;`
+    ///
+    /// # Returns
+    /// * `String` - program source of the instruction's operations
+    pub fn to_source(&self) -> String {
+        self.operations()
+            .into_iter()
+            .map(|(spec, operands)| {
+                operands
+                    .iter()
+                    .fold(spec.mnemonic.to_string(), |op, operand| {
+                        format!("{op} {operand}")
+                    })
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Get the canonical form of the instruction, which is equal for bundles
+    /// with the same operations regardless of the order they were set in
+    ///
+    /// # Returns
+    /// * `CanonicalInstruction` - operations in issue order with their operands
+    pub fn canonical(&self) -> CanonicalInstruction {
+        CanonicalInstruction(
+            self.operations()
+                .into_iter()
+                .map(|(spec, operands)| (spec.kind, operands))
+                .collect(),
+        )
+    }
+
+    /// Get the operands of each operation of a kind set in this instruction
+    ///
+    /// # Arguments
+    /// * `kind` - operation kind
+    ///
+    /// # Returns
+    /// * `Vec<Vec<u32>>` - numeric operands in the order they are written, with
+    ///   one entry per `str` and at most one for other kinds
+    fn operands_of(&self, kind: OpKind) -> Vec<Vec<u32>> {
+        let binary = |op: Option<(Reg, Reg, Reg)>| op.map(|(a, b, c)| Vec::from([a.0, b.0, c.0]));
+        let unary = |op: Option<(Reg, Reg)>| op.map(|(a, b)| Vec::from([a.0, b.0]));
+        let operands = match kind {
+            OpKind::Ldi => self
+                .ldi
+                .map(|(reg, constant)| Vec::from([reg.0, constant.0])),
+            OpKind::Ldr => self.ldr.map(|(reg, addr)| Vec::from([reg.0, addr.0])),
+            OpKind::Str => {
+                return self
+                    .str
+                    .iter()
+                    .map(|(reg, addr)| Vec::from([reg.0, addr.0]))
+                    .collect()
+            }
+            OpKind::Sti => self
+                .sti
+                .map(|(addr, constant)| Vec::from([addr.0, constant.0])),
+            OpKind::Add => binary(self.add),
+            OpKind::Sub => binary(self.sub),
+            OpKind::Mul => binary(self.mul),
+            OpKind::Div => binary(self.div),
+            OpKind::Pow => binary(self.pow),
+            OpKind::Min => binary(self.min),
+            OpKind::Max => binary(self.max),
+            OpKind::And => binary(self.and),
+            OpKind::Or => binary(self.or),
+            OpKind::Xor => binary(self.xor),
+            OpKind::Shl => binary(self.shl),
+            OpKind::Shr => binary(self.shr),
+            OpKind::Neg => unary(self.neg),
+            OpKind::Sqr => unary(self.sqr),
+            OpKind::Cmovz => binary(self.cmovz),
+            OpKind::Mov => unary(self.mov),
+            OpKind::Clr => self.clr.map(|reg| Vec::from([reg.0])),
+            OpKind::Jmp => self.jmp.map(|target| Vec::from([target as u32])),
+            OpKind::Jz => self
+                .jz
+                .map(|(reg, target)| Vec::from([reg.0, target as u32])),
+            OpKind::Halt => self.halt.then(Vec::new),
+        };
+        operands.into_iter().collect()
+    }
+
+    /// Get every operation set in this instruction with its operands
+    ///
+    /// # Returns
+    /// * `Vec<(&OperationSpec, Vec<u32>)>` - operations in issue order
+    fn operations(&self) -> Vec<(&'static OperationSpec, Vec<u32>)> {
+        OPERATIONS
+            .iter()
+            .flat_map(|spec| {
+                self.operands_of(spec.kind)
+                    .into_iter()
+                    .map(move |operands| (spec, operands))
+            })
+            .collect()
+    }
+
+    /// Get the locations each operation reads when issued and the location it
+    /// writes when completed
+    ///
+    /// # Note
+    /// `cmovz` only writes its destination when its condition is zero, so its
+    /// destination may keep the previous value. Jumps and `halt` write nothing
+    /// and are left out.
+    ///
+    /// # Returns
+    /// * `Vec<(OpKind, Vec<Location>, Location)>` - `(kind, reads, write)` of
+    ///   each operation in issue order
+    pub(super) fn effects(&self) -> Vec<(OpKind, Vec<Location>, Location)> {
+        self.operations()
+            .into_iter()
+            .filter_map(|(spec, operands)| {
+                let write = spec.write?;
+                let locations = operand_locations(spec, &operands);
+                let reads = locations
+                    .iter()
+                    .filter(|(i, _)| *i != write)
+                    .map(|(_, location)| *location)
+                    .collect();
+                let (_, write) = locations.into_iter().find(|(i, _)| *i == write)?;
+                Some((spec.kind, reads, write))
+            })
+            .collect()
+    }
+
+    /// Get the registers and memory addresses read by this instruction
+    ///
+    /// # Returns
+    /// * `Vec<Location>` - locations read in issue order, with duplicates
+    pub fn reads(&self) -> Vec<Location> {
+        self.operations()
+            .into_iter()
+            .flat_map(|(spec, operands)| {
+                operand_locations(spec, &operands)
+                    .into_iter()
+                    .filter(|(i, _)| Some(*i) != spec.write)
+                    .map(|(_, location)| location)
+            })
+            .collect()
+    }
+
+    /// Get the registers and memory addresses written by this instruction
+    ///
+    /// # Returns
+    /// * `Vec<Location>` - locations written in issue order
+    pub fn writes(&self) -> Vec<Location> {
+        self.effects()
+            .into_iter()
+            .map(|(_, _, write)| write)
+            .collect()
+    }
+
+    /// Check that no two operations of this instruction write the same
+    /// destination, which would otherwise complete in different cycles
+    /// depending on their latencies
+    ///
+    /// # Note
+    /// `str`s to the same address are not checked since they complete in the
+    /// same cycle, which the machine reports as a memory data race.
+    ///
+    /// # Returns
+    /// * `Ok(())` if every operation writes a distinct destination
+    /// * `Err(InstructionError::ConflictingJumps)` if both `jmp` and `jz` are set
+    /// * `Err(InstructionError)` for the first destination written twice
+    pub fn validate(&self) -> Result<(), InstructionError> {
+        if self.jmp.is_some() && self.jz.is_some() {
+            return Err(InstructionError::ConflictingJumps);
+        }
+
+        let effects = self.effects();
+        for (i, (first, _, write)) in effects.iter().enumerate() {
+            if let Some((second, _, _)) = effects[i + 1..]
+                .iter()
+                .find(|(kind, _, w)| w == write && (*first, *kind) != (OpKind::Str, OpKind::Str))
+            {
+                let (first, second) = (*first, *second);
+                return Err(match *write {
+                    Location::Register(reg) => {
+                        InstructionError::DuplicateRegister { reg, first, second }
+                    }
+                    Location::Memory(addr) => InstructionError::DuplicateAddress {
+                        addr,
+                        first,
+                        second,
+                    },
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the kinds of all operations set in this instruction
+    ///
+    /// # Note
+    /// Kinds are listed in the order the operations are issued in, which does
+    /// not depend on the order they were set in.
+    ///
+    /// # Returns
+    /// * `Vec<OpKind>` - operation kinds, with one `Str` per stored register
+    pub fn op_kinds(&self) -> Vec<OpKind> {
+        self.operations()
+            .into_iter()
+            .map(|(spec, _)| spec.kind)
+            .collect()
+    }
+}
+
+/// Get the registers and memory addresses among the operands of an operation
+///
+/// # Arguments
+/// * `spec` - operation kind
+/// * `operands` - numeric operands in the order they are written
+///
+/// # Returns
+/// * `Vec<(usize, Location)>` - `(operand index, location)` of each register
+///   and address operand
+fn operand_locations(spec: &OperationSpec, operands: &[u32]) -> Vec<(usize, Location)> {
+    spec.operands
+        .iter()
+        .zip(operands)
+        .enumerate()
+        .filter_map(|(i, (kind, &operand))| match kind {
+            OperandKind::Register => Some((i, Location::Register(Reg(operand)))),
+            OperandKind::Address => Some((i, Location::Memory(Addr(operand)))),
+            OperandKind::Constant | OperandKind::Label => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_canonical_order_independent() {
+        let lhs = Instruction::new()
+            .with_ldi(Reg(1), Const(2))
+            .with_add(Reg(0), Reg(1), Reg(2));
+        let rhs = Instruction::new()
+            .with_add(Reg(0), Reg(1), Reg(2))
+            .with_ldi(Reg(1), Const(2));
+        assert_eq!(lhs.canonical(), rhs.canonical());
+        assert_eq!(lhs.canonical().to_string(), "{ ldi 1 2; add 0 1 2 }");
+
+        let other = Instruction::new()
+            .with_add(Reg(0), Reg(2), Reg(1))
+            .with_ldi(Reg(1), Const(2));
+        assert_ne!(lhs.canonical(), other.canonical());
+    }
+
+    #[test]
+    fn test_validate() {
+        let instruction = Instruction::new()
+            .with_ldi(Reg(0), Const(1))
+            .with_mov(Reg(0), Reg(2));
+        assert_eq!(
+            instruction.validate().unwrap_err(),
+            InstructionError::DuplicateRegister {
+                reg: Reg(0),
+                first: OpKind::Ldi,
+                second: OpKind::Mov,
+            }
+        );
+
+        let instruction = Instruction::new()
+            .with_str(Reg(1), Addr(3))
+            .with_sti(Addr(3), Const(4));
+        assert_eq!(
+            instruction.validate().unwrap_err(),
+            InstructionError::DuplicateAddress {
+                addr: Addr(3),
+                first: OpKind::Str,
+                second: OpKind::Sti,
+            }
+        );
+
+        let instruction = Instruction::new()
+            .with_ldi(Reg(0), Const(1))
+            .with_add(Reg(1), Reg(0), Reg(0))
+            .with_str(Reg(0), Addr(0))
+            .with_str(Reg(1), Addr(0));
+        assert!(instruction.validate().is_ok());
+        assert_eq!(
+            Instruction::new()
+                .with_jmp(0)
+                .with_jz(Reg(0), 1)
+                .validate()
+                .unwrap_err(),
+            InstructionError::ConflictingJumps
+        );
+        assert_eq!(
+            instruction.op_kinds(),
+            Vec::from([OpKind::Ldi, OpKind::Str, OpKind::Str, OpKind::Add])
+        );
+    }
+
+    #[test]
+    fn test_reads_writes() {
+        let instruction = Instruction::new()
+            .with_ldr(Reg(1), Addr(4))
+            .with_str(Reg(2), Addr(5))
+            .with_mul(Reg(0), Reg(1), Reg(3));
+        assert_eq!(
+            instruction.reads(),
+            Vec::from([
+                Location::Memory(Addr(4)),
+                Location::Register(Reg(2)),
+                Location::Register(Reg(1)),
+                Location::Register(Reg(3)),
+            ])
+        );
+        assert_eq!(
+            instruction.writes(),
+            Vec::from([
+                Location::Register(Reg(1)),
+                Location::Memory(Addr(5)),
+                Location::Register(Reg(0)),
+            ])
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new()
+                .with_ldi(Reg(2), Const(2))
+                .with_ldr(Reg(3), Addr(1)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+            Instruction::new().with_add(Reg(2), Reg(2), Reg(3)),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(0), Reg(0), Reg(2)),
+        ]);
+        let json = serde_json::to_string(&program).unwrap();
+        let deserialized: Vec<Instruction> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            deserialized
+                .iter()
+                .map(Instruction::canonical)
+                .collect::<Vec<_>>(),
+            program
+                .iter()
+                .map(Instruction::canonical)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(serde_json::to_string(&deserialized).unwrap(), json);
+    }
 }
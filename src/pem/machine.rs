@@ -1,33 +1,87 @@
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
 use log::{debug, trace, warn};
 use thiserror::Error;
 
 use super::{
     inflight_operation::{InflightOperation, OperationOutput},
+    memory::Memory,
+    scenario::MachineConfig,
+    timeline::OperationRecord,
     types::{Addr, Reg},
-    ExprWrapper, Instruction,
+    ExpectedState, ExprWrapper, Instruction, InstructionError, Latencies, Location, Mismatch,
+    OpKind, Scenario,
 };
 
-const REGISTER_COUNT: usize = 8;
+/// Default number of registers in the PEM
+pub const REGISTER_COUNT: usize = 8;
 
-/// Polynomial Evaluation Machine (PEM) with 8 32-bit registers and a 32-bit
-/// addressable memory
+/// Polynomial Evaluation Machine (PEM) with 8 32-bit registers by default and
+/// a 32-bit addressable memory
 #[derive(Debug)]
-pub(crate) struct Machine {
+pub struct Machine {
     /// Registers
     regs: Vec<Option<ExprWrapper>>,
     /// Memory
-    mem: HashMap<Addr, ExprWrapper>,
+    mem: Memory,
     /// Program counter
     pc: usize,
+    /// Index of the next instruction to issue, behind `pc` once stalled and
+    /// moved by jumps
+    next_instruction: usize,
     /// Pending operations
     pending_operations: BinaryHeap<InflightOperation>,
+    /// Maximum number of cycles to run while operations are pending
+    max_cycles: usize,
 
     allow_data_race: bool,
+    /// Whether operations overwriting registers written by later instructions
+    /// fail the computation
+    detect_write_after_write: bool,
+    /// `(instruction, issued_at, cycle)` of the operation that last wrote each
+    /// register
+    last_writers: HashMap<Reg, (usize, usize, usize)>,
+    /// Number of registers
+    register_count: usize,
+    /// Register holding the result of a program
+    result_register: Reg,
+    /// Whether registers start at `0` rather than uninitialized
+    zeroed_registers: bool,
+    /// Number of cycles each operation takes to complete
+    latencies: Latencies,
+    /// When to stop draining pending operations after the last instruction
+    flush_policy: FlushPolicy,
+    /// How to read registers with pending writes
+    hazard_policy: HazardPolicy,
+    /// How to load memory addresses with pending stores
+    store_policy: StorePolicy,
+    /// Whether operations with only numeric operands store a numeric constant
+    eager_fold: bool,
+    /// Whether numeric operations overflowing 32 bits fail the computation
+    /// rather than wrap around
+    checked_arithmetic: bool,
+    /// Whether register operands are forwarded from operations completing at
+    /// the end of the current cycle
+    bypass: bool,
+    /// Opcodes programs may use, or `None` if every opcode is permitted
+    permitted_opcodes: Option<HashSet<OpKind>>,
+    /// Data races allowed to continue by `allow_data_race`
+    races: Vec<RaceReport>,
+    /// Retired operations as `(cycle, instruction, issued_at, output)` in
+    /// retire order
+    retirements: Vec<(usize, usize, usize, OperationOutput)>,
+    /// Number of operations of each kind issued at each cycle
+    issues: Vec<HashMap<OpKind, usize>>,
+    /// Pipeline state captured when the last computation failed
+    error_context: Option<ErrorContext>,
+    /// Whether a `halt` was issued, so no further instructions are fetched
+    halted: bool,
+    /// Locations written by the operations completed in the last cycle, see
+    /// `CycleEvent::completed`
+    completions: Vec<(Location, Option<ExprWrapper>)>,
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Clone, Error, PartialEq)]
 pub enum ComputeError {
     #[error("Machine terminated. Please use a new machine.")]
     Terminated,
@@ -37,6 +91,29 @@ pub enum ComputeError {
     UninitializedRegister { reg: Reg, pc: usize },
     #[error("Accessing uninitialized memory address #{} at instruction #{pc}", .addr.0)]
     UninitializedMemory { addr: Addr, pc: usize },
+    #[error("Opcode {op} not permitted at instruction #{pc}")]
+    OpcodeNotPermitted { op: OpKind, pc: usize },
+    #[error("Division by zero at instruction #{pc}")]
+    DivisionByZero { pc: usize },
+    #[error("Arithmetic overflow at instruction #{pc}")]
+    ArithmeticOverflow { pc: usize },
+    #[error(
+        "Reading register #{} before the pending write by instruction #{pending_inst} at instruction #{pc}",
+        .reg.0
+    )]
+    ReadHazard {
+        reg: Reg,
+        pc: usize,
+        pending_inst: usize,
+    },
+    #[error("Loading memory address #{} with a pending store at instruction #{pc}", .addr.0)]
+    MemoryReadBeforeWrite { addr: Addr, pc: usize },
+    #[error("Branching on non-numeric register #{} at instruction #{pc}", .reg.0)]
+    BranchOnSymbolic { reg: Reg, pc: usize },
+    #[error("Non-numeric operand of min or max at instruction #{pc}")]
+    NonNumericComparison { pc: usize },
+    #[error("Non-numeric condition in register #{} at instruction #{pc}", .reg.0)]
+    NonNumericCondition { reg: Reg, pc: usize },
     #[error("Register #{} data race detected at cycle #{pc} from operations originated by instructions #{inst1} and #{inst2}", .reg.0)]
     RegisterDataRace {
         reg: Reg,
@@ -51,6 +128,225 @@ pub enum ComputeError {
         inst1: usize,
         inst2: usize,
     },
+    #[error("Invalid instruction #{pc}: {error}")]
+    InvalidInstruction { error: InstructionError, pc: usize },
+    #[error("Cycle limit of {max} exceeded with operations still pending")]
+    CycleLimitExceeded { max: usize },
+    #[error("Cycle limit of {max} exceeded while still jumping")]
+    JumpLimitExceeded { max: usize },
+    #[error("Register #{} write-after-write hazard at cycle #{pc}: operation originated by instruction #{earlier_inst} overwrites the result of instruction #{later_inst}", .reg.0)]
+    WriteAfterWrite {
+        reg: Reg,
+        pc: usize,
+        earlier_inst: usize,
+        later_inst: usize,
+    },
+}
+
+/// Policy controlling how long pending operations are drained after the last
+/// instruction is issued
+///
+/// # Variants
+/// * `FullDrain` - retire every pending operation
+/// * `UntilResultReady` - stop once the result register has no pending writes,
+///   leaving other operations in flight and uncounted
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FlushPolicy {
+    #[default]
+    FullDrain,
+    UntilResultReady,
+}
+
+/// Policy controlling how an instruction reads a register with a pending
+/// write, i.e. a read-after-write hazard
+///
+/// # Variants
+/// * `ReadStale` - read the last value written back to the register
+/// * `Stall` - defer issuing the instruction until every pending write to its
+///   register operands has retired
+/// * `Error` - fail the computation with `ComputeError::ReadHazard`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HazardPolicy {
+    #[default]
+    ReadStale,
+    Stall,
+    Error,
+}
+
+/// Policy controlling how `ldr` loads a memory address with a pending store,
+/// i.e. a store-to-load hazard
+///
+/// # Variants
+/// * `ReadStale` - load the last value written back to memory
+/// * `Forward` - load the value of the most recently issued pending store
+/// * `Error` - fail the computation with `ComputeError::MemoryReadBeforeWrite`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StorePolicy {
+    #[default]
+    ReadStale,
+    Forward,
+    Error,
+}
+
+/// Progress of a program computed by `Machine::compute_yielding()`
+///
+/// # Variants
+/// * `Pending` - the cycle budget ran out before the program finished
+/// * `Done(result)` - the program terminated with `result`
+#[derive(Debug, PartialEq)]
+pub enum ComputeProgress {
+    Pending,
+    Done(Result<ExprWrapper, ComputeError>),
+}
+
+/// Result of a program together with the number of cycles it took, to compare
+/// the scheduling of programs computing the same polynomial
+#[derive(Debug, PartialEq)]
+pub struct ComputeSummary<'a> {
+    /// Value of the result register once the program terminated
+    pub value: &'a ExprWrapper,
+    /// Number of cycles executed, including draining pending operations
+    pub cycles: usize,
+    /// Instruction whose operation last wrote the result register, see
+    /// `Machine::last_writer()`
+    pub result_writer: Option<usize>,
+}
+
+/// Scheduling density of a program, to compare the efficiency of programs
+/// computing the same polynomial, see `Machine::stats()`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecutionStats {
+    /// Number of operations of each kind issued, with no entry for kinds
+    /// never issued
+    pub issued: HashMap<OpKind, usize>,
+    /// Number of cycles at which at least one operation completed
+    pub busy_cycles: usize,
+    /// Number of cycles at which no operation completed
+    pub idle_cycles: usize,
+}
+
+impl ExecutionStats {
+    /// Get the number of operations of a kind issued
+    ///
+    /// # Arguments
+    /// * `kind` - kind of operation to count
+    pub fn count(&self, kind: OpKind) -> usize {
+        self.issued.get(&kind).copied().unwrap_or(0)
+    }
+}
+
+/// Checkpoint of the execution state of a `Machine`, see `Machine::snapshot()`
+#[derive(Debug, Clone)]
+pub struct MachineState {
+    regs: Vec<Option<ExprWrapper>>,
+    mem: Memory,
+    pc: usize,
+    next_instruction: usize,
+    pending_operations: BinaryHeap<InflightOperation>,
+    last_writers: HashMap<Reg, (usize, usize, usize)>,
+    races: Vec<RaceReport>,
+    retirements: Vec<(usize, usize, usize, OperationOutput)>,
+    issues: Vec<HashMap<OpKind, usize>>,
+    halted: bool,
+}
+
+/// Pipeline state at the cycle a computation failed, for crash reports
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorContext {
+    /// Cycle at which the computation failed
+    pub pc: usize,
+    /// Operations retiring in or still pending at the failing cycle, in retire
+    /// order
+    pub in_flight: Vec<InFlightRecord>,
+}
+
+/// An operation retiring in or still pending at the cycle a computation
+/// failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct InFlightRecord {
+    /// Index of the instruction that issued the operation
+    pub instruction: usize,
+    /// Cycle the operation was issued at
+    pub issued_at: usize,
+    /// Cycle by which the operation completes
+    pub complete_by: usize,
+    /// Description of the operation output
+    pub output: String,
+}
+
+/// Outcome of a single cycle run by `Machine::step()`
+///
+/// # Variants
+/// * `Busy` - operations are still pending after the cycle
+/// * `Idle` - no operations are pending after the cycle
+/// * `Stalled` - the instruction was deferred by `HazardPolicy::Stall` and
+///   must be passed to the next step again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    Busy,
+    Idle,
+    Stalled,
+}
+
+/// A cycle run by `Machine::run_events()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleEvent {
+    /// Cycle that was run
+    pub cycle: usize,
+    /// Instruction issued at the cycle, or `None` if the cycle stalled or
+    /// only drained pending operations
+    pub issued: Option<usize>,
+    /// `(location, value)` written by each operation completed at the cycle,
+    /// in retire order, where `value` is `None` for cleared registers
+    pub completed: Vec<(Location, Option<String>)>,
+}
+
+/// Register or memory address written by racing operations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RaceResource {
+    Register(Reg),
+    Memory(Addr),
+}
+
+/// A data race that was allowed to continue executing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaceReport {
+    /// Register or memory address written by both operations
+    pub resource: RaceResource,
+    /// Cycle at which both operations completed
+    pub pc: usize,
+    /// Instruction originating the overwritten operation
+    pub inst1: usize,
+    /// Instruction originating the operation whose value was kept
+    pub inst2: usize,
+}
+
+impl From<RaceReport> for ComputeError {
+    fn from(report: RaceReport) -> Self {
+        let RaceReport {
+            resource,
+            pc,
+            inst1,
+            inst2,
+        } = report;
+        match resource {
+            RaceResource::Register(reg) => Self::RegisterDataRace {
+                reg,
+                pc,
+                inst1,
+                inst2,
+            },
+            RaceResource::Memory(addr) => Self::MemoryDataRace {
+                addr,
+                pc,
+                inst1,
+                inst2,
+            },
+        }
+    }
 }
 
 impl Machine {
@@ -61,10 +357,197 @@ impl Machine {
     pub fn new(mem: HashMap<Addr, ExprWrapper>) -> Self {
         Self {
             regs: vec![None; REGISTER_COUNT],
-            mem,
+            mem: Memory::from(mem),
             pc: 0,
+            next_instruction: 0,
             pending_operations: BinaryHeap::new(),
+            max_cycles: usize::MAX,
             allow_data_race: false,
+            detect_write_after_write: false,
+            last_writers: HashMap::new(),
+            register_count: REGISTER_COUNT,
+            result_register: Reg(0),
+            zeroed_registers: false,
+            latencies: Latencies::default(),
+            flush_policy: FlushPolicy::default(),
+            hazard_policy: HazardPolicy::default(),
+            store_policy: StorePolicy::default(),
+            eager_fold: false,
+            checked_arithmetic: false,
+            bypass: false,
+            permitted_opcodes: None,
+            races: Vec::new(),
+            retirements: Vec::new(),
+            issues: Vec::new(),
+            error_context: None,
+            halted: false,
+            completions: Vec::new(),
+        }
+    }
+
+    /// Set the register holding the result of a program, returned by
+    /// `compute()` and waited for by `FlushPolicy::UntilResultReady`
+    ///
+    /// # Arguments
+    /// * `reg` - result register, `Reg(0)` by default
+    ///
+    /// # Panics
+    /// * If `reg` is not below the register count
+    pub fn with_result_register(mut self, reg: Reg) -> Self {
+        assert!(
+            (reg.0 as usize) < self.register_count,
+            "Result register {} is out of range",
+            reg
+        );
+        self.result_register = reg;
+        self
+    }
+
+    /// Set the number of cycles each operation takes to complete
+    ///
+    /// # Arguments
+    /// * `latencies` - operation latencies to use
+    pub fn with_latencies(mut self, latencies: Latencies) -> Self {
+        self.latencies = latencies;
+        self
+    }
+
+    /// Store the values of memory addresses below `capacity` in a `Vec` rather
+    /// than a `HashMap`, which is faster for programs using a contiguous range
+    /// of low addresses
+    ///
+    /// # Note
+    /// Addresses at or above `capacity` are still stored sparsely, and the
+    /// capacity is kept by `reset()`.
+    ///
+    /// # Arguments
+    /// * `capacity` - number of low addresses to store densely
+    pub fn with_dense_memory(mut self, capacity: u32) -> Self {
+        self.mem = std::mem::take(&mut self.mem).with_dense_capacity(capacity);
+        self
+    }
+
+    /// Set the number of registers, resetting every register
+    ///
+    /// # Arguments
+    /// * `count` - number of registers, `REGISTER_COUNT` by default
+    ///
+    /// # Panics
+    /// * If `count` is `0` or does not cover the result register
+    pub fn with_register_count(mut self, count: usize) -> Self {
+        assert!(count > 0, "Register count must be at least 1");
+        assert!(
+            (self.result_register.0 as usize) < count,
+            "Register count must cover result register {}",
+            self.result_register
+        );
+        self.register_count = count;
+        self.regs = self.initial_registers();
+        self
+    }
+
+    /// Initialize every register to `0` rather than leaving it uninitialized,
+    /// so reading a register that was never written yields `0`
+    pub fn with_zeroed_registers(mut self) -> Self {
+        self.zeroed_registers = true;
+        self.regs = self.initial_registers();
+        self
+    }
+
+    /// Set when to stop draining pending operations after the last instruction
+    ///
+    /// # Arguments
+    /// * `policy` - flush policy to use
+    pub fn with_flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flush_policy = policy;
+        self
+    }
+
+    /// Set how instructions read registers with pending writes
+    ///
+    /// # Note
+    /// Writes forwarded by the bypass network are not hazards.
+    ///
+    /// # Arguments
+    /// * `policy` - hazard policy to use
+    pub fn with_hazard_policy(mut self, policy: HazardPolicy) -> Self {
+        self.hazard_policy = policy;
+        self
+    }
+
+    /// Set whether reading a register with a pending write fails the
+    /// computation, i.e. the hazard policy is `HazardPolicy::Error`
+    ///
+    /// # Note
+    /// Turning strict hazards off restores the default hazard policy if it was
+    /// `HazardPolicy::Error` and keeps any other policy.
+    ///
+    /// # Arguments
+    /// * `strict` - whether read-after-write hazards are errors
+    pub fn with_strict_hazards(mut self, strict: bool) -> Self {
+        if strict {
+            self.hazard_policy = HazardPolicy::Error;
+        } else if self.hazard_policy == HazardPolicy::Error {
+            self.hazard_policy = HazardPolicy::default();
+        }
+        self
+    }
+
+    /// Limit the number of cycles a computation may run, so programs with huge
+    /// latencies or infinite loops cannot run for a very long time
+    ///
+    /// # Note
+    /// Operations still pending once the limit is reached fail the computation
+    /// with `ComputeError::CycleLimitExceeded`, and jumps taken once it is
+    /// reached fail it with `ComputeError::JumpLimitExceeded`.
+    ///
+    /// # Arguments
+    /// * `max` - maximum number of cycles, unbounded by default
+    pub fn with_max_cycles(mut self, max: usize) -> Self {
+        self.max_cycles = max;
+        self
+    }
+
+    /// Fail the computation when an arithmetic operation with only numeric
+    /// operands overflows 32 bits, rather than wrapping around
+    ///
+    /// # Note
+    /// Subtracting a larger constant and negating a non-zero constant overflow
+    /// as values are unsigned. Operations with symbolic operands never
+    /// overflow.
+    ///
+    /// # Arguments
+    /// * `checked` - whether to check for overflows, wrapping by default
+    pub fn with_checked_arithmetic(mut self, checked: bool) -> Self {
+        self.checked_arithmetic = checked;
+        self
+    }
+
+    /// Set how `ldr` loads memory addresses with pending stores
+    ///
+    /// # Arguments
+    /// * `policy` - store policy to use
+    pub fn with_store_policy(mut self, policy: StorePolicy) -> Self {
+        self.store_policy = policy;
+        self
+    }
+
+    /// Restrict the opcodes programs may use, e.g. forbid `str` so untrusted
+    /// programs cannot mutate memory
+    ///
+    /// # Arguments
+    /// * `opcodes` - opcodes to permit, any other opcode fails the computation
+    pub fn with_permitted_opcodes<I: IntoIterator<Item = OpKind>>(mut self, opcodes: I) -> Self {
+        self.permitted_opcodes = Some(opcodes.into_iter().collect());
+        self
+    }
+
+    /// Get the registers a program starts with
+    fn initial_registers(&self) -> Vec<Option<ExprWrapper>> {
+        if self.zeroed_registers {
+            vec![Some(ExprWrapper::from(0)); self.register_count]
+        } else {
+            vec![None; self.register_count]
         }
     }
 
@@ -75,47 +558,252 @@ impl Machine {
         }
     }
 
-    /// Compute the result of a program
+    /// Fail with `ComputeError::WriteAfterWrite` when an operation overwrites a
+    /// register written in an earlier cycle by an operation of a later
+    /// instruction, clobbering the later result
+    ///
+    /// # Note
+    /// Disabled by default since programs may deliberately reuse a register
+    /// once the later result has been read, as `long_polynomial.txt` does.
     ///
     /// # Arguments
-    /// * `program` - a vector of `Instruction`s to compute
+    /// * `detect` - whether to detect write-after-write hazards
+    pub fn detect_write_after_write(&mut self, detect: bool) {
+        self.detect_write_after_write = detect;
+    }
+
+    /// Fold arithmetic operations with only numeric operands into numeric
+    /// constants as they are issued, so fully numeric computations never grow
+    /// the expression tree
     ///
-    /// # Returns
-    /// * `Ok(value)` if the program terminated successfully
-    /// * `Err(ComputeError)` if the program terminated with an error
-    pub fn compute(&mut self, program: &Vec<Instruction>) -> Result<&ExprWrapper, ComputeError> {
-        if self.pc != 0 {
-            return Err(ComputeError::Terminated);
-        }
+    /// # Arguments
+    /// * `fold` - whether to fold eagerly
+    pub fn eager_fold(&mut self, fold: bool) {
+        self.eager_fold = fold;
+    }
 
-        for instruction in program {
-            debug!("Executing instruction #{}: {}", self.pc, instruction);
-            self.begin_execution(instruction)?;
-            self.end_cycle()?;
-        }
+    /// Forward the results of operations completing at the end of the current
+    /// cycle to the register operands read in this cycle, so dependent
+    /// operations can be issued one cycle before the result is written back
+    ///
+    /// # Note
+    /// Only register operands are forwarded, and only from operations issued
+    /// in earlier cycles.
+    ///
+    /// # Arguments
+    /// * `enable` - whether to enable the bypass network
+    pub fn bypass(&mut self, enable: bool) {
+        self.bypass = enable;
+    }
 
-        while self.pending_operations.peek().is_some() {
-            self.end_cycle()?;
-        }
+    /// Reset the registers, pending operations and program counter so another
+    /// program can run against the current memory
+    pub fn reset_registers(&mut self) {
+        self.regs = self.initial_registers();
+        self.pending_operations.clear();
+        self.last_writers.clear();
+        self.pc = 0;
+        self.next_instruction = 0;
+        self.races.clear();
+        self.retirements.clear();
+        self.issues.clear();
+        self.error_context = None;
+        self.halted = false;
+        self.completions.clear();
+    }
 
-        debug!("All instructions executed");
+    /// Reset the machine to run another program against new memory, keeping
+    /// its configuration such as `allow_data_race`
+    ///
+    /// # Arguments
+    /// * `mem` - memory to run the next program with
+    pub fn reset(&mut self, mem: HashMap<Addr, ExprWrapper>) {
+        self.reset_registers();
+        self.mem.replace(mem);
+    }
 
-        self.get_register_value(Reg(0))
+    /// Save the execution state, including pending operations, to roll back to
+    /// with `restore()`
+    ///
+    /// # Note
+    /// Configuration such as latencies and policies is not part of the state.
+    pub fn snapshot(&self) -> MachineState {
+        MachineState {
+            regs: self.regs.clone(),
+            mem: self.mem.clone(),
+            pc: self.pc,
+            next_instruction: self.next_instruction,
+            pending_operations: self.pending_operations.clone(),
+            last_writers: self.last_writers.clone(),
+            races: self.races.clone(),
+            retirements: self.retirements.clone(),
+            issues: self.issues.clone(),
+            halted: self.halted,
+        }
     }
 
-    /// Validate a register and return it if valid
+    /// Roll back to an execution state saved by `snapshot()`
     ///
     /// # Arguments
-    /// * `reg` - register to validate
+    /// * `state` - state to restore
+    pub fn restore(&mut self, state: MachineState) {
+        let MachineState {
+            regs,
+            mem,
+            pc,
+            next_instruction,
+            pending_operations,
+            last_writers,
+            races,
+            retirements,
+            issues,
+            halted,
+        } = state;
+        self.regs = regs;
+        self.mem = mem;
+        self.pc = pc;
+        self.next_instruction = next_instruction;
+        self.pending_operations = pending_operations;
+        self.last_writers = last_writers;
+        self.races = races;
+        self.retirements = retirements;
+        self.issues = issues;
+        self.halted = halted;
+        self.error_context = None;
+        self.completions.clear();
+    }
+
+    /// Capture a replayable scenario of running a program on this machine
+    ///
+    /// # Note
+    /// The scenario captures the current memory and the configuration of the
+    /// machine but not its registers, so it should be captured before
+    /// computing the program.
+    ///
+    /// # Arguments
+    /// * `program` - program to capture
+    pub fn capture_scenario(&self, program: &[Instruction]) -> Scenario {
+        let permitted_opcodes = self.permitted_opcodes.as_ref().map(|opcodes| {
+            let mut opcodes = Vec::from_iter(opcodes.iter().copied());
+            opcodes.sort_by_key(OpKind::to_string);
+            opcodes
+        });
+        let config = MachineConfig {
+            register_count: self.register_count,
+            result_register: self.result_register,
+            zeroed_registers: self.zeroed_registers,
+            latencies: self.latencies,
+            flush_policy: self.flush_policy,
+            hazard_policy: self.hazard_policy,
+            store_policy: self.store_policy,
+            eager_fold: self.eager_fold,
+            checked_arithmetic: self.checked_arithmetic,
+            bypass: self.bypass,
+            permitted_opcodes,
+            allow_data_race: self.allow_data_race,
+            detect_write_after_write: self.detect_write_after_write,
+            max_cycles: self.max_cycles,
+        };
+        Scenario::new(program, self.mem.iter(), config)
+    }
+
+    /// Get the index of the next instruction to issue, following jumps
+    pub fn next_instruction(&self) -> usize {
+        self.next_instruction
+    }
+
+    /// Check whether a `halt` was issued, after which no further instructions
+    /// should be issued
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Get the number of cycles executed so far
+    pub fn cycle_count(&self) -> usize {
+        self.pc
+    }
+
+    /// Get the data races that were allowed to continue executing
     ///
     /// # Returns
-    /// * `Ok(reg)` if `reg` is valid
-    /// * `Err(ComputeError::InvalidRegister)` if `reg` is invalid
-    fn validated_register(&self, reg: Reg) -> Result<Reg, ComputeError> {
-        if reg.0 >= REGISTER_COUNT as u32 {
-            return Err(ComputeError::InvalidRegister { reg, pc: self.pc });
+    /// * `&[RaceReport]` - races in the order they occurred, always empty
+    ///   unless `allow_data_race` is set
+    pub fn races(&self) -> &[RaceReport] {
+        &self.races
+    }
+
+    /// Get the operations retired so far in the exact order they were popped
+    /// from the pending operations queue
+    ///
+    /// # Returns
+    /// * `Vec<(usize, usize, String)>` - `(cycle, instruction, output)` of
+    ///   each retired operation, where `instruction` is the instruction that
+    ///   originated the operation
+    pub fn retirement_log(&self) -> Vec<(usize, usize, String)> {
+        self.retirements
+            .iter()
+            .map(|(cycle, instruction, _, output)| (*cycle, *instruction, output.to_string()))
+            .collect()
+    }
+
+    /// Get the number of operations of each kind issued at each cycle
+    ///
+    /// # Returns
+    /// * `&[HashMap<OpKind, usize>]` - issue counts indexed by cycle, covering
+    ///   the cycles instructions were issued at, with no entry for kinds not
+    ///   issued at a cycle
+    pub fn issue_profile(&self) -> &[HashMap<OpKind, usize>] {
+        &self.issues
+    }
+
+    /// Get the operation counters of the cycles executed so far
+    ///
+    /// # Returns
+    /// * `ExecutionStats` - operations issued per kind, and cycles with and
+    ///   without completing operations
+    pub fn stats(&self) -> ExecutionStats {
+        let mut issued = HashMap::new();
+        for (kind, count) in self.issues.iter().flatten() {
+            *issued.entry(*kind).or_default() += count;
         }
-        Ok(reg)
+        let busy_cycles = self
+            .retirements
+            .iter()
+            .map(|(cycle, _, _, _)| cycle)
+            .collect::<HashSet<_>>()
+            .len();
+        ExecutionStats {
+            issued,
+            busy_cycles,
+            idle_cycles: self.pc - busy_cycles,
+        }
+    }
+
+    /// Get the operations retired so far as records for rendering a timeline
+    /// with `timeline::render_timeline()`
+    ///
+    /// # Returns
+    /// * `Vec<OperationRecord>` - retired operations in retire order
+    pub fn operation_records(&self) -> Vec<OperationRecord> {
+        self.retirements
+            .iter()
+            .map(|(cycle, instruction, issued_at, output)| OperationRecord {
+                instruction: *instruction,
+                issued_at: *issued_at,
+                retired_at: *cycle,
+                output: output.to_string(),
+            })
+            .collect()
+    }
+
+    /// Get the pipeline state captured when the last computation failed
+    ///
+    /// # Returns
+    /// * `Some(&ErrorContext)` - operations in flight at the failing cycle
+    /// * `None` - if no computation failed since the machine was created or
+    ///   reset
+    pub fn last_error_context(&self) -> Option<&ErrorContext> {
+        self.error_context.as_ref()
     }
 
     /// Get the value of a register
@@ -125,24 +813,22 @@ impl Machine {
     ///
     /// # Returns
     /// * `Ok(value)` if the register is valid and initialized
-    /// * `Err(ComputeError::InvalidRegister)` if the register is invalid
-    /// * `Err(ComputeError::UninitializedRegister)` if the register is valid
-    ///   but uninitialized
-    fn get_register_value(&self, reg: Reg) -> Result<&ExprWrapper, ComputeError> {
-        self.regs
-            .get(reg.0 as usize)
-            .ok_or(ComputeError::InvalidRegister { reg, pc: self.pc })?
-            .as_ref()
-            .ok_or(ComputeError::UninitializedRegister { reg, pc: self.pc })
-            .map(|v| {
-                trace!(
-                    "Register {} accessed with value `{}` at cycle #{}",
-                    reg,
-                    v,
-                    self.pc
-                );
-                v
-            })
+    /// * `Err(ComputeError)` otherwise, see `get_register_value()`
+    pub fn register(&self, reg: Reg) -> Result<&ExprWrapper, ComputeError> {
+        self.get_register_value(reg)
+    }
+
+    /// Get the instruction whose completed operation last wrote a register
+    ///
+    /// # Arguments
+    /// * `reg` - register to get the last writer of
+    ///
+    /// # Returns
+    /// * `Some(instruction)` if an operation of the instruction at this index
+    ///   in the program wrote or cleared the register
+    /// * `None` if the register was never written since the machine was reset
+    pub fn last_writer(&self, reg: Reg) -> Option<usize> {
+        self.last_writers.get(&reg).map(|&(inst, _, _)| inst)
     }
 
     /// Get the value of a memory address
@@ -151,326 +837,2760 @@ impl Machine {
     /// * `addr` - memory address to get the value of
     ///
     /// # Returns
-    /// * `Ok(value)` if the memory address is valid and initialized
-    /// * `Err(ComputeError::UninitializedMemory)` if the memory address is
-    ///   valid but uninitialized
-    fn get_address_value(&self, addr: &Addr) -> Result<&ExprWrapper, ComputeError> {
-        self.mem
-            .get(addr)
-            .ok_or(ComputeError::UninitializedMemory {
-                addr: *addr,
-                pc: self.pc,
-            })
-            .map(|v| {
-                trace!(
-                    "Memory address {} accessed with value `{}` at cycle #{}",
-                    addr,
-                    v,
-                    self.pc
-                );
-                v
-            })
+    /// * `Ok(value)` if the memory address is initialized
+    /// * `Err(ComputeError::UninitializedMemory)` otherwise
+    pub fn memory(&self, addr: Addr) -> Result<&ExprWrapper, ComputeError> {
+        self.get_address_value(&addr)
     }
 
-    /// Begin execution of an instruction by reading operands from registers or
-    /// memory, and create an `InflightOperation` for each operation
+    /// Iterate over every register in order
     ///
-    /// # Arguments
-    /// * `instruction` - instruction to execute
+    /// # Returns
+    /// * `impl Iterator<Item = (Reg, Option<&ExprWrapper>)>` - each register
+    ///   with its value, or `None` if uninitialized
+    pub fn registers(&self) -> impl Iterator<Item = (Reg, Option<&ExprWrapper>)> {
+        self.regs
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (Reg(i as u32), value.as_ref()))
+    }
+
+    /// Iterate over the initialized memory cells
     ///
     /// # Returns
-    /// * `Ok(())` if the instruction execution was successfully started
-    /// * `Err(ComputeError)` if the instruction execution failed
-    fn begin_execution(&mut self, instruction: &Instruction) -> Result<(), ComputeError> {
-        if let Some((dst, constant)) = instruction.ldi {
-            self.pending_operations.push(InflightOperation::from_ldi(
-                self.pc,
-                self.validated_register(dst)?,
-                constant,
-            ));
-        }
+    /// * `impl Iterator<Item = (Addr, &ExprWrapper)>` - each initialized
+    ///   memory address with its value, in no particular order
+    pub fn memory_cells(&self) -> impl Iterator<Item = (Addr, &ExprWrapper)> {
+        self.mem.iter()
+    }
 
-        if let Some((dst, addr)) = instruction.ldr {
-            self.pending_operations.push(InflightOperation::from_ldr(
-                self.pc,
-                self.validated_register(dst)?,
-                self.get_address_value(&addr)?,
-            ));
-        }
+    /// Render every initialized memory cell sorted by address, e.g. to diff the
+    /// final state of programs
+    ///
+    /// # Returns
+    /// * `Vec<(Addr, String)>` - each initialized memory address with its
+    ///   strongly evaluated value, in ascending address order
+    pub fn dump_memory(&self) -> Vec<(Addr, String)> {
+        let mut cells: Vec<_> = self
+            .memory_cells()
+            .map(|(addr, value)| (addr, value.strong_eval()))
+            .collect();
+        cells.sort_by_key(|(addr, _)| addr.0);
+        cells
+    }
 
-        if let Some((src, addr)) = instruction.str {
-            self.pending_operations.push(InflightOperation::from_str(
-                self.pc,
-                self.get_register_value(src)?,
-                addr,
-            ));
+    /// Render every register in order
+    ///
+    /// # Returns
+    /// * `Vec<(Reg, Option<String>)>` - each register with its strongly
+    ///   evaluated value, or `None` if uninitialized
+    pub fn dump_registers(&self) -> Vec<(Reg, Option<String>)> {
+        self.registers()
+            .map(|(reg, value)| (reg, value.map(ExprWrapper::strong_eval)))
+            .collect()
+    }
+
+    /// Find registers holding structurally identical expression trees
+    ///
+    /// # Returns
+    /// * `Vec<(Reg, Reg)>` - pairs of initialized registers with structurally
+    ///   equal values, ordered by register
+    pub fn duplicate_registers(&self) -> Vec<(Reg, Reg)> {
+        let mut duplicates = Vec::new();
+        for (i, lhs) in self.regs.iter().enumerate() {
+            for (j, rhs) in self.regs.iter().enumerate().skip(i + 1) {
+                if lhs.is_some() && lhs == rhs {
+                    duplicates.push((Reg(i as u32), Reg(j as u32)));
+                }
+            }
         }
+        duplicates
+    }
 
-        if let Some((dst, src1, src2)) = instruction.add {
-            self.pending_operations.push(InflightOperation::from_add(
-                self.pc,
-                self.validated_register(dst)?,
-                self.get_register_value(src1)?,
-                self.get_register_value(src2)?,
-            ));
+    /// Export register values as startup memory for another machine, so the
+    /// outputs of one stage can become the inputs of the next
+    ///
+    /// # Arguments
+    /// * `mapping` - `(register, address)` pairs to copy register values to
+    ///
+    /// # Returns
+    /// * `HashMap<Addr, ExprWrapper>` - memory to pass to `Machine::new()`,
+    ///   with no entry for invalid or uninitialized registers
+    pub fn export_registers_to_memory(
+        &self,
+        mapping: &[(Reg, Addr)],
+    ) -> HashMap<Addr, ExprWrapper> {
+        mapping
+            .iter()
+            .filter_map(|(reg, addr)| {
+                let value = self.regs.get(reg.0 as usize)?.as_ref()?;
+                Some((*addr, value.clone()))
+            })
+            .collect()
+    }
+
+    /// Compute the result of a program
+    ///
+    /// # Arguments
+    /// * `program` - a vector of `Instruction`s to compute
+    ///
+    /// # Returns
+    /// * `Ok(value)` of the result register, `Reg(0)` unless set by
+    ///   `with_result_register()`, if the program terminated successfully
+    /// * `Err(ComputeError)` if the program terminated with an error
+    pub fn compute(&mut self, program: &[Instruction]) -> Result<&ExprWrapper, ComputeError> {
+        if self.pc != 0 {
+            return Err(ComputeError::Terminated);
         }
 
-        if let Some((dst, src1, src2)) = instruction.sub {
-            self.pending_operations.push(InflightOperation::from_sub(
-                self.pc,
-                self.validated_register(dst)?,
-                self.get_register_value(src1)?,
-                self.get_register_value(src2)?,
-            ));
+        while let Some(instruction) = self.fetch(program) {
+            self.step(Some(instruction))?;
         }
 
-        if let Some((dst, src1, src2)) = instruction.mul {
-            self.pending_operations.push(InflightOperation::from_mul(
-                self.pc,
-                self.validated_register(dst)?,
-                self.get_register_value(src1)?,
-                self.get_register_value(src2)?,
-            ));
+        while !self.is_drained() {
+            self.step(None)?;
         }
 
-        Ok(())
+        debug!("All instructions executed");
+
+        self.get_register_value(self.result_register)
     }
 
-    /// End a cycle by writing the output of all completed operations to
-    /// registers or memory
+    /// Compute the result of a program and the number of cycles it took
+    ///
+    /// # Arguments
+    /// * `program` - a vector of `Instruction`s to compute
     ///
     /// # Returns
-    /// * `Ok(())` if the cycle was successfully ended
-    /// * `Err(ComputeError::RegisterDataRace)` if two operations wrote to the
-    ///   same register and `allow_data_race` is `false`
-    /// * `Err(ComputeError::MemoryDataRace)` if two operations wrote to the
-    ///   same memory address and `allow_data_race` is `false`
+    /// * `Ok(ComputeSummary)` if the program terminated successfully
+    /// * `Err(ComputeError)` if the program terminated with an error
+    pub fn compute_with_summary(
+        &mut self,
+        program: &[Instruction],
+    ) -> Result<ComputeSummary<'_>, ComputeError> {
+        self.compute(program)?;
+        Ok(ComputeSummary {
+            value: self.get_register_value(self.result_register)?,
+            cycles: self.pc,
+            result_writer: self.last_writer(self.result_register),
+        })
+    }
+
+    /// Compute a program one cycle at a time, yielding what was issued and
+    /// completed at each cycle, e.g. to drive a timeline visualization
     ///
-    /// # Panics
-    /// * If the `complete_by` of an `InflightOperation` is less than or equal
-    ///   to the program counter `pc`
-    fn end_cycle(&mut self) -> Result<(), ComputeError> {
-        let mut prev: Option<InflightOperation> = None;
-        while let Some(next) = self.pending_operations.peek() {
-            let complete_by = next.get_complete_by();
-            assert!(complete_by > self.pc);
+    /// # Arguments
+    /// * `program` - instructions to compute
+    ///
+    /// # Returns
+    /// * `impl Iterator<Item = Result<CycleEvent, ComputeError>>` - one
+    ///   `Ok(CycleEvent)` per cycle run until the program terminates, with
+    ///   the error as the final item if the computation failed
+    pub fn run_events<'a>(
+        &'a mut self,
+        program: &'a [Instruction],
+    ) -> impl Iterator<Item = Result<CycleEvent, ComputeError>> + 'a {
+        let mut error = (self.pc != 0).then_some(ComputeError::Terminated);
+        let mut finished = false;
+        std::iter::from_fn(move || {
+            if let Some(e) = error.take() {
+                finished = true;
+                return Some(Err(e));
+            }
+            if finished {
+                return None;
+            }
+            let cycle = self.pc;
+            let next_instruction = self.next_instruction;
+            let instruction = self.fetch(program);
+            if instruction.is_none() && self.is_drained() {
+                return None;
+            }
+            match self.step(instruction) {
+                Ok(outcome) => Some(Ok(CycleEvent {
+                    cycle,
+                    issued: instruction
+                        .filter(|_| outcome != StepOutcome::Stalled)
+                        .map(|_| next_instruction),
+                    completed: self
+                        .completions
+                        .iter()
+                        .map(|(location, value)| {
+                            (*location, value.as_ref().map(ExprWrapper::to_string))
+                        })
+                        .collect(),
+                })),
+                Err(e) => {
+                    finished = true;
+                    Some(Err(e))
+                }
+            }
+        })
+    }
 
-            if complete_by > self.pc + 1 {
-                break;
+    /// Run a single cycle, issuing an instruction if one is given, so a
+    /// program can be executed one cycle at a time
+    ///
+    /// # Note
+    /// Keep stepping without an instruction after the program is exhausted
+    /// until the machine is idle to drain pending operations. Programs with
+    /// jumps should issue the instruction at `next_instruction()`, and stop
+    /// issuing instructions once `is_halted()`.
+    ///
+    /// # Arguments
+    /// * `instruction` - instruction to issue this cycle, or `None` to only
+    ///   retire pending operations
+    ///
+    /// # Returns
+    /// * `Ok(StepOutcome)` if the cycle ran successfully
+    /// * `Err(ComputeError)` if the cycle failed, see `last_error_context()`
+    pub fn step(&mut self, instruction: Option<&Instruction>) -> Result<StepOutcome, ComputeError> {
+        let result = match instruction {
+            Some(instruction) => self.issue(instruction),
+            None => self.end_cycle().map(|_| true),
+        };
+        match result {
+            Ok(false) => Ok(StepOutcome::Stalled),
+            Ok(true) if self.pending_operations.is_empty() => Ok(StepOutcome::Idle),
+            Ok(true) => Ok(StepOutcome::Busy),
+            Err(e) => {
+                self.capture_error_context();
+                Err(e)
             }
+        }
+    }
 
-            let next = self.pending_operations.pop().unwrap();
-            let output = next.get_output();
-            debug!(
-                "Operation originated by instruction #{} completed at cycle #{}: {}",
-                next.get_instruction(),
-                self.pc,
-                output
-            );
+    /// Capture the operations retired in the current cycle and those still
+    /// pending as the context of a failed computation
+    fn capture_error_context(&mut self) {
+        let retired = self
+            .retirements
+            .iter()
+            .filter(|(cycle, _, _, _)| *cycle == self.pc)
+            .map(|(cycle, instruction, issued_at, output)| InFlightRecord {
+                instruction: *instruction,
+                issued_at: *issued_at,
+                complete_by: cycle + 1,
+                output: output.to_string(),
+            });
+        let mut pending: Vec<_> = self.pending_operations.iter().collect();
+        pending.sort_by(|lhs, rhs| rhs.cmp(lhs));
+        let pending = pending.into_iter().map(|op| InFlightRecord {
+            instruction: op.get_instruction(),
+            issued_at: op.get_issued_at(),
+            complete_by: op.get_complete_by(),
+            output: op.get_output().to_string(),
+        });
+        self.error_context = Some(ErrorContext {
+            pc: self.pc,
+            in_flight: retired.chain(pending).collect(),
+        });
+    }
 
-            if prev.as_ref().map(|op| op.get_output()) == Some(output) {
-                let err = match output {
-                    OperationOutput::WriteToRegister(reg, _) => ComputeError::RegisterDataRace {
-                        reg: *reg,
-                        pc: self.pc,
-                        inst1: prev.unwrap().get_instruction(),
-                        inst2: next.get_instruction(),
-                    },
-                    OperationOutput::WriteToMemory(addr, _) => ComputeError::MemoryDataRace {
-                        addr: *addr,
-                        pc: self.pc,
-                        inst1: prev.unwrap().get_instruction(),
-                        inst2: next.get_instruction(),
-                    },
-                };
+    /// Compute a program cooperatively, running at most `budget` cycles per
+    /// call so long-running programs can be interleaved with other work
+    ///
+    /// # Note
+    /// Call repeatedly with the same program until it returns
+    /// `ComputeProgress::Done`, then reset the machine before running another
+    /// program.
+    ///
+    /// # Arguments
+    /// * `program` - instructions to compute
+    /// * `budget` - maximum number of cycles to run in this call
+    ///
+    /// # Returns
+    /// * `ComputeProgress::Pending` if the program has not finished yet
+    /// * `ComputeProgress::Done(result)` if the program terminated, with the
+    ///   same result `compute()` would return
+    pub fn compute_yielding(&mut self, program: &[Instruction], budget: usize) -> ComputeProgress {
+        for _ in 0..budget {
+            let result = match self.fetch(program) {
+                Some(instruction) => self.step(Some(instruction)),
+                None if !self.is_drained() => self.step(None),
+                None => break,
+            };
+            if let Err(e) = result {
+                return ComputeProgress::Done(Err(e));
+            }
+        }
 
-                if !self.allow_data_race {
-                    return Err(err);
+        if self.fetch(program).is_some() || !self.is_drained() {
+            trace!("Yielding at cycle #{}", self.pc);
+            return ComputeProgress::Pending;
+        }
+        ComputeProgress::Done(self.get_register_value(self.result_register).cloned())
+    }
+
+    /// Run one cycle issuing an instruction, or stalling if the hazard policy
+    /// defers it
+    ///
+    /// # Arguments
+    /// * `instruction` - instruction to issue
+    ///
+    /// # Returns
+    /// * `Ok(true)` if the instruction was issued
+    /// * `Ok(false)` if the cycle stalled without issuing the instruction
+    /// * `Err(ComputeError)` if the cycle failed
+    fn issue(&mut self, instruction: &Instruction) -> Result<bool, ComputeError> {
+        if let Some((reg, pending_inst)) = self.read_hazard(instruction) {
+            match self.hazard_policy {
+                HazardPolicy::ReadStale => {}
+                HazardPolicy::Stall => {
+                    debug!(
+                        "Stalling instruction #{} on register {} at cycle #{}",
+                        self.next_instruction, reg, self.pc
+                    );
+                    self.issues.push(HashMap::new());
+                    self.end_cycle()?;
+                    return Ok(false);
+                }
+                HazardPolicy::Error => {
+                    return Err(ComputeError::ReadHazard {
+                        reg,
+                        pc: self.pc,
+                        pending_inst,
+                    })
                 }
-                warn!("{err}");
             }
+        }
 
-            match output {
-                OperationOutput::WriteToRegister(reg, value) => {
-                    self.regs[reg.0 as usize] = Some(value.clone());
+        debug!(
+            "Executing instruction #{}: {}",
+            self.next_instruction, instruction
+        );
+        self.begin_execution(instruction)?;
+        let jump = if instruction.halt {
+            None
+        } else {
+            self.jump_target(instruction)?
+        };
+        if jump.is_some() && self.pc >= self.max_cycles {
+            return Err(ComputeError::JumpLimitExceeded {
+                max: self.max_cycles,
+            });
+        }
+        self.end_cycle()?;
+        if instruction.halt {
+            debug!("Halting at instruction #{}", self.next_instruction);
+            self.halted = true;
+        }
+        self.next_instruction = match jump {
+            Some(target) => {
+                debug!("Jumping to instruction #{}", target);
+                target
+            }
+            None => self.next_instruction + 1,
+        };
+        Ok(true)
+    }
+
+    /// Get the next instruction of a program to issue
+    ///
+    /// # Arguments
+    /// * `program` - program being computed
+    ///
+    /// # Returns
+    /// * `Some(instruction)` - instruction at `next_instruction()`
+    /// * `None` - if the program is exhausted or a `halt` was issued
+    fn fetch<'a>(&self, program: &'a [Instruction]) -> Option<&'a Instruction> {
+        if self.halted {
+            return None;
+        }
+        program.get(self.next_instruction)
+    }
+
+    /// Get the instruction an instruction jumps to
+    ///
+    /// # Arguments
+    /// * `instruction` - instruction being issued
+    ///
+    /// # Returns
+    /// * `Ok(Some(target))` if the instruction has a `jmp` or a `jz` whose
+    ///   condition is numeric zero
+    /// * `Ok(None)` if execution falls through to the next instruction
+    /// * `Err(ComputeError::BranchOnSymbolic)` if the condition of a `jz` does
+    ///   not strongly evaluate to a numeric constant
+    fn jump_target(&self, instruction: &Instruction) -> Result<Option<usize>, ComputeError> {
+        if let Some((reg, target)) = instruction.jz {
+            let cond = self
+                .read_register(reg)?
+                .as_const()
+                .ok_or(ComputeError::BranchOnSymbolic { reg, pc: self.pc })?;
+            return Ok((cond == 0).then_some(target));
+        }
+        Ok(instruction.jmp)
+    }
+
+    /// Find a register operand of an instruction with a pending write that
+    /// is not forwarded by the bypass network
+    ///
+    /// # Arguments
+    /// * `instruction` - instruction to check
+    ///
+    /// # Returns
+    /// * `Some((reg, pending_inst))` - first register operand with a
+    ///   read-after-write hazard and the latest instruction with a pending
+    ///   write to it, always `None` with `HazardPolicy::ReadStale`
+    fn read_hazard(&self, instruction: &Instruction) -> Option<(Reg, usize)> {
+        if self.hazard_policy == HazardPolicy::ReadStale {
+            return None;
+        }
+        instruction
+            .reads()
+            .into_iter()
+            .find_map(|location| match location {
+                Location::Register(reg) => self
+                    .pending_operations
+                    .iter()
+                    .filter(|op| !(self.bypass && op.get_complete_by() == self.pc + 1))
+                    .filter(|op| {
+                        matches!(
+                            op.get_output(),
+                            OperationOutput::WriteToRegister(dst, _)
+                                | OperationOutput::ClearRegister(dst) if *dst == reg
+                        )
+                    })
+                    .max_by_key(|op| op.get_issued_at())
+                    .map(|op| (reg, op.get_instruction())),
+                Location::Memory(_) => None,
+            })
+    }
+
+    /// Check whether draining pending operations is finished according to the
+    /// flush policy
+    fn is_drained(&self) -> bool {
+        if self.pending_operations.is_empty() {
+            return true;
+        }
+        if self.flush_policy == FlushPolicy::UntilResultReady
+            && !self.has_pending_write(self.result_register)
+        {
+            debug!("Result ready, leaving remaining operations in flight");
+            return true;
+        }
+        false
+    }
+
+    /// Get the numeric value of the result register without rendering it
+    ///
+    /// # Returns
+    /// * `Ok(Some(value))` if the result strongly evaluates to a numeric
+    ///   constant
+    /// * `Ok(None)` if the result contains symbolic variables
+    /// * `Err(ComputeError)` if the result register is invalid or uninitialized
+    pub fn result_u32(&self) -> Result<Option<u32>, ComputeError> {
+        self.get_register_value(self.result_register)
+            .map(ExprWrapper::as_const)
+    }
+
+    /// Compare the final register and memory values against an expected state
+    ///
+    /// # Arguments
+    /// * `spec` - expected strongly evaluated values
+    ///
+    /// # Returns
+    /// * `Vec<Mismatch>` - locations whose strongly evaluated value differs
+    ///   from the expected one, in the order of `spec`, empty if all match
+    pub fn verify_against(&self, spec: &ExpectedState) -> Vec<Mismatch> {
+        spec.values
+            .iter()
+            .filter_map(|(location, expected)| {
+                let actual = match location {
+                    Location::Register(reg) => {
+                        self.regs.get(reg.0 as usize).and_then(Option::as_ref)
+                    }
+                    Location::Memory(addr) => self.mem.get(addr),
+                }
+                .map(ExprWrapper::strong_eval);
+                (actual.as_ref() != Some(expected)).then(|| Mismatch {
+                    location: *location,
+                    expected: expected.clone(),
+                    actual,
+                })
+            })
+            .collect()
+    }
+
+    /// Check whether any pending operation writes to a register
+    ///
+    /// # Arguments
+    /// * `reg` - register to check
+    fn has_pending_write(&self, reg: Reg) -> bool {
+        self.pending_operations.iter().any(|operation| {
+            matches!(
+                operation.get_output(),
+                OperationOutput::WriteToRegister(dst, _) | OperationOutput::ClearRegister(dst)
+                    if *dst == reg
+            )
+        })
+    }
+
+    /// Validate a register and return it if valid
+    ///
+    /// # Arguments
+    /// * `reg` - register to validate
+    ///
+    /// # Returns
+    /// * `Ok(reg)` if `reg` is valid
+    /// * `Err(ComputeError::InvalidRegister)` if `reg` is invalid
+    fn validated_register(&self, reg: Reg) -> Result<Reg, ComputeError> {
+        if reg.0 as usize >= self.register_count {
+            return Err(ComputeError::InvalidRegister { reg, pc: self.pc });
+        }
+        Ok(reg)
+    }
+
+    /// Get the value of a register
+    ///
+    /// # Arguments
+    /// * `reg` - register to get the value of
+    ///
+    /// # Returns
+    /// * `Ok(value)` if the register is valid and initialized
+    /// * `Err(ComputeError::InvalidRegister)` if the register is invalid
+    /// * `Err(ComputeError::UninitializedRegister)` if the register is valid
+    ///   but uninitialized
+    fn get_register_value(&self, reg: Reg) -> Result<&ExprWrapper, ComputeError> {
+        self.regs
+            .get(reg.0 as usize)
+            .ok_or(ComputeError::InvalidRegister { reg, pc: self.pc })?
+            .as_ref()
+            .ok_or(ComputeError::UninitializedRegister { reg, pc: self.pc })
+            .inspect(|v| {
+                trace!(
+                    "Register {} accessed with value `{}` at cycle #{}",
+                    reg,
+                    v,
+                    self.pc
+                );
+            })
+    }
+
+    /// Read a register operand, forwarding the result of an operation
+    /// completing at the end of this cycle if `bypass` is set
+    ///
+    /// # Arguments
+    /// * `reg` - register to read
+    ///
+    /// # Returns
+    /// * `Ok(value)` if the register is valid and initialized, or its value is
+    ///   forwarded
+    /// * `Err(ComputeError)` otherwise, see `get_register_value()`
+    fn read_register(&self, reg: Reg) -> Result<&ExprWrapper, ComputeError> {
+        if self.bypass {
+            let forwarded = self
+                .pending_operations
+                .iter()
+                .filter(|op| op.get_complete_by() == self.pc + 1 && op.get_issued_at() < self.pc)
+                .map(InflightOperation::get_output)
+                .find(|output| match output {
+                    OperationOutput::WriteToRegister(dst, _)
+                    | OperationOutput::ClearRegister(dst) => *dst == reg,
+                    OperationOutput::WriteToMemory(_, _) => false,
+                });
+            match forwarded {
+                Some(OperationOutput::WriteToRegister(_, value)) => {
                     trace!(
-                        "Register {} written with value `{}` at cycle #{}",
+                        "Register {} forwarded with value `{}` at cycle #{}",
                         reg,
                         value,
                         self.pc
-                    )
+                    );
+                    return Ok(value);
                 }
-                OperationOutput::WriteToMemory(addr, value) => {
-                    self.mem.insert(*addr, value.clone());
+                Some(OperationOutput::ClearRegister(_)) => {
+                    return Err(ComputeError::UninitializedRegister { reg, pc: self.pc })
+                }
+                _ => {}
+            }
+        }
+        self.get_register_value(reg)
+    }
+
+    /// Get the value of a memory address
+    ///
+    /// # Arguments
+    /// * `addr` - memory address to get the value of
+    ///
+    /// # Returns
+    /// * `Ok(value)` if the memory address is valid and initialized
+    /// * `Err(ComputeError::UninitializedMemory)` if the memory address is
+    ///   valid but uninitialized
+    fn get_address_value(&self, addr: &Addr) -> Result<&ExprWrapper, ComputeError> {
+        self.mem
+            .get(addr)
+            .ok_or(ComputeError::UninitializedMemory {
+                addr: *addr,
+                pc: self.pc,
+            })
+            .inspect(|v| {
+                trace!(
+                    "Memory address {} accessed with value `{}` at cycle #{}",
+                    addr,
+                    v,
+                    self.pc
+                );
+            })
+    }
+
+    /// Load a memory address operand according to the store policy
+    ///
+    /// # Arguments
+    /// * `addr` - memory address to load
+    ///
+    /// # Returns
+    /// * `Ok(value)` if the memory address is initialized, or the value of a
+    ///   pending store is forwarded
+    /// * `Err(ComputeError::MemoryReadBeforeWrite)` if the memory address has a
+    ///   pending store and the store policy is `StorePolicy::Error`
+    /// * `Err(ComputeError)` otherwise, see `get_address_value()`
+    fn load_address(&self, addr: &Addr) -> Result<&ExprWrapper, ComputeError> {
+        if self.store_policy != StorePolicy::ReadStale {
+            let pending = self
+                .pending_operations
+                .iter()
+                .filter_map(|op| match op.get_output() {
+                    OperationOutput::WriteToMemory(dst, value) if dst == addr => {
+                        Some((op.get_issued_at(), value))
+                    }
+                    _ => None,
+                })
+                .max_by_key(|(issued_at, _)| *issued_at);
+            match (pending, self.store_policy) {
+                (Some(_), StorePolicy::Error) => {
+                    return Err(ComputeError::MemoryReadBeforeWrite {
+                        addr: *addr,
+                        pc: self.pc,
+                    })
+                }
+                (Some((_, value)), _) => {
                     trace!(
-                        "Memory address {} written with value `{}` at cycle #{}",
+                        "Memory address {} forwarded with value `{}` at cycle #{}",
                         addr,
                         value,
                         self.pc
-                    )
+                    );
+                    return Ok(value);
                 }
+                (None, _) => {}
             }
+        }
+        self.get_address_value(addr)
+    }
 
-            prev = Some(next);
+    /// Begin execution of an instruction by reading operands from registers or
+    /// memory, and create an `InflightOperation` for each operation
+    ///
+    /// # Arguments
+    /// * `instruction` - instruction to execute
+    ///
+    /// # Returns
+    /// * `Ok(())` if the instruction execution was successfully started
+    /// * `Err(ComputeError)` if the instruction execution failed
+    fn begin_execution(&mut self, instruction: &Instruction) -> Result<(), ComputeError> {
+        if let Some(permitted) = &self.permitted_opcodes {
+            if let Some(op) = instruction
+                .op_kinds()
+                .into_iter()
+                .find(|op| !permitted.contains(op))
+            {
+                return Err(ComputeError::OpcodeNotPermitted { op, pc: self.pc });
+            }
         }
 
-        debug!("Cycle #{} completed", self.pc);
-        self.pc += 1;
+        instruction
+            .validate()
+            .map_err(|error| ComputeError::InvalidInstruction { error, pc: self.pc })?;
+
+        let mut issued = HashMap::new();
+        for op in instruction.op_kinds() {
+            *issued.entry(op).or_default() += 1;
+        }
+        self.issues.push(issued);
+
+        if let Some((dst, constant)) = instruction.ldi {
+            self.start_operation(InflightOperation::from_ldi(
+                self.pc,
+                self.latencies.ldi,
+                self.validated_register(dst)?,
+                constant,
+            ));
+        }
+
+        if let Some((dst, addr)) = instruction.ldr {
+            self.start_operation(InflightOperation::from_ldr(
+                self.pc,
+                self.latencies.ldr,
+                self.validated_register(dst)?,
+                self.load_address(&addr)?,
+            ));
+        }
+
+        for &(src, addr) in &instruction.str {
+            self.start_operation(InflightOperation::from_str(
+                self.pc,
+                self.latencies.str,
+                self.read_register(src)?,
+                addr,
+            ));
+        }
+
+        if let Some((addr, constant)) = instruction.sti {
+            self.start_operation(InflightOperation::from_sti(
+                self.pc,
+                self.latencies.str,
+                addr,
+                constant,
+            ));
+        }
+
+        if let Some((dst, src1, src2)) = instruction.add {
+            let operation = InflightOperation::from_add(
+                self.pc,
+                self.latencies.add,
+                self.validated_register(dst)?,
+                self.read_register(src1)?,
+                self.read_register(src2)?,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src1, src2)) = instruction.sub {
+            let operation = InflightOperation::from_sub(
+                self.pc,
+                self.latencies.sub,
+                self.validated_register(dst)?,
+                self.read_register(src1)?,
+                self.read_register(src2)?,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src1, src2)) = instruction.mul {
+            let operation = InflightOperation::from_mul(
+                self.pc,
+                self.latencies.mul,
+                self.validated_register(dst)?,
+                self.read_register(src1)?,
+                self.read_register(src2)?,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src1, src2)) = instruction.div {
+            let divisor = self.read_register(src2)?;
+            if divisor.as_const() == Some(0) {
+                return Err(ComputeError::DivisionByZero { pc: self.pc });
+            }
+            let operation = InflightOperation::from_div(
+                self.pc,
+                self.latencies.div,
+                self.validated_register(dst)?,
+                self.read_register(src1)?,
+                divisor,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src1, src2)) = instruction.pow {
+            let operation = InflightOperation::from_pow(
+                self.pc,
+                self.latencies.pow,
+                self.validated_register(dst)?,
+                self.read_register(src1)?,
+                self.read_register(src2)?,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src1, src2)) = instruction.min {
+            let (src1_value, src2_value) = self.comparison_operands(src1, src2)?;
+            let operation = InflightOperation::from_min(
+                self.pc,
+                self.latencies.min,
+                self.validated_register(dst)?,
+                src1_value,
+                src2_value,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src1, src2)) = instruction.max {
+            let (src1_value, src2_value) = self.comparison_operands(src1, src2)?;
+            let operation = InflightOperation::from_max(
+                self.pc,
+                self.latencies.max,
+                self.validated_register(dst)?,
+                src1_value,
+                src2_value,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src1, src2)) = instruction.and {
+            let operation = InflightOperation::from_and(
+                self.pc,
+                self.latencies.and,
+                self.validated_register(dst)?,
+                self.read_register(src1)?,
+                self.read_register(src2)?,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src1, src2)) = instruction.or {
+            let operation = InflightOperation::from_or(
+                self.pc,
+                self.latencies.or,
+                self.validated_register(dst)?,
+                self.read_register(src1)?,
+                self.read_register(src2)?,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src1, src2)) = instruction.xor {
+            let operation = InflightOperation::from_xor(
+                self.pc,
+                self.latencies.xor,
+                self.validated_register(dst)?,
+                self.read_register(src1)?,
+                self.read_register(src2)?,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src1, src2)) = instruction.shl {
+            let operation = InflightOperation::from_shl(
+                self.pc,
+                self.latencies.shl,
+                self.validated_register(dst)?,
+                self.read_register(src1)?,
+                self.read_register(src2)?,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src1, src2)) = instruction.shr {
+            let operation = InflightOperation::from_shr(
+                self.pc,
+                self.latencies.shr,
+                self.validated_register(dst)?,
+                self.read_register(src1)?,
+                self.read_register(src2)?,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src)) = instruction.neg {
+            let operation = InflightOperation::from_neg(
+                self.pc,
+                self.latencies.neg,
+                self.validated_register(dst)?,
+                self.read_register(src)?,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src)) = instruction.sqr {
+            let operation = InflightOperation::from_sqr(
+                self.pc,
+                self.latencies.mul,
+                self.validated_register(dst)?,
+                self.read_register(src)?,
+            );
+            self.issue_arithmetic(operation)?;
+        }
+
+        if let Some((dst, src, cond)) = instruction.cmovz {
+            let cond_value =
+                self.read_register(cond)?
+                    .as_const()
+                    .ok_or(ComputeError::NonNumericCondition {
+                        reg: cond,
+                        pc: self.pc,
+                    })?;
+            if let Some(operation) = InflightOperation::from_cmovz(
+                self.pc,
+                self.latencies.cmovz,
+                self.validated_register(dst)?,
+                self.read_register(src)?,
+                cond_value,
+            ) {
+                self.start_operation(operation);
+            }
+        }
+
+        if let Some((dst, src)) = instruction.mov {
+            self.start_operation(InflightOperation::from_mov(
+                self.pc,
+                self.latencies.mov,
+                self.validated_register(dst)?,
+                self.read_register(src)?,
+            ));
+        }
+
+        if let Some(reg) = instruction.clr {
+            self.start_operation(InflightOperation::from_clr(
+                self.pc,
+                self.latencies.clr,
+                self.validated_register(reg)?,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Add an operation of the instruction being issued to the pending
+    /// operations
+    ///
+    /// # Arguments
+    /// * `operation` - operation to issue
+    fn start_operation(&mut self, operation: InflightOperation) {
+        self.pending_operations
+            .push(operation.with_instruction(self.next_instruction));
+    }
+
+    /// Add an arithmetic operation to the pending operations, folding it first
+    /// if `eager_fold` is set
+    ///
+    /// # Arguments
+    /// * `operation` - arithmetic operation to issue
+    ///
+    /// # Returns
+    /// * `Ok(())` if the operation was issued
+    /// * `Err(ComputeError::ArithmeticOverflow)` if the operation overflows
+    ///   and `checked_arithmetic` is set
+    fn issue_arithmetic(&mut self, operation: InflightOperation) -> Result<(), ComputeError> {
+        if let OperationOutput::WriteToRegister(_, value) = operation.get_output() {
+            if self.checked_arithmetic && value.overflows() {
+                return Err(ComputeError::ArithmeticOverflow { pc: self.pc });
+            }
+        }
+        self.start_operation(if self.eager_fold {
+            operation.folded()
+        } else {
+            operation
+        });
+        Ok(())
+    }
+
+    /// Read the numeric values of the operands of a `min` or `max`
+    ///
+    /// # Arguments
+    /// * `src1` - source register 1
+    /// * `src2` - source register 2
+    ///
+    /// # Returns
+    /// * `Ok((src1_value, src2_value))` if both operands strongly evaluate to
+    ///   numeric constants
+    /// * `Err(ComputeError::NonNumericComparison)` if either operand is
+    ///   symbolic
+    /// * `Err(ComputeError)` otherwise, see `read_register()`
+    fn comparison_operands(&self, src1: Reg, src2: Reg) -> Result<(u32, u32), ComputeError> {
+        let src1_value = self.read_register(src1)?.as_const();
+        let src2_value = self.read_register(src2)?.as_const();
+        src1_value
+            .zip(src2_value)
+            .ok_or(ComputeError::NonNumericComparison { pc: self.pc })
+    }
+
+    /// End a cycle by writing the output of all completed operations to
+    /// registers or memory
+    ///
+    /// # Returns
+    /// * `Ok(())` if the cycle was successfully ended
+    /// * `Err(ComputeError::RegisterDataRace)` if two operations wrote to the
+    ///   same register and `allow_data_race` is `false`
+    /// * `Err(ComputeError::MemoryDataRace)` if two operations wrote to the
+    ///   same memory address and `allow_data_race` is `false`
+    /// * `Err(ComputeError::WriteAfterWrite)` if an operation overwrote a
+    ///   register written in an earlier cycle by an operation of a later
+    ///   instruction and `detect_write_after_write` is set
+    /// * `Err(ComputeError::CycleLimitExceeded)` if the cycle limit was reached
+    ///   with operations still pending
+    ///
+    /// # Panics
+    /// * If the `complete_by` of an `InflightOperation` is less than or equal
+    ///   to the program counter `pc`
+    fn end_cycle(&mut self) -> Result<(), ComputeError> {
+        if self.pc >= self.max_cycles && !self.pending_operations.is_empty() {
+            return Err(ComputeError::CycleLimitExceeded {
+                max: self.max_cycles,
+            });
+        }
+
+        self.completions.clear();
+        let mut prev: Option<InflightOperation> = None;
+        while let Some(next) = self.pending_operations.peek() {
+            let complete_by = next.get_complete_by();
+            assert!(complete_by > self.pc);
+
+            if complete_by > self.pc + 1 {
+                break;
+            }
+
+            let next = self.pending_operations.pop().unwrap();
+            let output = next.get_output();
+            debug!(
+                "Operation originated by instruction #{} completed at cycle #{}: {}",
+                next.get_instruction(),
+                self.pc,
+                output
+            );
+            self.retirements.push((
+                self.pc,
+                next.get_instruction(),
+                next.get_issued_at(),
+                output.clone(),
+            ));
+            self.completions.push(match output {
+                OperationOutput::WriteToRegister(reg, value) => {
+                    (Location::Register(*reg), Some(value.clone()))
+                }
+                OperationOutput::WriteToMemory(addr, value) => {
+                    (Location::Memory(*addr), Some(value.clone()))
+                }
+                OperationOutput::ClearRegister(reg) => (Location::Register(*reg), None),
+            });
+
+            if prev.as_ref().map(|op| op.get_output()) == Some(output) {
+                let report = RaceReport {
+                    resource: match output {
+                        OperationOutput::WriteToRegister(reg, _)
+                        | OperationOutput::ClearRegister(reg) => RaceResource::Register(*reg),
+                        OperationOutput::WriteToMemory(addr, _) => RaceResource::Memory(*addr),
+                    },
+                    pc: self.pc,
+                    inst1: prev.unwrap().get_instruction(),
+                    inst2: next.get_instruction(),
+                };
+
+                if !self.allow_data_race {
+                    return Err(report.into());
+                }
+                warn!("{}", ComputeError::from(report));
+                self.races.push(report);
+            }
+
+            if let OperationOutput::WriteToRegister(reg, _) | OperationOutput::ClearRegister(reg) =
+                output
+            {
+                if let Some(&(later_inst, later_issued_at, cycle)) = self.last_writers.get(reg) {
+                    if later_issued_at > next.get_issued_at() && cycle < self.pc {
+                        let error = ComputeError::WriteAfterWrite {
+                            reg: *reg,
+                            pc: self.pc,
+                            earlier_inst: next.get_instruction(),
+                            later_inst,
+                        };
+                        if self.detect_write_after_write {
+                            return Err(error);
+                        }
+                        debug!("{}", error);
+                    }
+                }
+                self.last_writers.insert(
+                    *reg,
+                    (next.get_instruction(), next.get_issued_at(), self.pc),
+                );
+            }
+
+            match output {
+                OperationOutput::WriteToRegister(reg, value) => {
+                    self.regs[reg.0 as usize] = Some(value.clone());
+                    trace!(
+                        "Register {} written with value `{}` at cycle #{}",
+                        reg,
+                        value,
+                        self.pc
+                    )
+                }
+                OperationOutput::WriteToMemory(addr, value) => {
+                    self.mem.insert(*addr, value.clone());
+                    trace!(
+                        "Memory address {} written with value `{}` at cycle #{}",
+                        addr,
+                        value,
+                        self.pc
+                    )
+                }
+                OperationOutput::ClearRegister(reg) => {
+                    self.regs[reg.0 as usize] = None;
+                    trace!("Register {} cleared at cycle #{}", reg, self.pc)
+                }
+            }
+
+            prev = Some(next);
+        }
+
+        debug!("Cycle #{} completed", self.pc);
+        self.pc += 1;
         Ok(())
     }
-}
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use crate::pem::{
+        types::{Const, Reg},
+        Latencies, MemoryBuilder,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_str() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_str(Reg(0), Addr(0)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.weak_eval(), "1".to_string());
+        assert_eq!(expr.strong_eval(), "1".to_string());
+        machine
+            .memory(Addr(0))
+            .map(|v| {
+                assert_eq!(v.weak_eval(), "1".to_string());
+            })
+            .expect("Memory address 0 should be initialized");
+        assert_eq!(machine.cycle_count(), 6);
+    }
+
+    #[test]
+    fn test_state_accessors() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(1),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]))
+        .with_result_register(Reg(2));
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(2), Const(7)),
+            Instruction::new().with_str(Reg(2), Addr(3)),
+        ]);
+        assert_eq!(machine.compute(&program), Ok(&ExprWrapper::from(7)));
+
+        assert_eq!(machine.register(Reg(2)), Ok(&ExprWrapper::from(7)));
+        assert_eq!(
+            machine.register(Reg(0)),
+            Err(ComputeError::UninitializedRegister { reg: Reg(0), pc: 6 })
+        );
+        assert_eq!(
+            machine
+                .registers()
+                .filter_map(|(reg, value)| value.map(|value| (reg, value.strong_eval())))
+                .collect::<Vec<_>>(),
+            [(Reg(2), "7".to_string())]
+        );
+        assert_eq!(machine.registers().count(), REGISTER_COUNT);
+
+        assert_eq!(machine.memory(Addr(3)), Ok(&ExprWrapper::from(7)));
+        assert_eq!(
+            machine.memory(Addr(0)),
+            Err(ComputeError::UninitializedMemory {
+                addr: Addr(0),
+                pc: 6
+            })
+        );
+        let mut cells: Vec<_> = machine
+            .memory_cells()
+            .map(|(addr, value)| (addr.0, value.strong_eval()))
+            .collect();
+        cells.sort();
+        assert_eq!(cells, [(1, "A".to_string()), (3, "7".to_string())]);
+    }
+
+    #[test]
+    fn test_dense_memory() {
+        let mem = HashMap::from([
+            (Addr(0), ExprWrapper::from_symbolic_variable("A")),
+            (Addr(5000), ExprWrapper::from_symbolic_variable("B")),
+        ]);
+        let mut program = Vec::from([Instruction::new().with_ldr(Reg(1), Addr(0))]);
+        program.extend((0..4).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_ldr(Reg(2), Addr(5000)));
+        program.extend((0..4).map(|_| Instruction::new()));
+        program.push(
+            Instruction::new()
+                .with_mul(Reg(0), Reg(1), Reg(2))
+                .with_str(Reg(1), Addr(7))
+                .with_str(Reg(2), Addr(9000)),
+        );
+
+        let mut sparse = Machine::new(mem.clone());
+        let mut dense = Machine::new(mem.clone()).with_dense_memory(1024);
+        assert_eq!(
+            sparse.compute(&program).unwrap(),
+            dense.compute(&program).unwrap()
+        );
+        assert_eq!(sparse.dump_memory(), dense.dump_memory());
+        assert_eq!(dense.memory(Addr(7)).unwrap().strong_eval(), "A");
+        assert_eq!(dense.memory(Addr(9000)).unwrap().strong_eval(), "B");
+
+        dense.reset(mem);
+        assert!(dense.memory(Addr(7)).is_err());
+        assert_eq!(dense.compute(&program).unwrap().strong_eval(), "A * B");
+    }
+
+    #[test]
+    fn test_dump_state() {
+        let mut machine = Machine::new(HashMap::from([
+            (Addr(9), ExprWrapper::from_symbolic_variable("B")),
+            (Addr(1), ExprWrapper::from_symbolic_variable("A")),
+        ]));
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(7)),
+            Instruction::new().with_str(Reg(0), Addr(3)),
+        ]);
+        machine.compute(&program).unwrap();
+
+        assert_eq!(
+            machine.dump_memory(),
+            [
+                (Addr(1), "A".to_string()),
+                (Addr(3), "7".to_string()),
+                (Addr(9), "B".to_string()),
+            ]
+        );
+        let registers = machine.dump_registers();
+        assert_eq!(registers.len(), REGISTER_COUNT);
+        assert_eq!(registers[0], (Reg(0), Some("7".to_string())));
+        assert_eq!(registers[1], (Reg(1), None));
+    }
+
+    #[test]
+    fn test_sti() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_sti(Addr(0), Const(7)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_ldr(Reg(0), Addr(0)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "7".to_string());
+        assert_eq!(machine.cycle_count(), 10);
+    }
+
+    #[test]
+    fn test_add() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_ldi(Reg(1), Const(8)),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.weak_eval(), "(8 + 1)".to_string());
+        assert_eq!(expr.strong_eval(), "9".to_string());
+        assert_eq!(machine.cycle_count(), 4);
+    }
+
+    #[test]
+    fn test_sub() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_ldi(Reg(1), Const(8)),
+            Instruction::new().with_sub(Reg(0), Reg(1), Reg(0)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.weak_eval(), "(8 - 1)".to_string());
+        assert_eq!(expr.strong_eval(), "7".to_string());
+        assert_eq!(machine.cycle_count(), 4);
+    }
+
+    #[test]
+    fn test_mul() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(2)),
+            Instruction::new().with_ldi(Reg(1), Const(8)),
+            Instruction::new().with_mul(Reg(0), Reg(0), Reg(1)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.weak_eval(), "(2 * 8)".to_string());
+        assert_eq!(expr.strong_eval(), "16".to_string());
+        assert_eq!(machine.cycle_count(), 12);
+    }
+
+    #[test]
+    fn test_mul_latency() {
+        let mut machine =
+            Machine::new(HashMap::new()).with_latencies(Latencies::default().with(OpKind::Mul, 2));
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(2)),
+            Instruction::new().with_ldi(Reg(1), Const(8)),
+            Instruction::new().with_mul(Reg(0), Reg(0), Reg(1)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "16".to_string());
+        assert_eq!(machine.cycle_count(), 4);
+    }
+
+    #[test]
+    fn test_cmovz_taken() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_ldi(Reg(1), Const(2)),
+            Instruction::new().with_ldi(Reg(2), Const(0)),
+            Instruction::new().with_cmovz(Reg(0), Reg(1), Reg(2)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "2".to_string());
+        assert_eq!(machine.cycle_count(), 4);
+    }
+
+    #[test]
+    fn test_cmovz_not_taken() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_ldi(Reg(1), Const(2)),
+            Instruction::new().with_ldi(Reg(2), Const(3)),
+            Instruction::new().with_cmovz(Reg(0), Reg(1), Reg(2)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "1".to_string());
+        assert_eq!(machine.cycle_count(), 4);
+    }
+
+    #[test]
+    fn test_cmovz_non_numeric_condition() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_cmovz(Reg(0), Reg(0), Reg(1)),
+        ]);
+        assert!(machine
+            .compute(&program)
+            .is_err_and(|e| e == ComputeError::NonNumericCondition { reg: Reg(1), pc: 5 }));
+    }
+
+    #[test]
+    fn test_mov() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(7)),
+            Instruction::new().with_mov(Reg(0), Reg(1)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "7".to_string());
+        assert_eq!(machine.cycle_count(), 2);
+    }
+
+    #[test]
+    fn test_mov_uninitialized_source() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([Instruction::new().with_mov(Reg(0), Reg(1))]);
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::UninitializedRegister { reg: Reg(1), pc: 0 })
+        );
+    }
+
+    #[test]
+    fn test_example_program() {
+        let mut machine = Machine::new(MemoryBuilder::new().variables_from(0..26, 'A').build());
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new()
+                .with_ldi(Reg(2), Const(2))
+                .with_ldr(Reg(3), Addr(1)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+            Instruction::new().with_add(Reg(2), Reg(2), Reg(3)),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(0), Reg(0), Reg(2)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.weak_eval(), "((A + 1) * (B + 2))".to_string());
+        assert_eq!(expr.strong_eval(), "(A + 1) * (B + 2)".to_string());
+        assert_eq!(expr.degree(), 2);
+        assert_eq!(
+            expr.variables(),
+            BTreeSet::from(["A".to_string(), "B".to_string()])
+        );
+        assert_eq!(machine.cycle_count(), 18);
+    }
+
+    #[test]
+    fn test_write_after_write() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(3)),
+            Instruction::new().with_ldi(Reg(2), Const(4)),
+            Instruction::new().with_mul(Reg(0), Reg(1), Reg(2)),
+            Instruction::new().with_ldi(Reg(0), Const(5)),
+        ]);
+
+        let mut machine = Machine::new(HashMap::new());
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "12".to_string());
+
+        let mut machine = Machine::new(HashMap::new());
+        machine.detect_write_after_write(true);
+        assert_eq!(
+            machine.compute(&program).unwrap_err(),
+            ComputeError::WriteAfterWrite {
+                reg: Reg(0),
+                pc: 1 + Latencies::default().mul,
+                earlier_inst: 2,
+                later_inst: 3,
+            }
+        );
+
+        // Writes retiring in issue order are not hazards
+        let mut machine = Machine::new(HashMap::new());
+        machine.detect_write_after_write(true);
+        let mut program = program;
+        program.swap(2, 3);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "12".to_string());
+
+        // Instructions are reported by their index rather than their cycle
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(3)).with_jmp(2),
+            Instruction::new(),
+            Instruction::new().with_ldi(Reg(2), Const(4)),
+            Instruction::new().with_mul(Reg(0), Reg(1), Reg(2)),
+            Instruction::new().with_ldi(Reg(0), Const(5)),
+        ]);
+        let mut machine = Machine::new(HashMap::new());
+        machine.detect_write_after_write(true);
+        assert_eq!(
+            machine.compute(&program).unwrap_err(),
+            ComputeError::WriteAfterWrite {
+                reg: Reg(0),
+                pc: 1 + Latencies::default().mul,
+                earlier_inst: 3,
+                later_inst: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_max_cycles() {
+        let program = Vec::from([Instruction::new().with_ldi(Reg(0), Const(1))]);
+        let latencies = Latencies::default().with(OpKind::Ldi, 1000);
+
+        let mut machine = Machine::new(HashMap::new())
+            .with_latencies(latencies)
+            .with_max_cycles(100);
+        assert_eq!(
+            machine.compute(&program).unwrap_err(),
+            ComputeError::CycleLimitExceeded { max: 100 }
+        );
+        assert_eq!(machine.cycle_count(), 100);
+
+        let mut machine = Machine::new(HashMap::new())
+            .with_latencies(latencies)
+            .with_max_cycles(1000);
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "1");
+        assert_eq!(machine.cycle_count(), 1000);
+    }
+
+    #[test]
+    fn test_jmp() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_jmp(3),
+            Instruction::new().with_ldi(Reg(0), Const(2)),
+            Instruction::new().with_ldi(Reg(1), Const(3)),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+        ]);
+        let mut machine = Machine::new(HashMap::new());
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "4");
+        assert_eq!(machine.cycle_count(), 5);
+
+        // Operations in flight still complete after jumping
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(2)),
+            Instruction::new()
+                .with_mul(Reg(0), Reg(1), Reg(1))
+                .with_jmp(3),
+            Instruction::new().with_ldi(Reg(0), Const(5)),
+        ]);
+        let mut machine = Machine::new(HashMap::new());
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "4");
+
+        // Infinite loops are stopped by the cycle limit
+        let program = Vec::from([Instruction::new().with_jmp(0)]);
+        let mut machine = Machine::new(HashMap::new()).with_max_cycles(50);
+        assert_eq!(
+            machine.compute(&program).unwrap_err(),
+            ComputeError::JumpLimitExceeded { max: 50 }
+        );
+        assert_eq!(machine.cycle_count(), 50);
+    }
+
+    #[test]
+    fn test_jz() {
+        // Compute 2^3 by multiplying while counting down
+        let mut program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_ldi(Reg(1), Const(2)),
+            Instruction::new().with_ldi(Reg(2), Const(3)),
+            Instruction::new().with_ldi(Reg(3), Const(1)),
+            Instruction::new().with_jz(Reg(2), 14),
+            Instruction::new()
+                .with_mul(Reg(0), Reg(0), Reg(1))
+                .with_sub(Reg(2), Reg(2), Reg(3)),
+        ]);
+        program.extend((0..7).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_jmp(4));
+        assert_eq!(program.len(), 14);
+
+        let mut machine = Machine::new(HashMap::new());
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "8");
+        assert_eq!(machine.cycle_count(), 5 + 3 * 10);
+
+        let program = Vec::from([
+            Instruction::new().with_ldr(Reg(1), Addr(0)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_jz(Reg(1), 0),
+        ]);
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        assert_eq!(
+            machine.compute(&program).unwrap_err(),
+            ComputeError::BranchOnSymbolic { reg: Reg(1), pc: 5 }
+        );
+    }
+
+    #[test]
+    fn test_compute_with_summary() {
+        let mem = HashMap::from([(Addr(0), ExprWrapper::from_symbolic_variable("A"))]);
+        let mut program = Vec::from([
+            Instruction::new()
+                .with_ldr(Reg(1), Addr(0))
+                .with_ldi(Reg(2), Const(3)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(0), Reg(1), Reg(2)),
+        ]);
+        let mut machine = Machine::new(mem.clone());
+        let summary = machine.compute_with_summary(&program).unwrap();
+        assert_eq!(summary.value.strong_eval(), "A * 3".to_string());
+        assert_eq!(summary.cycles, 5 + Latencies::default().mul);
+        assert_eq!(summary.result_writer, Some(5));
+
+        // A slower schedule of the same polynomial takes more cycles
+        program.insert(1, Instruction::new());
+        let mut machine = Machine::new(mem);
+        let summary = machine.compute_with_summary(&program).unwrap();
+        assert_eq!(summary.value.strong_eval(), "A * 3".to_string());
+        assert_eq!(summary.cycles, 6 + Latencies::default().mul);
+        assert_eq!(summary.result_writer, Some(6));
+        assert_eq!(machine.cycle_count(), 6 + Latencies::default().mul);
+        assert_eq!(machine.last_writer(Reg(1)), Some(0));
+        assert_eq!(machine.last_writer(Reg(3)), None);
+    }
+
+    #[test]
+    fn test_result_writer_after_stall() {
+        let mem = HashMap::from([(Addr(0), ExprWrapper::from_symbolic_variable("A"))]);
+        let program = Vec::from([
+            Instruction::new().with_ldr(Reg(1), Addr(0)),
+            Instruction::new().with_add(Reg(0), Reg(1), Reg(1)),
+        ]);
+        let mut machine = Machine::new(mem).with_hazard_policy(HazardPolicy::Stall);
+        let summary = machine.compute_with_summary(&program).unwrap();
+        assert_eq!(summary.value.strong_eval(), "A + A".to_string());
+        assert_eq!(
+            summary.cycles,
+            Latencies::default().ldr + Latencies::default().add
+        );
+        assert_eq!(summary.result_writer, Some(1));
+    }
+
+    #[test]
+    fn test_long_polynomial() {
+        let mut machine = Machine::new(MemoryBuilder::new().variables_from(0..26, 'A').build());
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(3), Const(4))
+                .with_ldr(Reg(6), Addr(2)),
+            Instruction::new()
+                .with_ldi(Reg(2), Const(3))
+                .with_ldr(Reg(7), Addr(3)),
+            Instruction::new()
+                .with_ldi(Reg(1), Const(2))
+                .with_ldr(Reg(5), Addr(1)),
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(4), Addr(0)),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(3), Reg(3), Reg(6)),
+            Instruction::new().with_sub(Reg(3), Reg(6), Reg(7)),
+            Instruction::new().with_add(Reg(1), Reg(1), Reg(5)),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(4)),
+            Instruction::new().with_add(Reg(2), Reg(2), Reg(3)),
+            Instruction::new().with_mul(Reg(1), Reg(0), Reg(1)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(3), Reg(3), Reg(7)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(1), Reg(1), Reg(2)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(0), Reg(3), Reg(1)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(
+            expr.weak_eval(),
+            "((((A + 1) * (B + 2)) * ((C - D) + 3)) + ((4 * C) * D))".to_string()
+        );
+        assert_eq!(
+            expr.strong_eval(),
+            "(A + 1) * (B + 2) * (C - D + 3) + 4 * C * D".to_string()
+        );
+        assert_eq!(machine.cycle_count(), 32);
+    }
+
+    #[test]
+    fn test_reset_registers() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(7)),
+            Instruction::new().with_str(Reg(0), Addr(5)),
+        ]);
+        assert!(machine.compute(&program).is_ok());
+
+        machine.reset_registers();
+        assert_eq!(machine.cycle_count(), 0);
+        assert!(machine.regs.iter().all(Option::is_none));
+
+        let program = Vec::from([
+            Instruction::new().with_ldr(Reg(1), Addr(5)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(0), Reg(1), Reg(1)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "14".to_string());
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        machine
+            .step(Some(&Instruction::new().with_ldr(Reg(1), Addr(0))))
+            .unwrap();
+        machine
+            .step(Some(&Instruction::new().with_ldi(Reg(2), Const(3))))
+            .unwrap();
+        let state = machine.snapshot();
+
+        let run = |machine: &mut Machine, instruction: Instruction| {
+            for _ in 0..4 {
+                machine.step(Some(&Instruction::new())).unwrap();
+            }
+            machine.step(Some(&instruction)).unwrap();
+            while machine.step(None).unwrap() != StepOutcome::Idle {}
+            (
+                machine.register(Reg(0)).unwrap().strong_eval(),
+                machine.cycle_count(),
+                machine.retirement_log(),
+            )
+        };
+
+        let (value, cycles, log) = run(
+            &mut machine,
+            Instruction::new().with_mul(Reg(0), Reg(1), Reg(2)),
+        );
+        assert_eq!(value, "A * 3");
+
+        machine.restore(state.clone());
+        assert_eq!(machine.cycle_count(), 2);
+        assert!(machine.register(Reg(0)).is_err());
+        let (other, _, _) = run(
+            &mut machine,
+            Instruction::new().with_add(Reg(0), Reg(1), Reg(2)),
+        );
+        assert_eq!(other, "3 + A");
+
+        machine.restore(state);
+        assert_eq!(
+            run(
+                &mut machine,
+                Instruction::new().with_mul(Reg(0), Reg(1), Reg(2))
+            ),
+            (value, cycles, log)
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        machine.allow_data_race(true);
+        let program = Vec::from([Instruction::new().with_ldr(Reg(0), Addr(0))]);
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "A");
+        assert_eq!(
+            machine.compute(&program).unwrap_err(),
+            ComputeError::Terminated
+        );
+
+        machine.reset(HashMap::from([(Addr(3), ExprWrapper::from(6))]));
+        assert_eq!(machine.cycle_count(), 0);
+        assert!(machine.pending_operations.is_empty());
+        assert!(machine.regs.iter().all(Option::is_none));
+        assert!(machine.allow_data_race);
+        assert!(machine.memory(Addr(0)).is_err());
+
+        let program = Vec::from([
+            Instruction::new().with_ldr(Reg(1), Addr(3)),
+            Instruction::new().with_ldi(Reg(2), Const(7)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(0), Reg(1), Reg(2)),
+        ]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "42".to_string());
+    }
+
+    #[test]
+    fn test_operation_records_after_jump() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)).with_jmp(2),
+            Instruction::new().with_ldi(Reg(1), Const(5)),
+            Instruction::new().with_ldi(Reg(2), Const(3)),
+        ]);
+        machine.compute(&program).unwrap();
+        assert_eq!(
+            machine.operation_records().last(),
+            Some(&OperationRecord {
+                instruction: 2,
+                issued_at: 1,
+                retired_at: 1,
+                output: "WriteToRegister(Reg(2), `3`)".to_string(),
+            })
+        );
+        assert_eq!(machine.retirement_log()[1].1, 2);
+    }
+
+    #[test]
+    fn test_retirement_log() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(2), Addr(0)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(1), Reg(0), Reg(0)),
+            Instruction::new().with_ldi(Reg(3), Const(9)),
+        ]);
+        assert!(machine.compute(&program).is_ok());
+        assert_eq!(
+            machine.retirement_log(),
+            Vec::from([
+                (0, 0, "WriteToRegister(Reg(0), `1`)".to_string()),
+                (4, 4, "WriteToRegister(Reg(3), `9`)".to_string()),
+                (4, 0, "WriteToRegister(Reg(2), `A`)".to_string()),
+                (4, 3, "WriteToRegister(Reg(1), `(1 + 1)`)".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_duplicate_registers() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(5))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new().with_ldi(Reg(4), Const(1)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(2), Reg(1), Reg(4)),
+            Instruction::new().with_add(Reg(3), Reg(1), Reg(4)),
+        ]);
+        assert!(machine.compute(&program).is_ok());
+        assert_eq!(machine.duplicate_registers(), Vec::from([(Reg(2), Reg(3))]));
+    }
+
+    #[test]
+    fn test_zeroed_registers() {
+        let mut machine = Machine::new(HashMap::new()).with_zeroed_registers();
+        let program = Vec::from([Instruction::new().with_add(Reg(0), Reg(1), Reg(2))]);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.weak_eval(), "(0 + 0)".to_string());
+        assert_eq!(expr.strong_eval(), "0".to_string());
+
+        machine.reset_registers();
+        assert!(machine
+            .compute(&Vec::new())
+            .is_ok_and(|expr| expr.strong_eval() == "0"));
+    }
+
+    #[test]
+    fn test_flush_policy() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(2)),
+            Instruction::new().with_ldi(Reg(2), Const(3)),
+            Instruction::new().with_mul(Reg(3), Reg(1), Reg(2)),
+            Instruction::new().with_add(Reg(0), Reg(1), Reg(2)),
+        ]);
+
+        let mut machine = Machine::new(HashMap::new());
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "5".to_string());
+        assert_eq!(machine.cycle_count(), 12);
+
+        let mut machine =
+            Machine::new(HashMap::new()).with_flush_policy(FlushPolicy::UntilResultReady);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "5".to_string());
+        assert_eq!(machine.cycle_count(), 5);
+    }
+
+    #[test]
+    fn test_store_policy() {
+        let mem = HashMap::from([(Addr(5), ExprWrapper::from_symbolic_variable("A"))]);
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(2)),
+            Instruction::new().with_str(Reg(0), Addr(5)),
+            Instruction::new().with_ldr(Reg(0), Addr(5)),
+        ]);
+
+        let mut machine = Machine::new(mem.clone());
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "A".to_string());
+
+        let mut machine = Machine::new(mem.clone()).with_store_policy(StorePolicy::Forward);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.strong_eval(), "2".to_string());
+
+        let mut machine = Machine::new(mem).with_store_policy(StorePolicy::Error);
+        assert_eq!(
+            machine.compute(&program).unwrap_err(),
+            ComputeError::MemoryReadBeforeWrite {
+                addr: Addr(5),
+                pc: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_opcode_not_permitted() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_str(Reg(0), Addr(0)),
+        ]);
+
+        let mut machine = Machine::new(HashMap::new()).with_permitted_opcodes([
+            OpKind::Ldi,
+            OpKind::Ldr,
+            OpKind::Add,
+        ]);
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::OpcodeNotPermitted {
+                op: OpKind::Str,
+                pc: 1
+            })
+        );
+
+        let mut machine = Machine::new(HashMap::new());
+        assert!(machine.compute(&program).is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_destination() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(2), Const(2)),
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_mov(Reg(0), Reg(2)),
+        ]);
+
+        let mut machine = Machine::new(HashMap::new());
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::InvalidInstruction {
+                error: InstructionError::DuplicateRegister {
+                    reg: Reg(0),
+                    first: OpKind::Ldi,
+                    second: OpKind::Mov,
+                },
+                pc: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_clr() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(1)),
+            Instruction::new().with_clr(Reg(1)),
+            Instruction::new().with_add(Reg(0), Reg(1), Reg(1)),
+        ]);
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::UninitializedRegister { reg: Reg(1), pc: 2 })
+        );
+    }
+
+    #[test]
+    fn test_compute_yielding() {
+        let mem = HashMap::from([
+            (Addr(0), ExprWrapper::from_symbolic_variable("A")),
+            (Addr(1), ExprWrapper::from_symbolic_variable("B")),
+        ]);
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new()
+                .with_ldi(Reg(2), Const(2))
+                .with_ldr(Reg(3), Addr(1)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+            Instruction::new().with_add(Reg(2), Reg(2), Reg(3)),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(0), Reg(0), Reg(2)),
+        ]);
+
+        let mut machine = Machine::new(mem.clone());
+        let expected = machine.compute(&program).unwrap().clone();
+        let cycles = machine.cycle_count();
+
+        let mut machine = Machine::new(mem);
+        let mut slices = 1;
+        let result = loop {
+            match machine.compute_yielding(&program, 4) {
+                ComputeProgress::Pending => slices += 1,
+                ComputeProgress::Done(result) => break result,
+            }
+        };
+        assert_eq!(result, Ok(expected));
+        assert_eq!(machine.cycle_count(), cycles);
+        assert_eq!(slices, cycles.div_ceil(4));
+    }
+
+    #[test]
+    fn test_halt() {
+        let mem = HashMap::from([(Addr(0), ExprWrapper::from_symbolic_variable("A"))]);
+        let program = Vec::from([
+            Instruction::new().with_ldr(Reg(0), Addr(0)).with_halt(),
+            Instruction::new().with_ldi(Reg(0), Const(2)),
+        ]);
+        let mut machine = Machine::new(mem.clone());
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "A");
+        assert!(machine.is_halted());
+        assert_eq!(machine.cycle_count(), Latencies::default().ldr);
+        assert_eq!(machine.stats().count(OpKind::Ldi), 0);
+
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_jmp(0).with_halt(),
+            Instruction::new().with_ldi(Reg(0), Const(2)),
+        ]);
+        let mut machine = Machine::new(mem);
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "1");
+        assert_eq!(machine.cycle_count(), 2);
+
+        machine.reset_registers();
+        assert!(!machine.is_halted());
+        assert_eq!(
+            machine.compute_yielding(&program, 10),
+            ComputeProgress::Done(Ok(ExprWrapper::from(1)))
+        );
+    }
+
+    #[test]
+    fn test_run_events() {
+        let mem = HashMap::from([(Addr(0), ExprWrapper::from_symbolic_variable("A"))]);
+        let ldr = Latencies::default().ldr;
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new().with_ldi(Reg(2), Const(2)),
+        ]);
+
+        let mut machine = Machine::new(mem.clone());
+        let events: Vec<_> = machine.run_events(&program).collect();
+        assert_eq!(events.len(), ldr);
+        assert_eq!(
+            events[0],
+            Ok(CycleEvent {
+                cycle: 0,
+                issued: Some(0),
+                completed: Vec::from([(Location::Register(Reg(0)), Some("1".to_string()))]),
+            })
+        );
+        assert_eq!(
+            events[1],
+            Ok(CycleEvent {
+                cycle: 1,
+                issued: Some(1),
+                completed: Vec::from([(Location::Register(Reg(2)), Some("2".to_string()))]),
+            })
+        );
+        assert_eq!(
+            events[ldr - 1],
+            Ok(CycleEvent {
+                cycle: ldr - 1,
+                issued: None,
+                completed: Vec::from([(Location::Register(Reg(1)), Some("A".to_string()))]),
+            })
+        );
+        assert_eq!(
+            machine.run_events(&program).collect::<Vec<_>>(),
+            [Err(ComputeError::Terminated)]
+        );
+
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(3)),
+        ]);
+        let mut machine = Machine::new(mem);
+        let events: Vec<_> = machine.run_events(&program).collect();
+        assert_eq!(events.len(), 2);
+        assert!(events[0].is_ok());
+        assert!(matches!(
+            events[1],
+            Err(ComputeError::UninitializedRegister { .. })
+        ));
+    }
+
+    #[test]
+    fn test_eager_fold() {
+        let mut program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_ldi(Reg(1), Const(3)),
+        ]);
+        for _ in 0..200 {
+            program.push(Instruction::new().with_mul(Reg(0), Reg(0), Reg(1)));
+            program.extend((1..Latencies::default().mul).map(|_| Instruction::new()));
+            program.push(Instruction::new().with_add(Reg(0), Reg(0), Reg(1)));
+            program.extend((1..Latencies::default().add).map(|_| Instruction::new()));
+        }
+
+        let mut machine = Machine::new(HashMap::new());
+        let expected = machine.compute(&program).unwrap().strong_eval();
+
+        let mut machine = Machine::new(HashMap::new());
+        machine.eager_fold(true);
+        let expr = machine.compute(&program).unwrap();
+        assert_eq!(expr.weak_eval(), expected);
+        assert_eq!(
+            expr.as_const().map(|value| value.to_string()),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_long_run_shared_subtrees() {
+        // `A` doubled 64 times shares each subtree twice, so rendering any
+        // intermediate value while running would take 2^64 steps
+        let mut program = Vec::from([Instruction::new().with_ldr(Reg(0), Addr(0))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        for _ in 0..64 {
+            program.push(Instruction::new().with_add(Reg(0), Reg(0), Reg(0)));
+            program.extend((1..Latencies::default().add).map(|_| Instruction::new()));
+        }
+
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        machine.compute(&program).unwrap();
+        assert_eq!(machine.stats().count(OpKind::Add), 64);
+
+        let mut machine = Machine::new(HashMap::from([(Addr(0), ExprWrapper::from(1))]));
+        machine.eager_fold(true);
+        machine.compute(&program).unwrap();
+        assert_eq!(machine.result_u32(), Ok(Some(0)));
+    }
+
+    #[test]
+    fn test_result_u32() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        assert_eq!(
+            machine.result_u32(),
+            Err(ComputeError::UninitializedRegister { reg: Reg(0), pc: 0 })
+        );
+
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(6)),
+            Instruction::new().with_ldi(Reg(1), Const(7)),
+            Instruction::new().with_mul(Reg(0), Reg(0), Reg(1)),
+        ]);
+        machine.compute(&program).unwrap();
+        assert_eq!(machine.result_u32(), Ok(Some(42)));
+
+        machine.reset_registers();
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(0), Addr(0))
+            .with_ldi(Reg(1), Const(7))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_mul(Reg(0), Reg(0), Reg(1)));
+        machine.compute(&program).unwrap();
+        assert_eq!(machine.result_u32(), Ok(None));
+    }
+
+    #[test]
+    fn test_export_registers_to_memory() {
+        let mut stage1 = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(1), Addr(0))
+            .with_ldi(Reg(2), Const(2))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_add(Reg(0), Reg(1), Reg(2)));
+        stage1.compute(&program).unwrap();
+
+        let memory = stage1.export_registers_to_memory(&[(Reg(0), Addr(5)), (Reg(3), Addr(6))]);
+        assert_eq!(memory.len(), 1);
+
+        let mut stage2 = Machine::new(memory);
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(1), Addr(5))
+            .with_ldi(Reg(2), Const(3))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_mul(Reg(0), Reg(1), Reg(2)));
+        assert_eq!(
+            stage2.compute(&program).unwrap().strong_eval(),
+            "(2 + A) * 3"
+        );
+    }
+
+    fn hazard_program() -> Vec<Instruction> {
+        Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(3)),
+            Instruction::new().with_ldi(Reg(2), Const(4)),
+            Instruction::new().with_mul(Reg(3), Reg(1), Reg(2)),
+            Instruction::new().with_add(Reg(0), Reg(3), Reg(1)),
+        ])
+    }
+
+    #[test]
+    fn test_hazard_policy_read_stale() {
+        let mut machine = Machine::new(HashMap::new()).with_zeroed_registers();
+        assert_eq!(
+            machine.compute(&hazard_program()).unwrap().strong_eval(),
+            "3"
+        );
+        assert_eq!(machine.cycle_count(), 12);
+    }
+
+    #[test]
+    fn test_hazard_policy_stall() {
+        let mut machine = Machine::new(HashMap::new())
+            .with_zeroed_registers()
+            .with_hazard_policy(HazardPolicy::Stall);
+        assert_eq!(
+            machine.compute(&hazard_program()).unwrap().strong_eval(),
+            "15"
+        );
+        assert_eq!(machine.cycle_count(), 14);
+        assert_eq!(machine.issue_profile().len(), 13);
+
+        let mut machine = Machine::new(HashMap::new())
+            .with_zeroed_registers()
+            .with_hazard_policy(HazardPolicy::Stall);
+        assert_eq!(
+            machine.compute_yielding(&hazard_program(), 5),
+            ComputeProgress::Pending
+        );
+        match machine.compute_yielding(&hazard_program(), 100) {
+            ComputeProgress::Done(Ok(result)) => assert_eq!(result.strong_eval(), "15"),
+            progress => panic!("Unexpected progress {progress:?}"),
+        }
+        assert_eq!(machine.cycle_count(), 14);
+    }
+
+    #[test]
+    fn test_hazard_policy_error() {
+        let mut machine = Machine::new(HashMap::new())
+            .with_zeroed_registers()
+            .with_hazard_policy(HazardPolicy::Error);
+        assert_eq!(
+            machine.compute(&hazard_program()),
+            Err(ComputeError::ReadHazard {
+                reg: Reg(3),
+                pc: 3,
+                pending_inst: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_strict_hazards() {
+        let mem = HashMap::from([(Addr(5), ExprWrapper::from_symbolic_variable("A"))]);
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_ldr(Reg(0), Addr(5)),
+            Instruction::new().with_add(Reg(1), Reg(0), Reg(0)),
+        ]);
+
+        let mut machine = Machine::new(mem.clone()).with_strict_hazards(true);
+        let error = machine.compute(&program).unwrap_err();
+        assert_eq!(
+            error,
+            ComputeError::ReadHazard {
+                reg: Reg(0),
+                pc: 2,
+                pending_inst: 1
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Reading register #0 before the pending write by instruction #1 at instruction #2"
+        );
+
+        // The policy set last applies
+        let mut machine = Machine::new(mem.clone())
+            .with_hazard_policy(HazardPolicy::Stall)
+            .with_strict_hazards(true);
+        assert!(matches!(
+            machine.compute(&program),
+            Err(ComputeError::ReadHazard { .. })
+        ));
+        let mut machine = Machine::new(mem.clone())
+            .with_strict_hazards(true)
+            .with_hazard_policy(HazardPolicy::Stall);
+        machine.compute(&program).unwrap();
+        assert_eq!(
+            machine.get_register_value(Reg(1)).unwrap().strong_eval(),
+            "A + A"
+        );
+
+        let mut machine = Machine::new(mem.clone())
+            .with_strict_hazards(true)
+            .with_strict_hazards(false);
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "A");
+
+        // Turning strict hazards off keeps a policy other than `Error`
+        let mut machine = Machine::new(mem)
+            .with_hazard_policy(HazardPolicy::Stall)
+            .with_strict_hazards(false);
+        machine.compute(&program).unwrap();
+        assert_eq!(
+            machine.get_register_value(Reg(1)).unwrap().strong_eval(),
+            "A + A"
+        );
+    }
+
+    #[test]
+    fn test_strict_hazards_after_jump() {
+        let mem = HashMap::from([(Addr(5), ExprWrapper::from_symbolic_variable("A"))]);
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)).with_jmp(2),
+            Instruction::new(),
+            Instruction::new().with_ldr(Reg(0), Addr(5)),
+            Instruction::new().with_add(Reg(1), Reg(0), Reg(0)),
+        ]);
+        let mut machine = Machine::new(mem).with_strict_hazards(true);
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::ReadHazard {
+                reg: Reg(0),
+                pc: 2,
+                pending_inst: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_div() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(1), Addr(0))
+            .with_ldi(Reg(2), Const(3))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_add(Reg(3), Reg(1), Reg(2)));
+        program.extend((1..Latencies::default().add).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_div(Reg(0), Reg(3), Reg(2)));
+        assert_eq!(
+            machine.compute(&program).unwrap().strong_eval(),
+            "(3 + A) / 3"
+        );
+
+        let mut machine = Machine::new(HashMap::new());
+        machine.eager_fold(true);
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(17)),
+            Instruction::new().with_ldi(Reg(2), Const(5)),
+            Instruction::new().with_div(Reg(0), Reg(1), Reg(2)),
+        ]);
+        assert_eq!(machine.compute(&program).unwrap(), &ExprWrapper::from(3));
+        assert_eq!(machine.cycle_count(), 2 + Latencies::default().div);
+    }
+
+    #[test]
+    fn test_neg() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(1), Addr(0))
+            .with_ldi(Reg(2), Const(5))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(
+            Instruction::new()
+                .with_neg(Reg(3), Reg(1))
+                .with_add(Reg(4), Reg(1), Reg(2)),
+        );
+        program.extend((1..Latencies::default().add).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_neg(Reg(0), Reg(4)));
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "-(5 + A)");
+        assert_eq!(machine.register(Reg(3)).unwrap().strong_eval(), "-A");
+    }
+
+    #[test]
+    fn test_result_register() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]))
+        .with_result_register(Reg(3));
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(1), Addr(0))
+            .with_ldi(Reg(2), Const(1))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_add(Reg(3), Reg(1), Reg(2)));
+        let summary = machine.compute_with_summary(&program).unwrap();
+        assert_eq!(summary.value.strong_eval(), "1 + A");
+        assert_eq!(summary.result_writer, Some(Latencies::default().ldr));
+        assert_eq!(machine.register(Reg(3)).unwrap().strong_eval(), "1 + A");
+        assert!(machine.register(Reg(0)).is_err());
+
+        let mut machine = Machine::new(HashMap::new()).with_result_register(Reg(1));
+        let program = Vec::from([Instruction::new().with_ldi(Reg(1), Const(7))]);
+        assert_eq!(
+            machine.result_u32(),
+            Err(ComputeError::UninitializedRegister { reg: Reg(1), pc: 0 })
+        );
+        machine.compute(&program).unwrap();
+        assert_eq!(machine.result_u32(), Ok(Some(7)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Result register Reg(8) is out of range")]
+    fn test_result_register_out_of_range() {
+        let _ = Machine::new(HashMap::new()).with_result_register(Reg(8));
+    }
+
+    #[test]
+    fn test_sqr() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        let mut program = Vec::from([Instruction::new().with_ldr(Reg(1), Addr(0))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_sqr(Reg(0), Reg(1)));
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "A * A");
+        assert_eq!(
+            machine.cycle_count(),
+            Latencies::default().ldr + Latencies::default().mul
+        );
+
+        let mut machine = Machine::new(HashMap::new()).with_checked_arithmetic(true);
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(1 << 16)),
+            Instruction::new().with_sqr(Reg(0), Reg(1)),
+        ]);
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::ArithmeticOverflow { pc: 1 })
+        );
+    }
+
+    #[test]
+    fn test_pow() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(1), Addr(0))
+            .with_ldi(Reg(2), Const(3))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_pow(Reg(0), Reg(1), Reg(2)));
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "A^3");
+        assert_eq!(
+            machine.cycle_count(),
+            Latencies::default().ldr + Latencies::default().pow
+        );
+
+        let mut machine = Machine::new(HashMap::new());
+        machine.eager_fold(true);
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(2)),
+            Instruction::new().with_ldi(Reg(2), Const(10)),
+            Instruction::new().with_pow(Reg(0), Reg(1), Reg(2)),
+        ]);
+        assert_eq!(machine.compute(&program), Ok(&ExprWrapper::from(1024)));
+    }
 
-#[cfg(test)]
-mod test {
-    use crate::pem::types::{Const, Reg};
+    #[test]
+    fn test_logic() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(1), Addr(0))
+            .with_ldi(Reg(2), Const(12))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(
+            Instruction::new()
+                .with_and(Reg(3), Reg(1), Reg(2))
+                .with_xor(Reg(4), Reg(2), Reg(2)),
+        );
+        program.push(Instruction::new().with_or(Reg(0), Reg(3), Reg(4)));
+        assert_eq!(
+            machine.compute(&program).unwrap().strong_eval(),
+            "A & 12 | 0"
+        );
+        assert_eq!(
+            machine.cycle_count(),
+            Latencies::default().ldr + 2 * Latencies::default().and
+        );
+    }
 
-    use super::*;
+    #[test]
+    fn test_shift() {
+        let mut machine = Machine::new(HashMap::new());
+        machine.eager_fold(true);
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(5)),
+            Instruction::new().with_ldi(Reg(2), Const(34)),
+            Instruction::new()
+                .with_shl(Reg(3), Reg(1), Reg(2))
+                .with_shr(Reg(4), Reg(1), Reg(2)),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(0), Reg(3), Reg(4)),
+        ]);
+        assert_eq!(machine.compute(&program), Ok(&ExprWrapper::from(21)));
+    }
 
     #[test]
-    fn test_str() {
+    fn test_min_max() {
         let mut machine = Machine::new(HashMap::new());
         let program = Vec::from([
-            Instruction::new().with_ldi(Reg(0), Const(1)),
-            Instruction::new().with_str(Reg(0), Addr(0)),
+            Instruction::new().with_ldi(Reg(1), Const(7)),
+            Instruction::new().with_ldi(Reg(2), Const(3)),
+            Instruction::new()
+                .with_min(Reg(0), Reg(1), Reg(2))
+                .with_max(Reg(3), Reg(1), Reg(2)),
         ]);
-        let expr = machine.compute(&program).unwrap();
-        assert_eq!(expr.weak_eval(), "1".to_string());
-        assert_eq!(expr.strong_eval(), "1".to_string());
-        machine
-            .get_address_value(&Addr(0))
-            .map(|v| {
-                assert_eq!(v.weak_eval(), "1".to_string());
+        assert_eq!(machine.compute(&program), Ok(&ExprWrapper::from(3)));
+        assert_eq!(machine.register(Reg(3)), Ok(&ExprWrapper::from(7)));
+        assert_eq!(machine.cycle_count(), 2 + Latencies::default().min);
+
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(1), Addr(0))
+            .with_ldi(Reg(2), Const(3))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_max(Reg(0), Reg(1), Reg(2)));
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::NonNumericComparison {
+                pc: Latencies::default().ldr
             })
-            .expect("Memory address 0 should be initialized");
-        assert_eq!(machine.pc, 6);
+        );
     }
 
     #[test]
-    fn test_add() {
+    fn test_checked_arithmetic() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(u32::MAX)),
+            Instruction::new().with_ldi(Reg(2), Const(1)),
+            Instruction::new().with_add(Reg(0), Reg(1), Reg(2)),
+        ]);
         let mut machine = Machine::new(HashMap::new());
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "0");
+
+        let mut machine = Machine::new(HashMap::new()).with_checked_arithmetic(true);
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::ArithmeticOverflow { pc: 2 })
+        );
+
         let program = Vec::from([
-            Instruction::new().with_ldi(Reg(0), Const(1)),
-            Instruction::new().with_ldi(Reg(1), Const(8)),
-            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+            Instruction::new().with_ldi(Reg(1), Const(1)),
+            Instruction::new().with_ldi(Reg(2), Const(2)),
+            Instruction::new().with_sub(Reg(0), Reg(1), Reg(2)),
         ]);
-        let expr = machine.compute(&program).unwrap();
-        assert_eq!(expr.weak_eval(), "(8 + 1)".to_string());
-        assert_eq!(expr.strong_eval(), "9".to_string());
-        assert_eq!(machine.pc, 4);
+        let mut machine = Machine::new(HashMap::new()).with_checked_arithmetic(true);
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::ArithmeticOverflow { pc: 2 })
+        );
+
+        // Symbolic operations never overflow
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]))
+        .with_checked_arithmetic(true);
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(1), Addr(0))
+            .with_ldi(Reg(2), Const(u32::MAX))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_mul(Reg(0), Reg(1), Reg(2)));
+        assert_eq!(
+            machine.compute(&program).unwrap().strong_eval(),
+            "A * 4294967295"
+        );
     }
 
     #[test]
-    fn test_sub() {
+    fn test_div_by_zero() {
         let mut machine = Machine::new(HashMap::new());
         let program = Vec::from([
-            Instruction::new().with_ldi(Reg(0), Const(1)),
-            Instruction::new().with_ldi(Reg(1), Const(8)),
-            Instruction::new().with_sub(Reg(0), Reg(1), Reg(0)),
+            Instruction::new().with_ldi(Reg(1), Const(17)),
+            Instruction::new().with_ldi(Reg(2), Const(0)),
+            Instruction::new().with_div(Reg(0), Reg(1), Reg(2)),
         ]);
-        let expr = machine.compute(&program).unwrap();
-        assert_eq!(expr.weak_eval(), "(8 - 1)".to_string());
-        assert_eq!(expr.strong_eval(), "7".to_string());
-        assert_eq!(machine.pc, 4);
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::DivisionByZero { pc: 2 })
+        );
     }
 
     #[test]
-    fn test_mul() {
+    fn test_step() {
         let mut machine = Machine::new(HashMap::new());
         let program = Vec::from([
             Instruction::new().with_ldi(Reg(0), Const(2)),
-            Instruction::new().with_ldi(Reg(1), Const(8)),
-            Instruction::new().with_mul(Reg(0), Reg(0), Reg(1)),
+            Instruction::new().with_ldi(Reg(1), Const(3)),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
         ]);
-        let expr = machine.compute(&program).unwrap();
-        assert_eq!(expr.weak_eval(), "(2 * 8)".to_string());
-        assert_eq!(expr.strong_eval(), "16".to_string());
-        assert_eq!(machine.pc, 12);
+        assert_eq!(machine.step(Some(&program[0])), Ok(StepOutcome::Idle));
+        assert_eq!(machine.step(Some(&program[1])), Ok(StepOutcome::Idle));
+        assert_eq!(machine.step(Some(&program[2])), Ok(StepOutcome::Busy));
+        assert_eq!(machine.step(None), Ok(StepOutcome::Idle));
+        assert_eq!(machine.cycle_count(), 4);
+        assert_eq!(machine.result_u32(), Ok(Some(5)));
+
+        let mut machine = Machine::new(HashMap::new())
+            .with_zeroed_registers()
+            .with_hazard_policy(HazardPolicy::Stall);
+        let program = hazard_program();
+        for instruction in &program[..3] {
+            machine.step(Some(instruction)).unwrap();
+        }
+        assert_eq!(machine.step(Some(&program[3])), Ok(StepOutcome::Stalled));
     }
 
     #[test]
-    fn test_example_program() {
-        let mut machine =
-            Machine::new(HashMap::from_iter(('A'..='Z').enumerate().map(|(i, c)| {
-                (Addr(i as u32), ExprWrapper::from_symbolic_variable(c))
-            })));
+    fn test_step_error_context() {
+        let mut machine = Machine::new(HashMap::new());
+        assert_eq!(
+            machine.step(Some(&Instruction::new().with_mov(Reg(0), Reg(1)))),
+            Err(ComputeError::UninitializedRegister { reg: Reg(1), pc: 0 })
+        );
+        assert_eq!(machine.last_error_context().unwrap().pc, 0);
+    }
+
+    #[test]
+    fn test_bypass() {
         let program = Vec::from([
-            Instruction::new()
-                .with_ldi(Reg(0), Const(1))
-                .with_ldr(Reg(1), Addr(0)),
-            Instruction::new()
-                .with_ldi(Reg(2), Const(2))
-                .with_ldr(Reg(3), Addr(1)),
-            Instruction::new(),
+            Instruction::new().with_ldi(Reg(1), Const(1)),
+            Instruction::new().with_add(Reg(0), Reg(1), Reg(1)),
             Instruction::new(),
-            Instruction::new(),
-            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
-            Instruction::new().with_add(Reg(2), Reg(2), Reg(3)),
-            Instruction::new(),
-            Instruction::new().with_mul(Reg(0), Reg(0), Reg(2)),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(0)),
         ]);
-        let expr = machine.compute(&program).unwrap();
-        assert_eq!(expr.weak_eval(), "((A + 1) * (B + 2))".to_string());
-        assert_eq!(expr.strong_eval(), "(A + 1) * (B + 2)".to_string());
-        assert_eq!(machine.pc, 18);
+        let mut machine = Machine::new(HashMap::new());
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "4");
+        assert_eq!(machine.cycle_count(), 5);
+
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(1)),
+            Instruction::new().with_add(Reg(0), Reg(1), Reg(1)),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(0)),
+        ]);
+        let mut machine = Machine::new(HashMap::new());
+        machine.bypass(true);
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "4");
+        assert_eq!(machine.cycle_count(), 4);
+
+        let mut machine = Machine::new(HashMap::new());
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::UninitializedRegister { reg: Reg(0), pc: 2 })
+        );
     }
 
     #[test]
-    fn test_long_polynomial() {
-        let mut machine =
-            Machine::new(HashMap::from_iter(('A'..='Z').enumerate().map(|(i, c)| {
-                (Addr(i as u32), ExprWrapper::from_symbolic_variable(c))
-            })));
+    fn test_verify_against() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(1), Addr(0))
+            .with_ldi(Reg(2), Const(2))]);
+        program.extend((1..Latencies::default().ldr).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_add(Reg(0), Reg(2), Reg(1)));
+        program.push(Instruction::new().with_str(Reg(2), Addr(1)));
+        machine.compute(&program).unwrap();
+
+        let spec = ExpectedState::new()
+            .with_register(Reg(0), "A + 2")
+            .with_register(Reg(2), "2")
+            .with_memory(Addr(1), "2");
+        assert!(machine.verify_against(&spec).is_empty());
+
+        let spec = ExpectedState::new()
+            .with_register(Reg(0), "2 + A")
+            .with_register(Reg(1), "A")
+            .with_register(Reg(3), "0")
+            .with_memory(Addr(0), "B");
+        assert_eq!(
+            machine.verify_against(&spec),
+            Vec::from([
+                Mismatch {
+                    location: Location::Register(Reg(0)),
+                    expected: "2 + A".to_string(),
+                    actual: Some("A + 2".to_string()),
+                },
+                Mismatch {
+                    location: Location::Register(Reg(3)),
+                    expected: "0".to_string(),
+                    actual: None,
+                },
+                Mismatch {
+                    location: Location::Memory(Addr(0)),
+                    expected: "B".to_string(),
+                    actual: Some("A".to_string()),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_issue_profile() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
         let program = Vec::from([
             Instruction::new()
-                .with_ldi(Reg(3), Const(4))
-                .with_ldr(Reg(6), Addr(2)),
-            Instruction::new()
-                .with_ldi(Reg(2), Const(3))
-                .with_ldr(Reg(7), Addr(3)),
-            Instruction::new()
-                .with_ldi(Reg(1), Const(2))
-                .with_ldr(Reg(5), Addr(1)),
-            Instruction::new()
-                .with_ldi(Reg(0), Const(1))
-                .with_ldr(Reg(4), Addr(0)),
-            Instruction::new(),
-            Instruction::new().with_mul(Reg(3), Reg(3), Reg(6)),
-            Instruction::new().with_sub(Reg(3), Reg(6), Reg(7)),
-            Instruction::new().with_add(Reg(1), Reg(1), Reg(5)),
-            Instruction::new().with_add(Reg(0), Reg(0), Reg(4)),
-            Instruction::new().with_add(Reg(2), Reg(2), Reg(3)),
-            Instruction::new().with_mul(Reg(1), Reg(0), Reg(1)),
-            Instruction::new(),
-            Instruction::new(),
-            Instruction::new(),
-            Instruction::new(),
-            Instruction::new().with_mul(Reg(3), Reg(3), Reg(7)),
-            Instruction::new(),
-            Instruction::new(),
-            Instruction::new(),
-            Instruction::new(),
-            Instruction::new().with_mul(Reg(1), Reg(1), Reg(2)),
-            Instruction::new(),
-            Instruction::new(),
+                .with_ldi(Reg(1), Const(1))
+                .with_ldr(Reg(2), Addr(0))
+                .with_sti(Addr(1), Const(2)),
             Instruction::new(),
             Instruction::new(),
-            Instruction::new(),
-            Instruction::new(),
-            Instruction::new(),
-            Instruction::new(),
-            Instruction::new(),
-            Instruction::new().with_add(Reg(0), Reg(3), Reg(1)),
+            Instruction::new().with_ldi(Reg(0), Const(3)),
         ]);
-        let expr = machine.compute(&program).unwrap();
-        assert_eq!(
-            expr.weak_eval(),
-            "((((A + 1) * (B + 2)) * ((C - D) + 3)) + ((4 * C) * D))".to_string()
-        );
+        machine.compute(&program).unwrap();
+
         assert_eq!(
-            expr.strong_eval(),
-            "(A + 1) * (B + 2) * (C - D + 3) + 4 * C * D".to_string()
+            machine.issue_profile(),
+            [
+                HashMap::from([(OpKind::Ldi, 1), (OpKind::Ldr, 1), (OpKind::Sti, 1)]),
+                HashMap::new(),
+                HashMap::new(),
+                HashMap::from([(OpKind::Ldi, 1)]),
+            ]
         );
-        assert_eq!(machine.pc, 32);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut machine = Machine::new(HashMap::from([(
+            Addr(0),
+            ExprWrapper::from_symbolic_variable("A"),
+        )]));
+        assert_eq!(machine.stats(), ExecutionStats::default());
+
+        let program = Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(1), Const(1))
+                .with_ldr(Reg(2), Addr(0))
+                .with_sti(Addr(1), Const(2)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_ldi(Reg(0), Const(3)),
+        ]);
+        machine.compute(&program).unwrap();
+
+        let stats = machine.stats();
+        assert_eq!(stats.count(OpKind::Ldi), 2);
+        assert_eq!(stats.count(OpKind::Ldr), 1);
+        assert_eq!(stats.count(OpKind::Mul), 0);
+        assert_eq!(stats.busy_cycles, 3);
+        assert_eq!(stats.idle_cycles, 2);
+        assert_eq!(stats.busy_cycles + stats.idle_cycles, machine.cycle_count());
     }
 
     #[test]
@@ -504,6 +3624,38 @@ mod test {
             }));
     }
 
+    #[test]
+    fn test_register_count() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(15), Const(3)),
+            Instruction::new().with_ldi(Reg(8), Const(4)),
+            Instruction::new().with_add(Reg(0), Reg(15), Reg(8)),
+        ]);
+        let mut machine = Machine::new(HashMap::new()).with_register_count(16);
+        assert_eq!(machine.compute(&program).unwrap().strong_eval(), "7");
+
+        let machine = Machine::new(HashMap::new())
+            .with_zeroed_registers()
+            .with_register_count(16);
+        assert_eq!(machine.regs.len(), 16);
+        assert!(machine.regs.iter().all(Option::is_some));
+
+        let mut machine = Machine::new(HashMap::new()).with_register_count(12);
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::InvalidRegister {
+                reg: Reg(15),
+                pc: 0
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Register count must be at least 1")]
+    fn test_zero_register_count() {
+        let _ = Machine::new(HashMap::new()).with_register_count(0);
+    }
+
     #[test]
     fn test_uninitialized_register() {
         let mut machine = Machine::new(HashMap::new());
@@ -541,8 +3693,141 @@ mod test {
             }));
     }
 
+    #[test]
+    fn test_register_data_race_after_jump() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)).with_jmp(2),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(1), Reg(0), Reg(0)),
+            Instruction::new().with_ldi(Reg(1), Const(3)),
+        ]);
+        assert_eq!(
+            machine.compute(&program),
+            Err(ComputeError::RegisterDataRace {
+                reg: Reg(1),
+                pc: 2,
+                inst1: 2,
+                inst2: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_data_race_error_context() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_add(Reg(1), Reg(0), Reg(0)),
+            Instruction::new()
+                .with_ldi(Reg(1), Const(3))
+                .with_mul(Reg(2), Reg(0), Reg(0)),
+        ]);
+        assert_eq!(machine.last_error_context(), None);
+        assert!(machine.compute(&program).is_err());
+
+        let context = machine.last_error_context().unwrap();
+        assert_eq!(context.pc, 2);
+        let mut in_flight: Vec<_> = context
+            .in_flight
+            .iter()
+            .map(|op| (op.instruction, op.complete_by))
+            .collect();
+        in_flight.sort();
+        assert_eq!(in_flight, [(1, 3), (2, 3), (2, 12)]);
+        assert!(context.in_flight[2]
+            .output
+            .starts_with("WriteToRegister(Reg(2)"));
+
+        machine.reset_registers();
+        assert_eq!(machine.last_error_context(), None);
+    }
+
+    #[test]
+    fn test_error_context_after_jump() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)).with_jmp(2),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(1), Reg(0), Reg(0)),
+            Instruction::new().with_ldi(Reg(1), Const(3)),
+        ]);
+        assert!(machine.compute(&program).is_err());
+
+        let context = machine.last_error_context().unwrap();
+        assert_eq!(
+            context.in_flight[0],
+            InFlightRecord {
+                instruction: 2,
+                issued_at: 1,
+                complete_by: 3,
+                output: "WriteToRegister(Reg(1), `(1 + 1)`)".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_allowed_register_data_race() {
+        let mut machine = Machine::new(HashMap::new());
+        machine.allow_data_race(true);
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_add(Reg(1), Reg(0), Reg(0)),
+            Instruction::new().with_ldi(Reg(1), Const(3)),
+        ]);
+        assert!(machine.compute(&program).is_ok());
+        assert_eq!(
+            machine.races(),
+            &[RaceReport {
+                resource: RaceResource::Register(Reg(1)),
+                pc: 2,
+                inst1: 1,
+                inst2: 2
+            }]
+        );
+    }
+
     #[test]
     fn test_memory_data_race() {
-        println!("Memory data race is not possible with the current operation set");
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(1)),
+            Instruction::new().with_ldi(Reg(2), Const(2)),
+            Instruction::new()
+                .with_str(Reg(1), Addr(0))
+                .with_str(Reg(2), Addr(0)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_ldr(Reg(0), Addr(0)),
+        ]);
+
+        let mut machine = Machine::new(HashMap::new());
+        assert_eq!(
+            machine.compute(&program).unwrap_err(),
+            ComputeError::MemoryDataRace {
+                addr: Addr(0),
+                pc: 1 + Latencies::default().str,
+                inst1: 2,
+                inst2: 2,
+            }
+        );
+
+        let mut machine = Machine::new(HashMap::new());
+        machine.allow_data_race(true);
+        assert!(machine.compute(&program).is_ok());
+        assert_eq!(machine.races()[0].resource, RaceResource::Memory(Addr(0)));
+
+        // Stores to distinct addresses in one bundle do not race
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(1)),
+            Instruction::new()
+                .with_str(Reg(1), Addr(0))
+                .with_str(Reg(1), Addr(1))
+                .with_mov(Reg(0), Reg(1)),
+        ]);
+        let mut machine = Machine::new(HashMap::new());
+        machine.compute(&program).unwrap();
+        assert_eq!(machine.memory(Addr(1)).unwrap().strong_eval(), "1");
     }
 }
@@ -0,0 +1,233 @@
+use std::{collections::HashMap, ops::Range};
+
+use super::{types::Addr, ExprWrapper};
+
+/// Memory of the PEM, storing addresses below a dense capacity in a `Vec` and
+/// every other address in a `HashMap`
+///
+/// The dense capacity is `0` by default, so every address is stored sparsely.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Memory {
+    /// Values of addresses below the dense capacity, indexed by address
+    dense: Vec<Option<ExprWrapper>>,
+    /// Values of addresses at or above the dense capacity
+    sparse: HashMap<Addr, ExprWrapper>,
+}
+
+impl From<HashMap<Addr, ExprWrapper>> for Memory {
+    fn from(sparse: HashMap<Addr, ExprWrapper>) -> Self {
+        Self {
+            dense: Vec::new(),
+            sparse,
+        }
+    }
+}
+
+impl Memory {
+    /// Move the values of addresses below `capacity` into dense storage
+    ///
+    /// # Arguments
+    /// * `capacity` - number of low addresses to store densely
+    pub fn with_dense_capacity(self, capacity: u32) -> Self {
+        let mut memory = Self {
+            dense: vec![None; capacity as usize],
+            sparse: HashMap::new(),
+        };
+        memory.extend(self.into_cells());
+        memory
+    }
+
+    /// Replace every value, keeping the dense capacity
+    ///
+    /// # Arguments
+    /// * `cells` - values of the new memory
+    pub fn replace(&mut self, cells: HashMap<Addr, ExprWrapper>) {
+        self.dense.iter_mut().for_each(|value| *value = None);
+        self.sparse.clear();
+        self.extend(cells);
+    }
+
+    /// Get the value of a memory address
+    ///
+    /// # Returns
+    /// * `Some(value)` - if the memory address is initialized
+    /// * `None` - otherwise
+    pub fn get(&self, addr: &Addr) -> Option<&ExprWrapper> {
+        match self.dense.get(addr.0 as usize) {
+            Some(value) => value.as_ref(),
+            None => self.sparse.get(addr),
+        }
+    }
+
+    /// Write the value of a memory address
+    ///
+    /// # Arguments
+    /// * `addr` - memory address to write
+    /// * `value` - value to write
+    pub fn insert(&mut self, addr: Addr, value: ExprWrapper) {
+        match self.dense.get_mut(addr.0 as usize) {
+            Some(cell) => *cell = Some(value),
+            None => {
+                self.sparse.insert(addr, value);
+            }
+        }
+    }
+
+    /// Iterate over the initialized memory addresses
+    ///
+    /// # Returns
+    /// * `impl Iterator<Item = (Addr, &ExprWrapper)>` - each initialized
+    ///   memory address with its value, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Addr, &ExprWrapper)> {
+        self.dense
+            .iter()
+            .enumerate()
+            .filter_map(|(addr, value)| value.as_ref().map(|value| (Addr(addr as u32), value)))
+            .chain(self.sparse.iter().map(|(addr, value)| (*addr, value)))
+    }
+
+    /// Take every initialized memory address with its value
+    fn into_cells(self) -> impl Iterator<Item = (Addr, ExprWrapper)> {
+        self.dense
+            .into_iter()
+            .enumerate()
+            .filter_map(|(addr, value)| value.map(|value| (Addr(addr as u32), value)))
+            .chain(self.sparse)
+    }
+}
+
+impl Extend<(Addr, ExprWrapper)> for Memory {
+    fn extend<I: IntoIterator<Item = (Addr, ExprWrapper)>>(&mut self, cells: I) {
+        for (addr, value) in cells {
+            self.insert(addr, value);
+        }
+    }
+}
+
+/// Builder of startup memory for `Machine::new()`, e.g.
+/// `MemoryBuilder::new().variables_from(0..26, 'A').constant(Addr(26), 5)`
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBuilder {
+    /// Values of the memory addresses set so far
+    cells: HashMap<Addr, ExprWrapper>,
+}
+
+impl MemoryBuilder {
+    /// Create an empty `MemoryBuilder`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a memory address to a symbolic variable
+    ///
+    /// # Arguments
+    /// * `addr` - memory address to set
+    /// * `name` - name of the symbolic variable
+    pub fn var(mut self, addr: Addr, name: &str) -> Self {
+        self.cells
+            .insert(addr, ExprWrapper::from_symbolic_variable(name));
+        self
+    }
+
+    /// Set a memory address to a numeric constant
+    ///
+    /// # Arguments
+    /// * `addr` - memory address to set
+    /// * `value` - numeric constant
+    pub fn constant(mut self, addr: Addr, value: u32) -> Self {
+        self.cells.insert(addr, ExprWrapper::from(value));
+        self
+    }
+
+    /// Set a range of memory addresses to single-character symbolic variables
+    /// named in sequence, e.g. `variables_from(0..26, 'A')` sets `Addr(0)` to
+    /// `A` through `Addr(25)` to `Z`
+    ///
+    /// # Arguments
+    /// * `addrs` - memory addresses to set
+    /// * `first` - name of the variable at the first address
+    ///
+    /// # Panics
+    /// Panics if the range runs past the last valid `char`.
+    pub fn variables_from(mut self, addrs: Range<u32>, first: char) -> Self {
+        for addr in addrs.clone() {
+            let name = char::from_u32(first as u32 + (addr - addrs.start))
+                .expect("variable name out of the char range");
+            self.cells
+                .insert(Addr(addr), ExprWrapper::from_symbolic_variable(name));
+        }
+        self
+    }
+
+    /// Get the startup memory
+    ///
+    /// # Returns
+    /// * `HashMap<Addr, ExprWrapper>` - value of each memory address set
+    pub fn build(self) -> HashMap<Addr, ExprWrapper> {
+        self.cells
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_memory_builder() {
+        let memory = MemoryBuilder::new()
+            .variables_from(0..26, 'A')
+            .var(Addr(26), "rate")
+            .constant(Addr(27), 5)
+            .build();
+        assert_eq!(
+            memory,
+            HashMap::from_iter(
+                ('A'..='Z')
+                    .enumerate()
+                    .map(|(i, c)| (Addr(i as u32), ExprWrapper::from_symbolic_variable(c)))
+                    .chain([
+                        (Addr(26), ExprWrapper::from_symbolic_variable("rate")),
+                        (Addr(27), ExprWrapper::from(5)),
+                    ])
+            )
+        );
+        assert_eq!(
+            MemoryBuilder::new()
+                .variables_from(3..5, 'x')
+                .constant(Addr(4), 1)
+                .build(),
+            HashMap::from([
+                (Addr(3), ExprWrapper::from_symbolic_variable("x")),
+                (Addr(4), ExprWrapper::from(1)),
+            ])
+        );
+        assert!(MemoryBuilder::new().build().is_empty());
+    }
+
+    #[test]
+    fn test_dense_and_sparse() {
+        let mut memory = Memory::from(HashMap::from([
+            (Addr(1), ExprWrapper::from(1)),
+            (Addr(100), ExprWrapper::from(100)),
+        ]))
+        .with_dense_capacity(16);
+        assert_eq!(memory.dense.iter().flatten().count(), 1);
+        assert_eq!(memory.sparse.len(), 1);
+
+        memory.insert(Addr(15), ExprWrapper::from(15));
+        memory.insert(Addr(16), ExprWrapper::from(16));
+        assert_eq!(memory.get(&Addr(15)), Some(&ExprWrapper::from(15)));
+        assert_eq!(memory.get(&Addr(16)), Some(&ExprWrapper::from(16)));
+        assert_eq!(memory.get(&Addr(2)), None);
+        assert_eq!(memory.get(&Addr(17)), None);
+
+        let mut cells: Vec<_> = memory.iter().map(|(addr, _)| addr.0).collect();
+        cells.sort();
+        assert_eq!(cells, [1, 15, 16, 100]);
+
+        memory.replace(HashMap::from([(Addr(3), ExprWrapper::from(3))]));
+        assert_eq!(memory.iter().count(), 1);
+        assert_eq!(memory.dense.len(), 16);
+        assert_eq!(memory.get(&Addr(3)), Some(&ExprWrapper::from(3)));
+    }
+}
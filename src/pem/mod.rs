@@ -1,18 +1,41 @@
 //! Polynomial Evaluation Machine (PEM)
 
+pub mod analysis;
+mod expected_state;
 mod expr;
 mod inflight_operation;
 mod instruction;
 mod machine;
+mod memory;
+mod opcode;
+pub mod optimize;
+mod polynomial;
+mod scenario;
+pub mod sensitivity;
+mod symbol;
+pub mod timeline;
 
-pub(crate) use expr::ExprWrapper;
-pub(crate) use instruction::Instruction;
-pub(crate) use machine::Machine;
+pub use expected_state::{ExpectedState, Mismatch};
+pub use expr::{CoeffError, EvalError, ExprWrapper, RenderStyle, SexprError, WeakEvalError};
+pub use inflight_operation::Latencies;
+pub use instruction::{CanonicalInstruction, Instruction, InstructionError, Location, OpKind};
+pub use machine::{
+    ComputeError, ComputeProgress, ComputeSummary, CycleEvent, ErrorContext, ExecutionStats,
+    FlushPolicy, HazardPolicy, InFlightRecord, Machine, MachineState, RaceReport, RaceResource,
+    StepOutcome, StorePolicy, REGISTER_COUNT,
+};
+pub use memory::MemoryBuilder;
+pub use opcode::OperandKind;
+pub use polynomial::VariableOrder;
+pub use scenario::Scenario;
+pub(crate) use symbol::is_identifier;
 
 /// PEM primitive types
-pub(crate) mod types {
-    /// Register ID in range 0..=7
-    #[derive(Debug, Clone, Copy, PartialEq)]
+pub mod types {
+    /// Register ID below the register count of the machine, see
+    /// `Machine::with_register_count()`
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct Reg(pub u32);
 
     impl std::fmt::Display for Reg {
@@ -22,6 +45,7 @@ pub(crate) mod types {
     }
 
     /// Memory address in range 0..2^32
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
     pub struct Addr(pub u32);
 
@@ -32,7 +56,8 @@ pub(crate) mod types {
     }
 
     /// 32-bit numeric constant
-    #[derive(Debug, Clone, Copy)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct Const(pub u32);
 
     impl std::fmt::Display for Const {
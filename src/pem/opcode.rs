@@ -0,0 +1,312 @@
+use super::{Latencies, OpKind};
+
+/// Kind of an instruction operand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandKind {
+    Register,
+    Address,
+    Constant,
+    Label,
+}
+
+impl std::fmt::Display for OperandKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Register => write!(f, "register"),
+            Self::Address => write!(f, "memory address"),
+            Self::Constant => write!(f, "constant"),
+            Self::Label => write!(f, "label"),
+        }
+    }
+}
+
+/// Field of `Latencies` an operation kind takes its latency from
+#[derive(Debug, Clone, Copy)]
+pub(super) enum LatencyField {
+    /// The operation has its own configurable latency
+    Own(fn(&mut Latencies) -> &mut usize),
+    /// The operation shares the latency of another kind, e.g. `sqr` of `mul`
+    Shared(OpKind),
+    /// The operation takes effect at the next cycle and has no configurable
+    /// latency
+    Fixed,
+}
+
+/// Static description of an operation kind
+#[derive(Debug, Clone, Copy)]
+pub(super) struct OperationSpec {
+    pub(super) kind: OpKind,
+    /// Mnemonic the operation is written with in program source
+    pub(super) mnemonic: &'static str,
+    /// Kinds of the operands in the order they are written
+    pub(super) operands: &'static [OperandKind],
+    /// Index of the operand written when the operation completes, all other
+    /// register and address operands are read when it is issued
+    pub(super) write: Option<usize>,
+    pub(super) latency: LatencyField,
+}
+
+/// Every operation kind in the order operations of an `Instruction` are
+/// issued
+pub(super) const OPERATIONS: [OperationSpec; 24] = {
+    use OperandKind::{Address, Constant, Label, Register};
+    const BINARY: &[OperandKind] = &[Register, Register, Register];
+    const UNARY: &[OperandKind] = &[Register, Register];
+    [
+        OperationSpec {
+            kind: OpKind::Ldi,
+            mnemonic: "ldi",
+            operands: &[Register, Constant],
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.ldi),
+        },
+        OperationSpec {
+            kind: OpKind::Ldr,
+            mnemonic: "ldr",
+            operands: &[Register, Address],
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.ldr),
+        },
+        OperationSpec {
+            kind: OpKind::Str,
+            mnemonic: "str",
+            operands: &[Register, Address],
+            write: Some(1),
+            latency: LatencyField::Own(|latencies| &mut latencies.str),
+        },
+        OperationSpec {
+            kind: OpKind::Sti,
+            mnemonic: "sti",
+            operands: &[Address, Constant],
+            write: Some(0),
+            latency: LatencyField::Shared(OpKind::Str),
+        },
+        OperationSpec {
+            kind: OpKind::Add,
+            mnemonic: "add",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.add),
+        },
+        OperationSpec {
+            kind: OpKind::Sub,
+            mnemonic: "sub",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.sub),
+        },
+        OperationSpec {
+            kind: OpKind::Mul,
+            mnemonic: "mul",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.mul),
+        },
+        OperationSpec {
+            kind: OpKind::Div,
+            mnemonic: "div",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.div),
+        },
+        OperationSpec {
+            kind: OpKind::Pow,
+            mnemonic: "powr",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.pow),
+        },
+        OperationSpec {
+            kind: OpKind::Min,
+            mnemonic: "min",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.min),
+        },
+        OperationSpec {
+            kind: OpKind::Max,
+            mnemonic: "max",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.max),
+        },
+        OperationSpec {
+            kind: OpKind::And,
+            mnemonic: "and",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.and),
+        },
+        OperationSpec {
+            kind: OpKind::Or,
+            mnemonic: "or",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.or),
+        },
+        OperationSpec {
+            kind: OpKind::Xor,
+            mnemonic: "xor",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.xor),
+        },
+        OperationSpec {
+            kind: OpKind::Shl,
+            mnemonic: "shl",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.shl),
+        },
+        OperationSpec {
+            kind: OpKind::Shr,
+            mnemonic: "shr",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.shr),
+        },
+        OperationSpec {
+            kind: OpKind::Neg,
+            mnemonic: "neg",
+            operands: UNARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.neg),
+        },
+        OperationSpec {
+            kind: OpKind::Sqr,
+            mnemonic: "sqr",
+            operands: UNARY,
+            write: Some(0),
+            latency: LatencyField::Shared(OpKind::Mul),
+        },
+        OperationSpec {
+            kind: OpKind::Cmovz,
+            mnemonic: "cmovz",
+            operands: BINARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.cmovz),
+        },
+        OperationSpec {
+            kind: OpKind::Mov,
+            mnemonic: "mov",
+            operands: UNARY,
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.mov),
+        },
+        OperationSpec {
+            kind: OpKind::Clr,
+            mnemonic: "clr",
+            operands: &[Register],
+            write: Some(0),
+            latency: LatencyField::Own(|latencies| &mut latencies.clr),
+        },
+        OperationSpec {
+            kind: OpKind::Jmp,
+            mnemonic: "jmp",
+            operands: &[Label],
+            write: None,
+            latency: LatencyField::Fixed,
+        },
+        OperationSpec {
+            kind: OpKind::Jz,
+            mnemonic: "jz",
+            operands: &[Register, Label],
+            write: None,
+            latency: LatencyField::Fixed,
+        },
+        OperationSpec {
+            kind: OpKind::Halt,
+            mnemonic: "halt",
+            operands: &[],
+            write: None,
+            latency: LatencyField::Fixed,
+        },
+    ]
+};
+
+impl OpKind {
+    /// Get the static description of the operation kind
+    pub(super) fn spec(self) -> &'static OperationSpec {
+        OPERATIONS
+            .iter()
+            .find(|spec| spec.kind == self)
+            .expect("every operation kind has a spec")
+    }
+
+    /// Get the mnemonic the operation kind is written with, e.g. `ldi`
+    pub fn mnemonic(self) -> &'static str {
+        self.spec().mnemonic
+    }
+
+    /// Get the kinds of the operands the operation kind takes, in the order
+    /// they are written
+    pub fn operands(self) -> &'static [OperandKind] {
+        self.spec().operands
+    }
+
+    /// Get every operation kind in issue order
+    ///
+    /// # Returns
+    /// * `impl Iterator<Item = OpKind>` - operation kinds
+    pub fn all() -> impl Iterator<Item = OpKind> {
+        OPERATIONS.iter().map(|spec| spec.kind)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Get the mnemonic of an operation kind without the table, so adding a
+    /// kind fails to compile until it is listed here
+    fn expected_mnemonic(kind: OpKind) -> &'static str {
+        match kind {
+            OpKind::Ldi => "ldi",
+            OpKind::Ldr => "ldr",
+            OpKind::Str => "str",
+            OpKind::Add => "add",
+            OpKind::Sub => "sub",
+            OpKind::Mul => "mul",
+            OpKind::Div => "div",
+            OpKind::Pow => "powr",
+            OpKind::Min => "min",
+            OpKind::Max => "max",
+            OpKind::And => "and",
+            OpKind::Or => "or",
+            OpKind::Xor => "xor",
+            OpKind::Shl => "shl",
+            OpKind::Shr => "shr",
+            OpKind::Neg => "neg",
+            OpKind::Sqr => "sqr",
+            OpKind::Cmovz => "cmovz",
+            OpKind::Sti => "sti",
+            OpKind::Clr => "clr",
+            OpKind::Mov => "mov",
+            OpKind::Jmp => "jmp",
+            OpKind::Jz => "jz",
+            OpKind::Halt => "halt",
+        }
+    }
+
+    #[test]
+    fn test_every_kind_has_one_spec() {
+        use OpKind::*;
+        let kinds = [
+            Ldi, Ldr, Str, Add, Sub, Mul, Div, Pow, Min, Max, And, Or, Xor, Shl, Shr, Neg, Sqr,
+            Cmovz, Sti, Clr, Mov, Jmp, Jz, Halt,
+        ];
+        assert_eq!(OPERATIONS.len(), kinds.len());
+        for kind in kinds {
+            let specs: Vec<_> = OPERATIONS
+                .iter()
+                .filter(|spec| spec.kind == kind)
+                .map(|spec| spec.mnemonic)
+                .collect();
+            assert_eq!(specs, [expected_mnemonic(kind)]);
+        }
+        assert_eq!(
+            OpKind::Jz.operands(),
+            [OperandKind::Register, OperandKind::Label]
+        );
+    }
+}
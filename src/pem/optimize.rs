@@ -0,0 +1,219 @@
+//! Optimization passes rewriting PEM programs into equivalent programs
+
+use super::{analysis::straight_line, Instruction, Latencies, Location};
+
+/// Forward values stored to scratch memory directly to the registers that
+/// load them back, replacing each `ldr` with a `mov` from the stored register
+/// and dropping `str`s whose every load was forwarded
+///
+/// A load is only forwarded when the stored value is the latest write to the
+/// address visible to the `ldr`, and the stored register is not written again
+/// before the `mov` reads it. The `mov` is issued so it completes in the same
+/// cycle as the `ldr` it replaces, so the program keeps its schedule.
+///
+/// # Note
+/// Memory is assumed to be scratch, i.e. the final memory is not observed,
+/// since addresses only written by dropped `str`s are left uninitialized.
+/// Programs that jump are returned unchanged, since the forwarding relies on
+/// each instruction being issued once, at the cycle equal to its index, and
+/// instructions after the first `halt` are kept as they are, since they are
+/// never issued.
+///
+/// # Arguments
+/// * `program` - program to optimize
+/// * `latencies` - operation latencies the program is scheduled for
+///
+/// # Returns
+/// * `Vec<Instruction>` - program computing the same register values
+pub fn eliminate_scratch_memory(
+    program: &[Instruction],
+    latencies: &Latencies,
+) -> Vec<Instruction> {
+    let Ok(issued) = straight_line(program) else {
+        return program.to_vec();
+    };
+    if issued.len() < program.len() {
+        let mut optimized = eliminate_scratch_memory(issued, latencies);
+        optimized.extend_from_slice(&program[issued.len()..]);
+        return optimized;
+    }
+
+    let mut optimized = program.to_vec();
+    // Every write as `(complete_by, location)`
+    let writes: Vec<_> = program
+        .iter()
+        .enumerate()
+        .flat_map(|(pc, instruction)| {
+            instruction
+                .effects()
+                .into_iter()
+                .map(move |(kind, _, write)| (pc + latencies.of(kind), write))
+        })
+        .collect();
+    let Some(delay) = latencies.ldr.checked_sub(latencies.mov) else {
+        return optimized;
+    };
+
+    for (store_pc, store) in program.iter().enumerate() {
+        for &(src, addr) in &store.str {
+            let stored_by = store_pc + latencies.str;
+
+            let mut all_forwarded = true;
+            let mut any_forwarded = false;
+            for (load_pc, load) in program.iter().enumerate() {
+                let Some((dst, _)) = load.ldr.filter(|(_, load_addr)| *load_addr == addr) else {
+                    continue;
+                };
+
+                // The load observes the store if it is the only latest write to the
+                // address completed by the time the load is issued
+                let visible: Vec<_> = writes
+                    .iter()
+                    .filter(|(complete_by, write)| {
+                        *write == Location::Memory(addr) && *complete_by <= load_pc
+                    })
+                    .map(|(complete_by, _)| *complete_by)
+                    .collect();
+                let latest = visible.iter().max();
+                if latest != Some(&stored_by)
+                    || visible.iter().filter(|c| **c == stored_by).count() > 1
+                {
+                    continue;
+                }
+
+                let mov_pc = load_pc + delay;
+                let src_overwritten = writes.iter().any(|(complete_by, write)| {
+                    *write == Location::Register(src)
+                        && (store_pc + 1..=mov_pc).contains(complete_by)
+                });
+                if src_overwritten || optimized.get(mov_pc).is_some_and(|inst| inst.mov.is_some()) {
+                    all_forwarded = false;
+                    continue;
+                }
+
+                if optimized.len() <= mov_pc {
+                    optimized.resize_with(mov_pc + 1, Instruction::new);
+                }
+                optimized[load_pc].ldr = None;
+                optimized[mov_pc].mov = Some((dst, src));
+                any_forwarded = true;
+            }
+
+            if any_forwarded && all_forwarded {
+                optimized[store_pc]
+                    .str
+                    .retain(|store| *store != (src, addr));
+            }
+        }
+    }
+
+    optimized
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::pem::{
+        types::{Addr, Const, Reg},
+        ExprWrapper, Machine, OpKind,
+    };
+
+    use super::*;
+
+    /// Count the memory operations of a program
+    fn memory_operations(program: &[Instruction]) -> usize {
+        program
+            .iter()
+            .flat_map(Instruction::op_kinds)
+            .filter(|op| matches!(op, OpKind::Ldr | OpKind::Str | OpKind::Sti))
+            .count()
+    }
+
+    #[test]
+    fn test_eliminate_scratch_memory() {
+        let latencies = Latencies::default();
+        let wait = |cycles: usize| (1..cycles).map(|_| Instruction::new());
+
+        let mut program = Vec::from([Instruction::new()
+            .with_ldr(Reg(1), Addr(0))
+            .with_ldi(Reg(2), Const(2))]);
+        program.extend(wait(latencies.ldr));
+        program.push(Instruction::new().with_mul(Reg(3), Reg(1), Reg(2)));
+        program.extend(wait(latencies.mul));
+        // Spill `Reg(3)` to scratch memory and load it back
+        program.push(Instruction::new().with_str(Reg(3), Addr(10)));
+        program.extend(wait(latencies.str));
+        program.push(Instruction::new().with_ldr(Reg(4), Addr(10)));
+        program.extend(wait(latencies.ldr));
+        program.push(Instruction::new().with_add(Reg(0), Reg(4), Reg(1)));
+
+        let optimized = eliminate_scratch_memory(&program, &latencies);
+        assert_eq!(memory_operations(&program), 3);
+        assert_eq!(memory_operations(&optimized), 1);
+        assert_eq!(optimized.len(), program.len());
+
+        let memory = HashMap::from([(Addr(0), ExprWrapper::from_symbolic_variable("A"))]);
+        let mut machine = Machine::new(memory.clone());
+        let expected = machine.compute(&program).unwrap().clone();
+        let cycles = machine.cycle_count();
+
+        let mut machine = Machine::new(memory);
+        assert_eq!(machine.compute(&optimized), Ok(&expected));
+        assert_eq!(machine.cycle_count(), cycles);
+        assert_eq!(expected.strong_eval(), "A + A * 2");
+    }
+
+    #[test]
+    fn test_eliminate_scratch_memory_overwritten_source() {
+        let latencies = Latencies::default();
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(1)),
+            Instruction::new().with_str(Reg(1), Addr(0)),
+            Instruction::new().with_ldi(Reg(1), Const(2)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_ldr(Reg(0), Addr(0)),
+        ]);
+        let optimized = eliminate_scratch_memory(&program, &latencies);
+        assert_eq!(memory_operations(&optimized), 2);
+    }
+
+    #[test]
+    fn test_eliminate_scratch_memory_with_jumps() {
+        let latencies = Latencies::default();
+        let mut program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(1)),
+            Instruction::new().with_str(Reg(1), Addr(0)),
+        ]);
+        program.extend((1..latencies.str).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_ldr(Reg(0), Addr(0)));
+        assert_eq!(
+            memory_operations(&eliminate_scratch_memory(&program, &latencies)),
+            0
+        );
+
+        // Jumping back reissues the `ldr` after `Reg(1)` is cleared
+        program.push(Instruction::new().with_clr(Reg(1)));
+        program.push(Instruction::new().with_jz(Reg(0), 2));
+        assert_eq!(eliminate_scratch_memory(&program, &latencies), program);
+    }
+
+    #[test]
+    fn test_eliminate_scratch_memory_with_halt() {
+        let latencies = Latencies::default();
+        let mut program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(1)),
+            Instruction::new().with_str(Reg(1), Addr(0)),
+        ]);
+        program.extend((1..latencies.str).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_ldr(Reg(0), Addr(0)).with_halt());
+        // Never issued, so neither forwarded nor keeping the `str`
+        program.push(Instruction::new().with_ldr(Reg(2), Addr(0)).with_jmp(0));
+
+        let optimized = eliminate_scratch_memory(&program, &latencies);
+        assert_eq!(memory_operations(&optimized), 1);
+        assert_eq!(optimized.last(), program.last());
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    collections::BTreeMap,
+    ops::{Add, Mul, Sub},
+};
+
+/// Product of symbolic variables sorted by name, with a variable repeated for
+/// each power, e.g. `[A, A, B]` for `A * A * B`
+pub(super) type Monomial = Vec<String>;
+
+/// Ordering of symbolic variables when rendering expanded polynomials
+///
+/// # Variants
+/// * `Lexicographic` - variables ordered by name
+/// * `Custom(order)` - variables ordered by their position in `order`, with
+///   unlisted variables after all listed ones, ordered by name
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum VariableOrder {
+    #[default]
+    Lexicographic,
+    Custom(Vec<String>),
+}
+
+impl VariableOrder {
+    /// Get the sort key of a symbolic variable
+    fn key<'a>(&self, variable: &'a str) -> (usize, &'a str) {
+        match self {
+            Self::Lexicographic => (0, variable),
+            Self::Custom(order) => (
+                order
+                    .iter()
+                    .position(|v| v == variable)
+                    .unwrap_or(order.len()),
+                variable,
+            ),
+        }
+    }
+}
+
+/// Polynomial expanded into a sum of monomials with 32-bit coefficients, with
+/// overflows handled by wrap-around
+///
+/// Monomials with a zero coefficient are never stored, so two equivalent
+/// polynomials always have equal terms.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct Polynomial(BTreeMap<Monomial, u32>);
+
+impl Polynomial {
+    /// Create a polynomial of a numeric constant
+    pub fn constant(value: u32) -> Self {
+        let mut polynomial = Self::default();
+        polynomial.add_term(Monomial::new(), value);
+        polynomial
+    }
+
+    /// Create a polynomial of a single symbolic variable
+    pub fn variable(name: &str) -> Self {
+        let mut polynomial = Self::default();
+        polynomial.add_term(Monomial::from([name.to_string()]), 1);
+        polynomial
+    }
+
+    /// Add a coefficient to a monomial, removing it if the coefficient wraps
+    /// around to zero
+    fn add_term(&mut self, monomial: Monomial, coefficient: u32) {
+        let coefficient = self
+            .0
+            .get(&monomial)
+            .map_or(coefficient, |c| c.wrapping_add(coefficient));
+        if coefficient == 0 {
+            self.0.remove(&monomial);
+        } else {
+            self.0.insert(monomial, coefficient);
+        }
+    }
+
+    /// Iterate over the monomials and their coefficients in monomial order
+    pub fn terms(&self) -> impl Iterator<Item = (&Monomial, u32)> {
+        self.0
+            .iter()
+            .map(|(monomial, coefficient)| (monomial, *coefficient))
+    }
+
+    /// Render the polynomial as a sum of terms, with the variables of each
+    /// monomial and the monomials themselves ordered by `order`, and the
+    /// constant term last
+    ///
+    /// # Arguments
+    /// * `order` - ordering of symbolic variables
+    pub fn render(&self, order: &VariableOrder) -> String {
+        let mut terms: Vec<_> = self
+            .terms()
+            .map(|(monomial, coefficient)| {
+                let mut variables: Vec<_> = monomial.iter().map(|v| order.key(v)).collect();
+                variables.sort();
+                (monomial.is_empty(), variables, coefficient)
+            })
+            .collect();
+        terms.sort();
+
+        let terms: Vec<_> = terms
+            .into_iter()
+            .map(|(_, variables, coefficient)| {
+                let variables = variables.into_iter().map(|(_, variable)| variable);
+                match (coefficient, variables.len()) {
+                    (_, 0) => coefficient.to_string(),
+                    (1, _) => variables.collect::<Vec<_>>().join(" * "),
+                    _ => std::iter::once(coefficient.to_string())
+                        .chain(variables.map(str::to_string))
+                        .collect::<Vec<_>>()
+                        .join(" * "),
+                }
+            })
+            .collect();
+
+        if terms.is_empty() {
+            "0".to_string()
+        } else {
+            terms.join(" + ")
+        }
+    }
+
+    /// Get the value of a constant polynomial
+    ///
+    /// # Returns
+    /// * `Some(u32)` - if the polynomial has no symbolic variables
+    /// * `None` - otherwise
+    pub fn as_const(&self) -> Option<u32> {
+        match self.degree() {
+            0 => Some(self.0.get(&Monomial::new()).copied().unwrap_or(0)),
+            _ => None,
+        }
+    }
+
+    /// Get the total degree of the polynomial, `0` for constants
+    pub fn degree(&self) -> usize {
+        self.0.keys().map(Vec::len).max().unwrap_or(0)
+    }
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut sum = self.clone();
+        for (monomial, coefficient) in rhs.terms() {
+            sum.add_term(monomial.clone(), coefficient);
+        }
+        sum
+    }
+}
+
+impl Sub for &Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let mut difference = self.clone();
+        for (monomial, coefficient) in rhs.terms() {
+            difference.add_term(monomial.clone(), coefficient.wrapping_neg());
+        }
+        difference
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut product = Polynomial::default();
+        for (lhs_monomial, lhs_coefficient) in self.terms() {
+            for (rhs_monomial, rhs_coefficient) in rhs.terms() {
+                let mut monomial = lhs_monomial.clone();
+                monomial.extend(rhs_monomial.iter().cloned());
+                monomial.sort();
+                product.add_term(monomial, lhs_coefficient.wrapping_mul(rhs_coefficient));
+            }
+        }
+        product
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn monomial(variables: &[&str]) -> Monomial {
+        variables.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_polynomial_arithmetic() {
+        let a = Polynomial::variable("A");
+        let b = Polynomial::variable("B");
+        let one = Polynomial::constant(1);
+
+        // (A + 1) * (A + B) = A * A + A * B + A + B
+        let product = &(&a + &one) * &(&a + &b);
+        assert_eq!(
+            product
+                .terms()
+                .map(|(monomial, coefficient)| (monomial.clone(), coefficient))
+                .collect::<Vec<_>>(),
+            Vec::from([
+                (monomial(&["A"]), 1),
+                (monomial(&["A", "A"]), 1),
+                (monomial(&["A", "B"]), 1),
+                (monomial(&["B"]), 1),
+            ])
+        );
+        assert_eq!(product.degree(), 2);
+        assert_eq!(&product - &product, Polynomial::default());
+    }
+
+    #[test]
+    fn test_polynomial_render() {
+        let a = Polynomial::variable("A");
+        let b = Polynomial::variable("B");
+
+        let polynomial =
+            &(&(&a * &a) + &(&Polynomial::constant(3) * &b)) + &Polynomial::constant(2);
+        assert_eq!(
+            polynomial.render(&VariableOrder::default()),
+            "A * A + 3 * B + 2"
+        );
+        assert_eq!(Polynomial::default().render(&VariableOrder::default()), "0");
+    }
+
+    #[test]
+    fn test_polynomial_wraparound() {
+        assert_eq!(Polynomial::constant(0), Polynomial::default());
+        assert_eq!(
+            &Polynomial::constant(u32::MAX) + &Polynomial::constant(1),
+            Polynomial::default()
+        );
+        assert_eq!(
+            &Polynomial::constant(0) - &Polynomial::variable("A"),
+            &Polynomial::constant(u32::MAX) * &Polynomial::variable("A")
+        );
+        assert_eq!(Polynomial::constant(7).degree(), 0);
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use super::{
+    types::{Addr, Reg},
+    ComputeError, ExprWrapper, FlushPolicy, HazardPolicy, Instruction, Latencies, Machine, OpKind,
+    StorePolicy,
+};
+
+/// Configuration of the machine a scenario was captured on
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct MachineConfig {
+    pub(super) register_count: usize,
+    pub(super) result_register: Reg,
+    pub(super) zeroed_registers: bool,
+    pub(super) latencies: Latencies,
+    pub(super) flush_policy: FlushPolicy,
+    pub(super) hazard_policy: HazardPolicy,
+    pub(super) store_policy: StorePolicy,
+    pub(super) eager_fold: bool,
+    pub(super) checked_arithmetic: bool,
+    pub(super) bypass: bool,
+    /// Permitted opcodes sorted by mnemonic, or `None` if every opcode is
+    /// permitted
+    pub(super) permitted_opcodes: Option<Vec<OpKind>>,
+    pub(super) allow_data_race: bool,
+    pub(super) detect_write_after_write: bool,
+    pub(super) max_cycles: usize,
+}
+
+impl MachineConfig {
+    /// Create a new machine with this configuration
+    ///
+    /// # Arguments
+    /// * `mem` - memory to initialize with
+    fn machine(&self, mem: HashMap<Addr, ExprWrapper>) -> Machine {
+        let mut machine = Machine::new(mem)
+            .with_register_count(self.register_count)
+            .with_result_register(self.result_register)
+            .with_latencies(self.latencies)
+            .with_flush_policy(self.flush_policy)
+            .with_hazard_policy(self.hazard_policy)
+            .with_store_policy(self.store_policy)
+            .with_checked_arithmetic(self.checked_arithmetic)
+            .with_max_cycles(self.max_cycles);
+        if self.zeroed_registers {
+            machine = machine.with_zeroed_registers();
+        }
+        if let Some(opcodes) = &self.permitted_opcodes {
+            machine = machine.with_permitted_opcodes(opcodes.iter().copied());
+        }
+        machine.eager_fold(self.eager_fold);
+        machine.bypass(self.bypass);
+        machine.allow_data_race(self.allow_data_race);
+        machine.detect_write_after_write(self.detect_write_after_write);
+        machine
+    }
+}
+
+/// A replayable execution bundling a program with the startup memory and
+/// configuration of the machine it ran on
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scenario {
+    /// Program to execute
+    program: Vec<Instruction>,
+    /// Startup memory sorted by address
+    memory: Vec<(Addr, ExprWrapper)>,
+    /// Configuration of the machine
+    config: MachineConfig,
+}
+
+impl Scenario {
+    /// Create a new `Scenario`
+    ///
+    /// # Arguments
+    /// * `program` - program to execute
+    /// * `memory` - startup memory
+    /// * `config` - configuration of the machine
+    pub(super) fn new<'a>(
+        program: &[Instruction],
+        memory: impl Iterator<Item = (Addr, &'a ExprWrapper)>,
+        config: MachineConfig,
+    ) -> Self {
+        let mut memory = Vec::from_iter(memory.map(|(addr, value)| (addr, value.clone())));
+        memory.sort_by_key(|(addr, _)| addr.0);
+
+        Self {
+            program: program.to_vec(),
+            memory,
+            config,
+        }
+    }
+
+    /// Replay the scenario on a new machine
+    ///
+    /// # Returns
+    /// * `Ok(String)` - weakly evaluated result of the program
+    /// * `Err(ComputeError)` - if the program terminated with an error
+    pub fn replay(&self) -> Result<String, ComputeError> {
+        let mut machine = self
+            .config
+            .machine(HashMap::from_iter(self.memory.iter().cloned()));
+        machine.compute(&self.program).map(ExprWrapper::weak_eval)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pem::{
+        types::{Const, Reg},
+        MemoryBuilder, OpKind,
+    };
+
+    use super::*;
+
+    fn example_machine() -> Machine {
+        Machine::new(MemoryBuilder::new().variables_from(0..26, 'A').build())
+    }
+
+    fn example_program() -> Vec<Instruction> {
+        Vec::from([
+            Instruction::new()
+                .with_ldi(Reg(0), Const(1))
+                .with_ldr(Reg(1), Addr(0)),
+            Instruction::new()
+                .with_ldi(Reg(2), Const(2))
+                .with_ldr(Reg(3), Addr(1)),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new(),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+            Instruction::new().with_add(Reg(2), Reg(2), Reg(3)),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(0), Reg(0), Reg(2)),
+        ])
+    }
+
+    #[test]
+    fn test_replay_result() {
+        let program = example_program();
+        let mut machine = example_machine();
+        let scenario = machine.capture_scenario(&program);
+        let expected = machine.compute(&program).unwrap().weak_eval();
+        assert_eq!(scenario.replay(), Ok(expected));
+        assert_eq!(scenario.replay(), scenario.replay());
+    }
+
+    #[test]
+    fn test_replay_error() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_add(Reg(1), Reg(0), Reg(0)),
+            Instruction::new().with_ldi(Reg(1), Const(3)),
+        ]);
+        let mut machine = Machine::new(HashMap::new());
+        let scenario = machine.capture_scenario(&program);
+        let expected = machine.compute(&program).map(ExprWrapper::weak_eval);
+        assert!(expected.is_err());
+        assert_eq!(scenario.replay(), expected);
+
+        machine = Machine::new(HashMap::new());
+        machine.allow_data_race(true);
+        assert_eq!(
+            machine.capture_scenario(&program).replay(),
+            Ok("1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_replay_latencies() {
+        let program = example_program();
+        let latencies = Latencies::default().with(OpKind::Ldr, 6);
+        let mut machine = example_machine().with_latencies(latencies);
+        let scenario = machine.capture_scenario(&program);
+        // Loads complete a cycle after the `add`s read their results
+        let expected = machine.compute(&program).map(ExprWrapper::weak_eval);
+        assert!(expected.is_err());
+        assert_eq!(scenario.replay(), expected);
+        assert_ne!(
+            scenario.replay(),
+            example_machine().capture_scenario(&program).replay()
+        );
+    }
+
+    #[test]
+    fn test_replay_config() {
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(9), Const(2)),
+            Instruction::new().with_add(Reg(9), Reg(9), Reg(1)),
+        ]);
+        let mut machine = Machine::new(HashMap::new())
+            .with_register_count(16)
+            .with_result_register(Reg(9))
+            .with_zeroed_registers()
+            .with_hazard_policy(HazardPolicy::Stall)
+            .with_permitted_opcodes([OpKind::Ldi, OpKind::Add]);
+        let scenario = machine.capture_scenario(&program);
+        let expected = machine.compute(&program).map(ExprWrapper::weak_eval);
+        assert_eq!(expected, Ok("(0 + 2)".to_string()));
+        assert_eq!(scenario.replay(), expected);
+
+        machine = Machine::new(HashMap::new())
+            .with_register_count(16)
+            .with_permitted_opcodes([OpKind::Ldi]);
+        assert_eq!(
+            machine.capture_scenario(&program).replay(),
+            Err(ComputeError::OpcodeNotPermitted {
+                op: OpKind::Add,
+                pc: 1
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let program = example_program();
+        let scenario = example_machine().capture_scenario(&program);
+        let json = serde_json::to_string(&scenario).unwrap();
+        let deserialized: Scenario = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.replay(), scenario.replay());
+        assert_eq!(serde_json::to_string(&deserialized).unwrap(), json);
+    }
+}
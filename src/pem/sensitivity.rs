@@ -0,0 +1,137 @@
+//! Robustness analysis of PEM schedules under perturbed operation latencies
+
+use std::collections::HashMap;
+
+use super::{
+    opcode::{LatencyField, OPERATIONS},
+    types::Addr,
+    ComputeError, ExprWrapper, Instruction, Latencies, Machine, OpKind, RaceReport,
+};
+
+/// A latency perturbation that changed how a program executes
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyPerturbation {
+    /// Operation kind whose latency was perturbed
+    pub op: OpKind,
+    /// Change applied to the latency of `op`, `-1` or `1`
+    pub delta: isize,
+    /// Perturbed latencies the program ran with
+    pub latencies: Latencies,
+    /// Data races introduced by the perturbation
+    pub races: Vec<RaceReport>,
+    /// Number of cycles the program took, or the error it terminated with
+    pub outcome: Result<usize, ComputeError>,
+}
+
+/// Run a program on a machine with the given latencies, allowing data races
+/// so they can be reported
+///
+/// # Returns
+/// * `(races, outcome)` - races that occurred, and the number of cycles or
+///   the error the program terminated with
+fn run(
+    program: &[Instruction],
+    memory: &HashMap<Addr, ExprWrapper>,
+    latencies: Latencies,
+) -> (Vec<RaceReport>, Result<usize, ComputeError>) {
+    let mut machine = Machine::new(memory.clone()).with_latencies(latencies);
+    machine.allow_data_race(true);
+    let outcome = machine.compute(program).map(|_| ());
+    (
+        machine.races().to_vec(),
+        outcome.map(|_| machine.cycle_count()),
+    )
+}
+
+/// Find the single-cycle latency perturbations a schedule is sensitive to by
+/// rerunning the program with each operation latency shortened and lengthened
+/// by one cycle
+///
+/// # Arguments
+/// * `program` - program to analyse
+/// * `memory` - startup memory to run the program with
+/// * `base_latencies` - latencies the program was scheduled for
+///
+/// # Returns
+/// * `Vec<LatencyPerturbation>` - perturbations that introduce a data race,
+///   change the cycle count or make the program fail, in operation kind order
+///   with shortened latencies first
+pub fn latency_sensitivity(
+    program: &[Instruction],
+    memory: &HashMap<Addr, ExprWrapper>,
+    base_latencies: &Latencies,
+) -> Vec<LatencyPerturbation> {
+    let (_, base_outcome) = run(program, memory, *base_latencies);
+
+    let mut perturbations = Vec::new();
+    // Kinds sharing the latency of another kind, e.g. `sti`, are covered by it
+    let perturbed_ops = OPERATIONS
+        .iter()
+        .filter(|spec| matches!(spec.latency, LatencyField::Own(_)))
+        .map(|spec| spec.kind);
+    for op in perturbed_ops {
+        let latency = base_latencies.of(op);
+        for delta in [-1isize, 1] {
+            // Operations must complete after the cycle they are issued at
+            let Some(perturbed) = latency.checked_add_signed(delta).filter(|l| *l > 0) else {
+                continue;
+            };
+
+            let latencies = base_latencies.with(op, perturbed);
+            let (races, outcome) = run(program, memory, latencies);
+            if !races.is_empty() || outcome != base_outcome {
+                perturbations.push(LatencyPerturbation {
+                    op,
+                    delta,
+                    latencies,
+                    races,
+                    outcome,
+                });
+            }
+        }
+    }
+    perturbations
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pem::{
+        types::{Const, Reg},
+        RaceResource,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_latency_sensitivity() {
+        let mut program = Vec::from([
+            Instruction::new().with_ldi(Reg(1), Const(3)),
+            Instruction::new(),
+            Instruction::new().with_mul(Reg(0), Reg(1), Reg(1)),
+        ]);
+        program.extend((3..10).map(|_| Instruction::new()));
+        program.push(Instruction::new().with_ldi(Reg(0), Const(5)));
+
+        let latencies = Latencies::default();
+        let perturbations = latency_sensitivity(&program, &HashMap::new(), &latencies);
+        let summary: Vec<_> = perturbations
+            .iter()
+            .map(|p| (p.op, p.delta, !p.races.is_empty(), p.outcome.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            Vec::from([
+                (OpKind::Ldi, 1, true, Ok(12)),
+                (OpKind::Mul, -1, true, Ok(11)),
+                (OpKind::Mul, 1, false, Ok(13)),
+            ])
+        );
+
+        let shortened_mul = &perturbations[1];
+        assert_eq!(shortened_mul.latencies, latencies.with(OpKind::Mul, 9));
+        assert_eq!(
+            shortened_mul.races[0].resource,
+            RaceResource::Register(Reg(0))
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+thread_local! {
+    /// Symbolic variable names interned so far
+    static SYMBOLS: RefCell<HashSet<Rc<str>>> = RefCell::new(HashSet::new());
+}
+
+/// Symbolic variable name interned in a pool, so every occurrence of a name
+/// shares one allocation and symbols are compared by pointer rather than by
+/// string
+///
+/// # Note
+/// The pool is per thread, which is sound since `Rc` cannot cross threads, and
+/// an interned name is freed once its last symbol is dropped.
+#[derive(Clone)]
+pub(super) struct Symbol(Rc<str>);
+
+impl Symbol {
+    /// Get the symbol of a name, interning it if it is new
+    ///
+    /// # Arguments
+    /// * `name` - symbolic variable name
+    pub fn intern(name: &str) -> Self {
+        SYMBOLS.with(|symbols| {
+            let mut symbols = symbols.borrow_mut();
+            if let Some(symbol) = symbols.get(name) {
+                return Self(Rc::clone(symbol));
+            }
+            let symbol: Rc<str> = Rc::from(name);
+            symbols.insert(Rc::clone(&symbol));
+            Self(symbol)
+        })
+    }
+
+    /// Get the name of the symbol
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for Symbol {
+    fn drop(&mut self) {
+        // The pool holds the only other reference once the last symbol goes.
+        // The pool may already be gone while the thread is shutting down
+        if Rc::strong_count(&self.0) == 2 {
+            let _ = SYMBOLS.try_with(|symbols| {
+                if let Ok(mut symbols) = symbols.try_borrow_mut() {
+                    symbols.remove(&*self.0);
+                }
+            });
+        }
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(Rc::as_ptr(&self.0), state)
+    }
+}
+
+impl std::fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+/// Check whether a name is an identifier starting with a letter or `_`
+/// followed by letters, digits or `_`, as used by named constants and
+/// symbolic variables
+///
+/// # Arguments
+/// * `name` - name to check
+pub(crate) fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Quotes a name that is not an identifier, e.g. `"complex name"`, so that
+/// rendered expressions can be parsed back unambiguously
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if is_identifier(self.as_str()) {
+            write!(f, "{}", self.as_str())
+        } else {
+            write!(f, "\"{}\"", self.as_str())
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Symbol {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Symbol {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = <String as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(Self::intern(&name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let a = Symbol::intern("A");
+        let other_a = Symbol::intern(&String::from("A"));
+        assert!(Rc::ptr_eq(&a.0, &other_a.0));
+        assert_eq!(a, other_a);
+        assert_ne!(a, Symbol::intern("B"));
+        assert_eq!(a.to_string(), "A");
+        assert_eq!(format!("{:?}", a), "\"A\"");
+    }
+
+    #[test]
+    fn test_intern_frees_unused_names() {
+        let interned = |name: &str| SYMBOLS.with(|symbols| symbols.borrow().contains(name));
+        let symbol = Symbol::intern("unused");
+        let clone = symbol.clone();
+        drop(symbol);
+        assert!(interned("unused"));
+        drop(clone);
+        assert!(!interned("unused"));
+
+        let symbol = Symbol::intern("unused");
+        assert!(interned("unused"));
+        assert_eq!(symbol.as_str(), "unused");
+    }
+
+    #[test]
+    fn test_display_quotes_non_identifiers() {
+        assert_eq!(Symbol::intern("_tmp1").to_string(), "_tmp1");
+        assert_eq!(
+            Symbol::intern("complex name").to_string(),
+            "\"complex name\""
+        );
+        assert_eq!(Symbol::intern("1A").to_string(), "\"1A\"");
+        assert!(!is_identifier(""));
+    }
+}
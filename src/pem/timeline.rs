@@ -0,0 +1,102 @@
+//! Rendering of the operations retired by a `Machine` as a cycle timeline
+
+/// An operation retired by a `Machine`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationRecord {
+    /// Instruction that originated the operation
+    pub instruction: usize,
+    /// Cycle the operation was issued at
+    pub issued_at: usize,
+    /// Cycle the operation retired at
+    pub retired_at: usize,
+    /// Description of the operation output
+    pub output: String,
+}
+
+/// How cycles are numbered when rendering a timeline
+///
+/// # Variants
+/// * `Absolute` - cycles counted from the start of the program
+/// * `RelativeToEnd` - cycles counted from the final retirement, so the last
+///   operation retires at cycle `0` and earlier cycles are negative
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineCycles {
+    #[default]
+    Absolute,
+    RelativeToEnd,
+}
+
+/// Render operation records as a timeline with one
+/// `<issued>..<retired> #<instruction> <output>` line per operation
+///
+/// # Arguments
+/// * `records` - operations in retire order
+/// * `cycles` - how cycles are numbered
+///
+/// # Returns
+/// * `String` - rendered timeline
+pub fn render_timeline(records: &[OperationRecord], cycles: TimelineCycles) -> String {
+    let origin = match cycles {
+        TimelineCycles::Absolute => 0,
+        TimelineCycles::RelativeToEnd => records
+            .iter()
+            .map(|record| record.retired_at)
+            .max()
+            .unwrap_or_default(),
+    } as isize;
+
+    records
+        .iter()
+        .map(|record| {
+            format!(
+                "{}..{} #{} {}",
+                record.issued_at as isize - origin,
+                record.retired_at as isize - origin,
+                record.instruction,
+                record.output
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::pem::{
+        types::{Const, Reg},
+        Instruction, Machine,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_render_timeline_relative_to_end() {
+        let mut machine = Machine::new(HashMap::new());
+        let program = Vec::from([
+            Instruction::new().with_ldi(Reg(0), Const(1)),
+            Instruction::new().with_ldi(Reg(1), Const(8)),
+            Instruction::new().with_add(Reg(0), Reg(0), Reg(1)),
+        ]);
+        machine.compute(&program).unwrap();
+        let records = machine.operation_records();
+
+        assert_eq!(
+            render_timeline(&records, TimelineCycles::Absolute),
+            [
+                "0..0 #0 WriteToRegister(Reg(0), `1`)",
+                "1..1 #1 WriteToRegister(Reg(1), `8`)",
+                "2..3 #2 WriteToRegister(Reg(0), `(8 + 1)`)",
+            ]
+            .join("\n")
+        );
+
+        let timeline = render_timeline(&records, TimelineCycles::RelativeToEnd);
+        assert_eq!(
+            timeline.lines().last(),
+            Some("-1..0 #2 WriteToRegister(Reg(0), `(8 + 1)`)")
+        );
+        assert!(timeline.starts_with("-3..-3 #0"));
+    }
+}